@@ -0,0 +1,139 @@
+//! Discovery of the DVB adapters available on the system.
+//!
+//! Each adapter is a directory like `/dev/dvb/adapterX`, holding its `frontendX` and `demuxX` devices.
+
+use std::{
+    collections::BTreeSet,
+    fs::read_dir,
+    path::{Path, PathBuf},
+};
+
+use crate::frontend::{DeliverySystem, Frontend, Info};
+
+/// Directory holding all DVB adapters
+pub const DVB_DIRECTORY: &str = "/dev/dvb";
+
+/// Canonical path of a frontend, like `/dev/dvb/adapter0/frontend0`.
+pub fn frontend_path(adapter: u32, frontend: u32) -> PathBuf {
+    adapter_path(adapter).join(format!("frontend{}", frontend))
+}
+
+/// Canonical path of a demux, like `/dev/dvb/adapter0/demux0`.
+pub fn demux_path(adapter: u32, demux: u32) -> PathBuf {
+    adapter_path(adapter).join(format!("demux{}", demux))
+}
+
+fn adapter_path(adapter: u32) -> PathBuf {
+    Path::new(DVB_DIRECTORY).join(format!("adapter{}", adapter))
+}
+
+//
+// -----
+
+/// Devices found for a single adapter, see [`list_adapters`].
+#[derive(Debug, Clone)]
+pub struct AdapterInfo {
+    pub adapter: u32,
+    pub frontends: Vec<u32>,
+    pub demuxes: Vec<u32>,
+    /// Name reported by the first frontend, unset if it couldn't be opened.
+    pub name: Option<String>,
+}
+
+/// List all adapters under `/dev/dvb`, with the indices of their frontends and demuxes.
+///
+/// Returns an empty list if there is no DVB device at all.
+pub fn list_adapters() -> Vec<AdapterInfo> {
+    let mut adapters = indices(Path::new(DVB_DIRECTORY), "adapter")
+        .into_iter()
+        .map(|adapter| {
+            let path = adapter_path(adapter);
+            let frontends = indices(&path, "frontend");
+            let demuxes = indices(&path, "demux");
+            let name = frontends.first().and_then(|&frontend| {
+                Frontend::open_adapter(adapter, frontend, false)
+                    .ok()
+                    .map(|f| f.info().name.clone())
+            });
+            AdapterInfo {
+                adapter,
+                frontends,
+                demuxes,
+                name,
+            }
+        })
+        .collect::<Vec<_>>();
+    adapters.sort_by_key(|a| a.adapter);
+    adapters
+}
+
+/// A single frontend with everything needed to choose it, see [`enumerate`].
+#[derive(Debug, Clone)]
+pub struct AdapterSummary {
+    pub adapter: u32,
+    pub frontend: u32,
+    pub info: Info,
+    pub systems: BTreeSet<DeliverySystem>,
+}
+
+/// Open every frontend under `/dev/dvb` read-only, to get its information and supported delivery systems.
+///
+/// Frontends that can't be queried, like busy ones, are left out.
+pub fn enumerate() -> Vec<AdapterSummary> {
+    let mut summaries = Vec::new();
+    for adapter in list_adapters() {
+        for &frontend_index in &adapter.frontends {
+            let Ok(mut frontend) = Frontend::open_adapter(adapter.adapter, frontend_index, false)
+            else {
+                continue;
+            };
+            let Ok(systems) = frontend.list_systems() else {
+                continue;
+            };
+            summaries.push(AdapterSummary {
+                adapter: adapter.adapter,
+                frontend: frontend_index,
+                info: frontend.info().clone(),
+                systems,
+            });
+        }
+    }
+    summaries
+}
+
+/// Sorted indices of all entries in `directory` named like `{prefix}X`.
+fn indices(directory: &Path, prefix: &str) -> Vec<u32> {
+    let Ok(entries) = read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut indices = entries
+        .flatten()
+        .filter_map(|entry| parse_index(entry.file_name().to_str()?, prefix))
+        .collect::<Vec<_>>();
+    indices.sort();
+    indices
+}
+
+fn parse_index(name: &str, prefix: &str) -> Option<u32> {
+    name.strip_prefix(prefix)?.parse().ok()
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths() {
+        assert_eq!(
+            frontend_path(1, 0),
+            Path::new("/dev/dvb/adapter1/frontend0")
+        );
+        assert_eq!(demux_path(0, 2), Path::new("/dev/dvb/adapter0/demux2"));
+        assert_eq!(parse_index("adapter12", "adapter"), Some(12));
+        assert_eq!(parse_index("frontend0", "demux"), None);
+        assert_eq!(parse_index("demux", "demux"), None);
+    }
+}
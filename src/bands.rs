@@ -4,15 +4,22 @@
 //! This allows scanning the most likely frequencies where a transponder could be found at.
 //!
 //! A user would probably want to choose their current country's parameters to get correct results while scanning.
+//!
+//! Satellites don't follow a raster, so a list of known [SatelliteTransponder]s is used instead.
 
-use crate::frontend::properties::set::BandwidthHz;
-
-// TODO: A way to chain multiple systems, like VHF then UHF, not just `.chain()` when scanning, to have a const here
+use crate::{
+    conf::vdr::parameters::Polarization,
+    frontend::{
+        DeliverySystem,
+        properties::set::{BandwidthHz, Tone, Voltage},
+    },
+};
 
 // https://en.wikipedia.org/wiki/Band_IV
 // https://en.wikipedia.org/wiki/Band_V
 // https://www.tvnt.net/forum/tableau-de-conversion-des-canaux-uhf-en-frequences-t23059.html
 // https://fr.wikipedia.org/wiki/Bandes_de_fr%C3%A9quences_de_la_t%C3%A9l%C3%A9vision_terrestre
+// https://en.wikipedia.org/wiki/Television_channel_frequencies
 
 //
 // -----
@@ -91,6 +98,124 @@ impl<'a> Iterator for FrequencyIter<'a> {
     }
 }
 
+/// Ordered set of bands to scan one after the other, like VHF then UHF.
+#[derive(Clone, Debug)]
+pub struct BandPlan {
+    pub bands: Vec<BroadcastBand>,
+}
+
+impl BandPlan {
+    pub fn new(bands: &[BroadcastBand]) -> BandPlan {
+        BandPlan {
+            bands: bands.to_vec(),
+        }
+    }
+
+    /// Return the usual plan for DVB-T in a given region
+    pub fn from_region(region: Region) -> BandPlan {
+        match region {
+            Region::Australia => BandPlan::new(AUSTRALIA_DVBT),
+            Region::France => BandPlan::new(FRANCE_DVBT),
+            Region::Germany => BandPlan::new(GERMANY_DVBT),
+            Region::Italy => BandPlan::new(ITALY_DVBT),
+            Region::UnitedKingdom => BandPlan::new(UK_DVBT),
+            Region::UnitedStates => BandPlan::new(US_ATSC),
+        }
+    }
+
+    /// Return the amount of channels in all bands
+    pub fn channel_count(&self) -> u32 {
+        self.bands.iter().map(|b| b.channel_count()).sum()
+    }
+
+    /// Iterate over all frequencies of all bands, in order
+    pub fn iter(&self) -> BandPlanIter<'_> {
+        BandPlanIter {
+            bands: self.bands.iter(),
+            current: None,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a BandPlan {
+    type Item = ChannelParameters;
+    type IntoIter = BandPlanIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator for frequencies across multiple bands. This is used by [BandPlan::iter].
+pub struct BandPlanIter<'a> {
+    bands: std::slice::Iter<'a, BroadcastBand>,
+    current: Option<FrequencyIter<'a>>,
+}
+
+impl<'a> Iterator for BandPlanIter<'a> {
+    type Item = ChannelParameters;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(channel) = self.current.as_mut().and_then(|c| c.next()) {
+                return Some(channel);
+            }
+            // Current band is exhausted, move on to the next one
+            self.current = Some(self.bands.next()?.iter());
+        }
+    }
+}
+
+/// Regions for which a [BandPlan] is known
+///
+/// For other regions, build a [BandPlan] from the bands of a neighbouring one (or [EUROPE_DVBT]) and give it to [scan_system](crate::scan::scan_system) directly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Region {
+    Australia,
+    France,
+    Germany,
+    Italy,
+    UnitedKingdom,
+    /// ATSC isn't supported, so its [ScanProfile] has no delivery system
+    UnitedStates,
+}
+
+impl Region {
+    /// Return everything needed to scan for terrestrial channels in this region, see [scan_region](crate::scan::scan_region).
+    pub fn scan_profile(self) -> ScanProfile {
+        let systems: &'static [DeliverySystem] = match self {
+            Region::Australia => &[DeliverySystem::DvbT],
+            // DVB-T was switched off
+            Region::Germany => &[DeliverySystem::DvbT2],
+            Region::France | Region::Italy | Region::UnitedKingdom => {
+                &[DeliverySystem::DvbT, DeliverySystem::DvbT2]
+            }
+            Region::UnitedStates => &[],
+        };
+        let bandwidth = match self {
+            Region::Australia => BandwidthHz::_7MHz,
+            Region::UnitedStates => BandwidthHz::_6MHz,
+            _ => BandwidthHz::_8MHz,
+        };
+
+        ScanProfile {
+            band_plan: BandPlan::from_region(self),
+            systems,
+            bandwidth,
+        }
+    }
+}
+
+/// Defaults for scanning the terrestrial channels of a [Region]
+#[derive(Clone, Debug)]
+pub struct ScanProfile {
+    pub band_plan: BandPlan,
+    /// Delivery systems to try on each frequency, in order
+    pub systems: &'static [DeliverySystem],
+    /// Bandwidth of most channels, useful to tune to a frequency outside of the plan
+    pub bandwidth: BandwidthHz,
+}
+
 //
 // -----
 
@@ -122,6 +247,191 @@ pub const FRANCE_UHF: BroadcastBand = BroadcastBand {
     ..EUROPE_UHF_BAND_IV_V
 };
 
+// --- Germany
+
+/// Channels above 48 were given to mobile networks (700MHz band)
+pub const GERMANY_UHF: BroadcastBand = BroadcastBand {
+    last_channel: 48,
+    ..EUROPE_UHF_BAND_IV_V
+};
+
+// --- Italy
+
+/// Italian VHF uses its own lettered raster, only UHF is defined here
+pub const ITALY_UHF: BroadcastBand = BroadcastBand {
+    last_channel: 48,
+    ..EUROPE_UHF_BAND_IV_V
+};
+
+// --- United Kingdom
+
+/// Some UK transmitters are offset by this amount, either up or down. It is not applied to [UK_UHF].
+pub const UK_CORRECTION: u32 = 166_000;
+
+pub const UK_UHF: BroadcastBand = BroadcastBand {
+    last_channel: 48,
+    ..EUROPE_UHF_BAND_IV_V
+};
+
+// --- Australia
+
+/// Some Australian transmitters are offset up by this amount. It is not applied to the Australian bands.
+pub const AUSTRALIA_CORRECTION: u32 = 125_000;
+
+/// Channels 6 to 9. Channel 9A (205.5MHz) sits between 9 and 10, see [AUSTRALIA_VHF_HIGH].
+pub const AUSTRALIA_VHF: BroadcastBand = BroadcastBand {
+    first_frequency: 177_500_000,
+    first_channel: 6,
+    last_channel: 9,
+    bandwidth: BandwidthHz::_7MHz,
+    display_prefix: "",
+};
+
+/// Channels 10 to 12, after channel 9A
+pub const AUSTRALIA_VHF_HIGH: BroadcastBand = BroadcastBand {
+    first_frequency: 212_500_000,
+    first_channel: 10,
+    last_channel: 12,
+    ..AUSTRALIA_VHF
+};
+
+pub const AUSTRALIA_UHF: BroadcastBand = BroadcastBand {
+    first_frequency: 529_500_000,
+    first_channel: 28,
+    last_channel: 51,
+    bandwidth: BandwidthHz::_7MHz,
+    display_prefix: "",
+};
+
+// --- United States
+
+/// ATSC channels left after the 600MHz repack
+pub const US_ATSC_UHF: BroadcastBand = BroadcastBand {
+    first_frequency: 473_000_000,
+    first_channel: 14,
+    last_channel: 36,
+    bandwidth: BandwidthHz::_6MHz,
+    display_prefix: "",
+};
+
+// --- Plans
+
+pub const EUROPE_DVBT: &[BroadcastBand] = &[EUROPE_VHF_BAND_III, EUROPE_UHF_BAND_IV_V];
+
+/// France has no DVB-T in VHF
+pub const FRANCE_DVBT: &[BroadcastBand] = &[FRANCE_UHF];
+
+pub const GERMANY_DVBT: &[BroadcastBand] = &[GERMANY_UHF];
+
+pub const ITALY_DVBT: &[BroadcastBand] = &[ITALY_UHF];
+
+pub const UK_DVBT: &[BroadcastBand] = &[UK_UHF];
+
+/// Channel 9A is missing as it can't be numbered
+pub const AUSTRALIA_DVBT: &[BroadcastBand] = &[AUSTRALIA_VHF, AUSTRALIA_VHF_HIGH, AUSTRALIA_UHF];
+
+pub const US_ATSC: &[BroadcastBand] = &[US_ATSC_UHF];
+
+//
+// ----- Satellite
+
+/// A single known transponder on a satellite
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SatelliteTransponder {
+    /// Downlink frequency, as received by the LNB
+    pub frequency_khz: u32,
+    pub polarization: Polarization,
+    /// In symbols per second
+    pub symbol_rate: u32,
+    pub system: DeliverySystem,
+}
+
+impl SatelliteTransponder {
+    /// Return the parameters to give to the frontend to receive this transponder through a given LNB
+    pub fn tuning(&self, lnb: &Lnb) -> SatelliteTuning {
+        let high_band = self.frequency_khz >= lnb.switch_frequency;
+        let oscillator = if high_band {
+            lnb.high_oscillator
+        } else {
+            lnb.low_oscillator
+        };
+        let voltage = match self.polarization {
+            Polarization::Vertical | Polarization::CircularRight => Voltage::_13V,
+            Polarization::Horizontal | Polarization::CircularLeft => Voltage::_18V,
+        };
+
+        SatelliteTuning {
+            frequency: self.frequency_khz.abs_diff(oscillator),
+            voltage,
+            tone: if high_band { Tone::On } else { Tone::Off },
+        }
+    }
+}
+
+/// Local oscillators of an LNB, all in kHz
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Lnb {
+    pub low_oscillator: u32,
+    pub high_oscillator: u32,
+    /// Transponders at or above this frequency are received with the high oscillator
+    pub switch_frequency: u32,
+}
+
+/// Parameters for the frontend to receive a [SatelliteTransponder]. This is returned by [SatelliteTransponder::tuning].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SatelliteTuning {
+    /// Intermediate frequency in kHz
+    pub frequency: u32,
+    pub voltage: Voltage,
+    pub tone: Tone,
+}
+
+pub const UNIVERSAL_LNB: Lnb = Lnb {
+    low_oscillator: 9_750_000,
+    high_oscillator: 10_600_000,
+    switch_frequency: 11_700_000,
+};
+
+/// A few well-known transponders of Astra at 19.2°E, enough to bootstrap a scan
+pub const ASTRA_19_2E: &[SatelliteTransponder] = &[
+    SatelliteTransponder {
+        frequency_khz: 11_362_000,
+        polarization: Polarization::Horizontal,
+        symbol_rate: 22_000_000,
+        system: DeliverySystem::DvbS2,
+    },
+    SatelliteTransponder {
+        frequency_khz: 11_494_000,
+        polarization: Polarization::Horizontal,
+        symbol_rate: 22_000_000,
+        system: DeliverySystem::DvbS2,
+    },
+    SatelliteTransponder {
+        frequency_khz: 11_836_000,
+        polarization: Polarization::Horizontal,
+        symbol_rate: 27_500_000,
+        system: DeliverySystem::DvbS,
+    },
+    SatelliteTransponder {
+        frequency_khz: 11_953_000,
+        polarization: Polarization::Horizontal,
+        symbol_rate: 27_500_000,
+        system: DeliverySystem::DvbS,
+    },
+    SatelliteTransponder {
+        frequency_khz: 12_188_000,
+        polarization: Polarization::Horizontal,
+        symbol_rate: 27_500_000,
+        system: DeliverySystem::DvbS,
+    },
+    SatelliteTransponder {
+        frequency_khz: 12_544_000,
+        polarization: Polarization::Horizontal,
+        symbol_rate: 22_000_000,
+        system: DeliverySystem::DvbS,
+    },
+];
+
 //
 // -----
 
@@ -129,6 +439,19 @@ pub const FRANCE_UHF: BroadcastBand = BroadcastBand {
 mod tests {
     use super::*;
 
+    #[test]
+    fn scan_profile() {
+        let profile = Region::France.scan_profile();
+        assert_eq!(profile.band_plan.channel_count(), 29);
+        assert_eq!(
+            profile.systems,
+            [DeliverySystem::DvbT, DeliverySystem::DvbT2]
+        );
+        assert_eq!(profile.bandwidth, BandwidthHz::_8MHz);
+
+        assert!(Region::UnitedStates.scan_profile().systems.is_empty());
+    }
+
     #[test]
     fn check() {
         let frequencies: Vec<ChannelParameters> = FRANCE_UHF.iter().collect();
@@ -286,4 +609,89 @@ mod tests {
 
         assert_eq!(frequencies, expected)
     }
+
+    #[test]
+    fn plan() {
+        let plan = BandPlan::new(EUROPE_DVBT);
+        let frequencies: Vec<ChannelParameters> = plan.iter().collect();
+
+        assert_eq!(plan.channel_count(), 8 + 48);
+        assert_eq!(frequencies.len() as u32, plan.channel_count());
+
+        // Last of VHF, directly followed by first of UHF
+        assert_eq!(frequencies[7].frequency, 226_500_000);
+        assert_eq!(frequencies[7].number, Some(12));
+        assert_eq!(frequencies[7].display_prefix, "E");
+        assert_eq!(frequencies[8].frequency, 474_000_000);
+        assert_eq!(frequencies[8].number, Some(21));
+
+        // Plan can be iterated again
+        assert_eq!(plan.iter().count(), frequencies.len());
+        assert_eq!(
+            BandPlan::from_region(Region::France).channel_count(),
+            FRANCE_UHF.channel_count()
+        );
+    }
+
+    /// Look for a channel number in a band and check its frequency
+    fn check_known(band: &BroadcastBand, known: &[(u32, u32)]) {
+        for &(number, frequency) in known {
+            let channel = band.iter().find(|c| c.number == Some(number)).unwrap();
+            assert_eq!(channel.frequency, frequency, "channel {}", number);
+        }
+    }
+
+    #[test]
+    fn australia() {
+        check_known(&AUSTRALIA_VHF, &[(6, 177_500_000), (9, 198_500_000)]);
+        check_known(&AUSTRALIA_VHF_HIGH, &[(10, 212_500_000), (12, 226_500_000)]);
+        check_known(
+            &AUSTRALIA_UHF,
+            &[(28, 529_500_000), (34, 571_500_000), (51, 690_500_000)],
+        );
+    }
+
+    #[test]
+    fn united_kingdom() {
+        check_known(
+            &UK_UHF,
+            &[(21, 474_000_000), (37, 602_000_000), (48, 690_000_000)],
+        );
+    }
+
+    #[test]
+    fn germany() {
+        check_known(&GERMANY_UHF, &[(21, 474_000_000), (48, 690_000_000)]);
+        assert_eq!(GERMANY_UHF.channel_count(), 28);
+    }
+
+    #[test]
+    fn italy() {
+        check_known(&ITALY_UHF, &[(21, 474_000_000), (40, 626_000_000)]);
+    }
+
+    #[test]
+    fn satellite_tuning() {
+        let low = ASTRA_19_2E[0].tuning(&UNIVERSAL_LNB);
+        assert_eq!(
+            low,
+            SatelliteTuning {
+                frequency: 1_612_000,
+                voltage: Voltage::_18V,
+                tone: Tone::Off,
+            }
+        );
+
+        let high = ASTRA_19_2E[2].tuning(&UNIVERSAL_LNB);
+        assert_eq!(high.frequency, 1_236_000);
+        assert_eq!(high.tone, Tone::On);
+    }
+
+    #[test]
+    fn united_states() {
+        check_known(
+            &US_ATSC_UHF,
+            &[(14, 473_000_000), (21, 515_000_000), (36, 605_000_000)],
+        );
+    }
 }
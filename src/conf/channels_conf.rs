@@ -0,0 +1,348 @@
+//! Legacy `zap`/dvb-apps `channels.conf` import/export
+//!
+//! Only the DVB-T flavor is supported for now, where each line looks like:
+//! ```Channel:474166000:INVERSION_AUTO:BANDWIDTH_8_MHZ:FEC_3_4:FEC_AUTO:QAM_16:TRANSMISSION_MODE_8K:GUARD_INTERVAL_1_4:HIERARCHY_NONE:120:130:1025```
+//!
+//! `AUTO` tokens are represented as `None`.
+
+use std::str::FromStr;
+
+use crate::{
+    conf::vdr::parameters::{
+        Bandwidth, CodeRate, GuardInterval, Hierarchy, Modulation, TransmissionMode,
+    },
+    error::ChannelsConfParseError,
+    interpret::ChannelInformation,
+};
+
+//
+// -----
+
+/// Parse an entire `channels.conf` file.
+pub fn from_list_str(s: &str) -> Result<Vec<ChannelConfDefinition>, ChannelsConfParseError> {
+    let mut channels = Vec::new();
+    for line in s.lines() {
+        // Skip empty lines and comments
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        channels.push(ChannelConfDefinition::from_str(line)?);
+    }
+    Ok(channels)
+}
+
+//
+// -----
+
+/// A single line of a DVB-T `channels.conf` file.
+#[derive(Debug, Clone)]
+pub struct ChannelConfDefinition {
+    pub name: String,
+    pub frequency: u32,
+    pub inversion: Option<bool>,
+    pub bandwidth: Option<Bandwidth>,
+    pub code_rate_high_priority: Option<CodeRate>,
+    pub code_rate_low_priority: Option<CodeRate>,
+    pub modulation: Option<Modulation>,
+    pub transmission_mode: Option<TransmissionMode>,
+    pub guard_interval: Option<GuardInterval>,
+    pub hierarchy: Option<Hierarchy>,
+    pub video_pid: u16,
+    pub audio_pid: u16,
+    pub service_id: u16,
+}
+
+impl FromStr for ChannelConfDefinition {
+    type Err = ChannelsConfParseError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let columns: Vec<&str> = line.split(':').collect();
+        if columns.len() < 13 {
+            return Err(ChannelsConfParseError::MissingColumn);
+        }
+
+        Ok(ChannelConfDefinition {
+            name: columns[0].to_string(),
+            frequency: columns[1]
+                .parse()
+                .map_err(ChannelsConfParseError::IntParse)?,
+            inversion: parse_inversion(columns[2])?,
+            bandwidth: parse_bandwidth(columns[3])?,
+            code_rate_high_priority: parse_code_rate(columns[4])?,
+            code_rate_low_priority: parse_code_rate(columns[5])?,
+            modulation: parse_modulation(columns[6])?,
+            transmission_mode: parse_transmission_mode(columns[7])?,
+            guard_interval: parse_guard_interval(columns[8])?,
+            hierarchy: parse_hierarchy(columns[9])?,
+            video_pid: columns[10]
+                .parse()
+                .map_err(ChannelsConfParseError::IntParse)?,
+            audio_pid: columns[11]
+                .parse()
+                .map_err(ChannelsConfParseError::IntParse)?,
+            service_id: columns[12]
+                .parse()
+                .map_err(ChannelsConfParseError::IntParse)?,
+        })
+    }
+}
+
+impl ChannelConfDefinition {
+    pub fn format(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}",
+            self.name.replace(':', "|"),
+            self.frequency,
+            format_inversion(self.inversion),
+            format_bandwidth(self.bandwidth),
+            format_code_rate(self.code_rate_high_priority),
+            format_code_rate(self.code_rate_low_priority),
+            format_modulation(self.modulation),
+            format_transmission_mode(self.transmission_mode),
+            format_guard_interval(self.guard_interval),
+            format_hierarchy(self.hierarchy),
+            self.video_pid,
+            self.audio_pid,
+            self.service_id,
+        )
+    }
+}
+
+impl From<ChannelInformation> for ChannelConfDefinition {
+    fn from(value: ChannelInformation) -> Self {
+        // This format only has room for a single audio PID, prefer regular audio
+        let audio_pid = value
+            .audio_pid_list
+            .regular_pids
+            .iter()
+            .chain(value.audio_pid_list.dolby_pids.iter())
+            .next()
+            .map(|a| a.pid)
+            .unwrap_or(0);
+
+        ChannelConfDefinition {
+            name: value.name,
+            frequency: value.frequency,
+            inversion: None,
+            bandwidth: value.bandwidth.map(Into::into),
+            code_rate_high_priority: None,
+            code_rate_low_priority: None,
+            modulation: None,
+            transmission_mode: None,
+            guard_interval: None,
+            hierarchy: None,
+            video_pid: value.video_pid.video_pid.unwrap_or(value.video_pid.pcr_pid),
+            audio_pid,
+            service_id: value.service_id,
+        }
+    }
+}
+
+//
+// ----- Tokens
+
+fn parse_inversion(s: &str) -> Result<Option<bool>, ChannelsConfParseError> {
+    Ok(match s {
+        "INVERSION_OFF" => Some(false),
+        "INVERSION_ON" => Some(true),
+        "INVERSION_AUTO" => None,
+        _ => return Err(ChannelsConfParseError::UnexpectedValue),
+    })
+}
+
+fn format_inversion(inversion: Option<bool>) -> &'static str {
+    match inversion {
+        Some(false) => "INVERSION_OFF",
+        Some(true) => "INVERSION_ON",
+        None => "INVERSION_AUTO",
+    }
+}
+
+fn parse_bandwidth(s: &str) -> Result<Option<Bandwidth>, ChannelsConfParseError> {
+    Ok(match s {
+        "BANDWIDTH_1_712_MHZ" => Some(Bandwidth::_1712kHz),
+        "BANDWIDTH_5_MHZ" => Some(Bandwidth::_5MHz),
+        "BANDWIDTH_6_MHZ" => Some(Bandwidth::_6Mhz),
+        "BANDWIDTH_7_MHZ" => Some(Bandwidth::_7MHz),
+        "BANDWIDTH_8_MHZ" => Some(Bandwidth::_8MHz),
+        "BANDWIDTH_10_MHZ" => Some(Bandwidth::_10MHz),
+        "BANDWIDTH_AUTO" => None,
+        _ => return Err(ChannelsConfParseError::UnexpectedValue),
+    })
+}
+
+fn format_bandwidth(bandwidth: Option<Bandwidth>) -> &'static str {
+    match bandwidth {
+        Some(Bandwidth::_1712kHz) => "BANDWIDTH_1_712_MHZ",
+        Some(Bandwidth::_5MHz) => "BANDWIDTH_5_MHZ",
+        Some(Bandwidth::_6Mhz) => "BANDWIDTH_6_MHZ",
+        Some(Bandwidth::_7MHz) => "BANDWIDTH_7_MHZ",
+        Some(Bandwidth::_8MHz) => "BANDWIDTH_8_MHZ",
+        Some(Bandwidth::_10MHz) => "BANDWIDTH_10_MHZ",
+        None => "BANDWIDTH_AUTO",
+    }
+}
+
+fn parse_code_rate(s: &str) -> Result<Option<CodeRate>, ChannelsConfParseError> {
+    Ok(match s {
+        "FEC_NONE" => Some(CodeRate::NoHierarchy),
+        "FEC_1_2" => Some(CodeRate::_1_2),
+        "FEC_2_3" => Some(CodeRate::_2_3),
+        "FEC_3_4" => Some(CodeRate::_3_4),
+        "FEC_3_5" => Some(CodeRate::_3_5),
+        "FEC_4_5" => Some(CodeRate::_4_5),
+        "FEC_5_6" => Some(CodeRate::_5_6),
+        "FEC_6_7" => Some(CodeRate::_6_7),
+        "FEC_7_8" => Some(CodeRate::_7_8),
+        "FEC_8_9" => Some(CodeRate::_8_9),
+        "FEC_9_10" => Some(CodeRate::_9_10),
+        "FEC_AUTO" => None,
+        _ => return Err(ChannelsConfParseError::UnexpectedValue),
+    })
+}
+
+fn format_code_rate(code_rate: Option<CodeRate>) -> &'static str {
+    match code_rate {
+        Some(CodeRate::NoHierarchy) => "FEC_NONE",
+        Some(CodeRate::_1_2) => "FEC_1_2",
+        Some(CodeRate::_2_3) => "FEC_2_3",
+        Some(CodeRate::_3_4) => "FEC_3_4",
+        Some(CodeRate::_3_5) => "FEC_3_5",
+        Some(CodeRate::_4_5) => "FEC_4_5",
+        Some(CodeRate::_5_6) => "FEC_5_6",
+        Some(CodeRate::_6_7) => "FEC_6_7",
+        Some(CodeRate::_7_8) => "FEC_7_8",
+        Some(CodeRate::_8_9) => "FEC_8_9",
+        Some(CodeRate::_9_10) => "FEC_9_10",
+        None => "FEC_AUTO",
+    }
+}
+
+fn parse_modulation(s: &str) -> Result<Option<Modulation>, ChannelsConfParseError> {
+    Ok(match s {
+        "QPSK" => Some(Modulation::Qpsk),
+        "QAM_16" => Some(Modulation::Qam16),
+        "QAM_32" => Some(Modulation::Qam32),
+        "QAM_64" => Some(Modulation::Qam64),
+        "QAM_128" => Some(Modulation::Qam128),
+        "QAM_256" => Some(Modulation::Qam256),
+        "8VSB" => Some(Modulation::Vsb8),
+        "16VSB" => Some(Modulation::Vsb16),
+        "QAM_AUTO" => None,
+        _ => return Err(ChannelsConfParseError::UnexpectedValue),
+    })
+}
+
+fn format_modulation(modulation: Option<Modulation>) -> &'static str {
+    match modulation {
+        Some(Modulation::Qpsk) => "QPSK",
+        Some(Modulation::Qam16) => "QAM_16",
+        Some(Modulation::Qam32) => "QAM_32",
+        Some(Modulation::Qam64) => "QAM_64",
+        Some(Modulation::Qam128) => "QAM_128",
+        Some(Modulation::Qam256) => "QAM_256",
+        Some(Modulation::Vsb8) => "8VSB",
+        Some(Modulation::Vsb16) => "16VSB",
+        // Not usable for DVB-T, let the frontend figure it out
+        _ => "QAM_AUTO",
+    }
+}
+
+fn parse_transmission_mode(s: &str) -> Result<Option<TransmissionMode>, ChannelsConfParseError> {
+    Ok(match s {
+        "TRANSMISSION_MODE_1K" => Some(TransmissionMode::_1k),
+        "TRANSMISSION_MODE_2K" => Some(TransmissionMode::_2k),
+        "TRANSMISSION_MODE_4K" => Some(TransmissionMode::_4k),
+        "TRANSMISSION_MODE_8K" => Some(TransmissionMode::_8k),
+        "TRANSMISSION_MODE_16K" => Some(TransmissionMode::_16k),
+        "TRANSMISSION_MODE_32K" => Some(TransmissionMode::_32k),
+        "TRANSMISSION_MODE_AUTO" => None,
+        _ => return Err(ChannelsConfParseError::UnexpectedValue),
+    })
+}
+
+fn format_transmission_mode(mode: Option<TransmissionMode>) -> &'static str {
+    match mode {
+        Some(TransmissionMode::_1k) => "TRANSMISSION_MODE_1K",
+        Some(TransmissionMode::_2k) => "TRANSMISSION_MODE_2K",
+        Some(TransmissionMode::_4k) => "TRANSMISSION_MODE_4K",
+        Some(TransmissionMode::_8k) => "TRANSMISSION_MODE_8K",
+        Some(TransmissionMode::_16k) => "TRANSMISSION_MODE_16K",
+        Some(TransmissionMode::_32k) => "TRANSMISSION_MODE_32K",
+        None => "TRANSMISSION_MODE_AUTO",
+    }
+}
+
+fn parse_guard_interval(s: &str) -> Result<Option<GuardInterval>, ChannelsConfParseError> {
+    Ok(match s {
+        "GUARD_INTERVAL_1_4" => Some(GuardInterval::_1_4),
+        "GUARD_INTERVAL_1_8" => Some(GuardInterval::_1_8),
+        "GUARD_INTERVAL_1_16" => Some(GuardInterval::_1_16),
+        "GUARD_INTERVAL_1_32" => Some(GuardInterval::_1_32),
+        "GUARD_INTERVAL_1_128" => Some(GuardInterval::_1_128),
+        "GUARD_INTERVAL_19_128" => Some(GuardInterval::_19_128),
+        "GUARD_INTERVAL_19_256" => Some(GuardInterval::_19_256),
+        "GUARD_INTERVAL_AUTO" => None,
+        _ => return Err(ChannelsConfParseError::UnexpectedValue),
+    })
+}
+
+fn format_guard_interval(interval: Option<GuardInterval>) -> &'static str {
+    match interval {
+        Some(GuardInterval::_1_4) => "GUARD_INTERVAL_1_4",
+        Some(GuardInterval::_1_8) => "GUARD_INTERVAL_1_8",
+        Some(GuardInterval::_1_16) => "GUARD_INTERVAL_1_16",
+        Some(GuardInterval::_1_32) => "GUARD_INTERVAL_1_32",
+        Some(GuardInterval::_1_128) => "GUARD_INTERVAL_1_128",
+        Some(GuardInterval::_19_128) => "GUARD_INTERVAL_19_128",
+        Some(GuardInterval::_19_256) => "GUARD_INTERVAL_19_256",
+        None => "GUARD_INTERVAL_AUTO",
+    }
+}
+
+fn parse_hierarchy(s: &str) -> Result<Option<Hierarchy>, ChannelsConfParseError> {
+    Ok(match s {
+        "HIERARCHY_NONE" => Some(Hierarchy::Off),
+        "HIERARCHY_1" => Some(Hierarchy::TwoStreams),
+        "HIERARCHY_2" => Some(Hierarchy::_2),
+        "HIERARCHY_4" => Some(Hierarchy::_4),
+        "HIERARCHY_AUTO" => None,
+        _ => return Err(ChannelsConfParseError::UnexpectedValue),
+    })
+}
+
+fn format_hierarchy(hierarchy: Option<Hierarchy>) -> &'static str {
+    match hierarchy {
+        Some(Hierarchy::Off) => "HIERARCHY_NONE",
+        Some(Hierarchy::TwoStreams) => "HIERARCHY_1",
+        Some(Hierarchy::_2) => "HIERARCHY_2",
+        Some(Hierarchy::_4) => "HIERARCHY_4",
+        None => "HIERARCHY_AUTO",
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::conf::channels_conf::{ChannelConfDefinition, from_list_str};
+
+    #[test]
+    fn round_trip() {
+        let example = "France 2:474166000:INVERSION_AUTO:BANDWIDTH_8_MHZ:FEC_3_4:FEC_AUTO:QAM_16:TRANSMISSION_MODE_8K:GUARD_INTERVAL_1_4:HIERARCHY_NONE:120:130:1025";
+
+        let parsed = ChannelConfDefinition::from_str(example).unwrap();
+        assert_eq!(parsed.name, "France 2");
+        assert_eq!(parsed.frequency, 474_166_000);
+        assert_eq!(parsed.inversion, None);
+        assert!(parsed.code_rate_low_priority.is_none());
+        assert_eq!(parsed.service_id, 1025);
+        assert_eq!(parsed.format(), example);
+
+        assert!(from_list_str("# Comment\n\nBroken:474166000:INVERSION_AUTO").is_err());
+    }
+}
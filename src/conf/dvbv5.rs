@@ -0,0 +1,250 @@
+//! dvbv5 (`dvbv5-zap`, libdvbv5) channel file import/export
+//!
+//! Each channel is an INI-like block:
+//! ```text
+//! [France 2]
+//!     SERVICE_ID = 1025
+//!     VIDEO_PID = 120
+//!     AUDIO_PID = 130 131
+//!     FREQUENCY = 474166000
+//!     BANDWIDTH_HZ = 8000000
+//!     DELIVERY_SYSTEM = DVBT
+//! ```
+
+use crate::{
+    error::Dvbv5ParseError,
+    frontend::{DeliverySystem, properties::set::BandwidthHz},
+    interpret::ChannelInformation,
+};
+
+//
+// -----
+
+/// Write a dvbv5 channel file for all given channels.
+pub fn format(channels: &[ChannelInformation]) -> String {
+    let mut text = String::new();
+    for channel in channels {
+        text.push_str(&Dvbv5Channel::from(channel).format());
+        text.push('\n');
+    }
+    text
+}
+
+/// Parse an entire dvbv5 channel file.
+pub fn from_list_str(s: &str) -> Result<Vec<Dvbv5Channel>, Dvbv5ParseError> {
+    let mut channels: Vec<Dvbv5Channel> = Vec::new();
+    for line in s.lines() {
+        let line = line.trim();
+
+        // Skip empty lines and comments
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // Start of a new channel
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            channels.push(Dvbv5Channel::new(name.to_string()));
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or(Dvbv5ParseError::UnexpectedLine)?;
+        let channel = channels
+            .last_mut()
+            .ok_or(Dvbv5ParseError::PropertyOutsideChannel)?;
+        channel.set_property(key.trim(), value.trim())?;
+    }
+
+    Ok(channels)
+}
+
+//
+// -----
+
+/// A single channel block of a dvbv5 channel file.
+#[derive(Debug, Clone)]
+pub struct Dvbv5Channel {
+    pub name: String,
+    pub service_id: Option<u16>,
+    pub video_pids: Vec<u16>,
+    pub audio_pids: Vec<u16>,
+    pub frequency: Option<u32>,
+    pub bandwidth: Option<BandwidthHz>,
+    pub delivery_system: Option<DeliverySystem>,
+    pub symbol_rate: Option<u32>,
+    /// Properties that aren't interpreted by this library, kept as-is in order.
+    pub other_properties: Vec<(String, String)>,
+}
+
+impl Dvbv5Channel {
+    pub fn new(name: String) -> Dvbv5Channel {
+        Dvbv5Channel {
+            name,
+            service_id: None,
+            video_pids: Vec::new(),
+            audio_pids: Vec::new(),
+            frequency: None,
+            bandwidth: None,
+            delivery_system: None,
+            symbol_rate: None,
+            other_properties: Vec::new(),
+        }
+    }
+
+    fn set_property(&mut self, key: &str, value: &str) -> Result<(), Dvbv5ParseError> {
+        match key {
+            "SERVICE_ID" => {
+                self.service_id = Some(value.parse().map_err(Dvbv5ParseError::IntParse)?)
+            }
+            "VIDEO_PID" => self.video_pids = parse_pid_list(value)?,
+            "AUDIO_PID" => self.audio_pids = parse_pid_list(value)?,
+            "FREQUENCY" => self.frequency = Some(value.parse().map_err(Dvbv5ParseError::IntParse)?),
+            "BANDWIDTH_HZ" => {
+                let hz: u32 = value.parse().map_err(Dvbv5ParseError::IntParse)?;
+                self.bandwidth =
+                    Some(BandwidthHz::from_value(hz).ok_or(Dvbv5ParseError::UnexpectedValue)?);
+            }
+            "DELIVERY_SYSTEM" => self.delivery_system = Some(parse_delivery_system(value)?),
+            "SYMBOL_RATE" => {
+                self.symbol_rate = Some(value.parse().map_err(Dvbv5ParseError::IntParse)?)
+            }
+            _ => self
+                .other_properties
+                .push((key.to_string(), value.to_string())),
+        }
+        Ok(())
+    }
+
+    pub fn format(&self) -> String {
+        let mut text = format!("[{}]\n", self.name);
+
+        let mut property = |key: &str, value: String| {
+            text.push_str(&format!("\t{} = {}\n", key, value));
+        };
+
+        if let Some(service_id) = self.service_id {
+            property("SERVICE_ID", service_id.to_string());
+        }
+        if !self.video_pids.is_empty() {
+            property("VIDEO_PID", format_pid_list(&self.video_pids));
+        }
+        if !self.audio_pids.is_empty() {
+            property("AUDIO_PID", format_pid_list(&self.audio_pids));
+        }
+        if let Some(frequency) = self.frequency {
+            property("FREQUENCY", frequency.to_string());
+        }
+        if let Some(bandwidth) = self.bandwidth {
+            property("BANDWIDTH_HZ", bandwidth.value().to_string());
+        }
+        if let Some(symbol_rate) = self.symbol_rate {
+            property("SYMBOL_RATE", symbol_rate.to_string());
+        }
+        if let Some(system) = self.delivery_system {
+            property(
+                "DELIVERY_SYSTEM",
+                format_delivery_system(system).to_string(),
+            );
+        }
+        for (key, value) in &self.other_properties {
+            property(key.as_str(), value.clone());
+        }
+
+        text
+    }
+}
+
+impl From<&ChannelInformation> for Dvbv5Channel {
+    fn from(value: &ChannelInformation) -> Self {
+        let audio_pids = value
+            .audio_pid_list
+            .regular_pids
+            .iter()
+            .chain(value.audio_pid_list.dolby_pids.iter())
+            .map(|a| a.pid)
+            .collect();
+
+        Dvbv5Channel {
+            name: value.name.clone(),
+            service_id: Some(value.service_id),
+            video_pids: vec![value.video_pid.video_pid.unwrap_or(value.video_pid.pcr_pid)],
+            audio_pids,
+            frequency: Some(value.frequency),
+            bandwidth: value.bandwidth,
+            delivery_system: Some(value.delivery_system),
+            symbol_rate: value.symbol_rate,
+            other_properties: Vec::new(),
+        }
+    }
+}
+
+//
+// ----- Tokens
+
+fn parse_pid_list(s: &str) -> Result<Vec<u16>, Dvbv5ParseError> {
+    s.split_whitespace()
+        .map(|pid| pid.parse().map_err(Dvbv5ParseError::IntParse))
+        .collect()
+}
+
+fn format_pid_list(pids: &[u16]) -> String {
+    pids.iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_delivery_system(s: &str) -> Result<DeliverySystem, Dvbv5ParseError> {
+    Ok(match s {
+        "DVBC/ANNEX_A" => DeliverySystem::DvbCAnnexA,
+        "DVBC/ANNEX_B" => DeliverySystem::DvbCAnnexB,
+        "DVBC/ANNEX_C" => DeliverySystem::DvbCAnnexC,
+        "DVBC2" => DeliverySystem::DvbC2,
+        "DVBT" => DeliverySystem::DvbT,
+        "DVBT2" => DeliverySystem::DvbT2,
+        "DVBS" => DeliverySystem::DvbS,
+        "DVBS2" => DeliverySystem::DvbS2,
+        _ => return Err(Dvbv5ParseError::UnexpectedValue),
+    })
+}
+
+fn format_delivery_system(system: DeliverySystem) -> &'static str {
+    match system {
+        DeliverySystem::DvbCAnnexA => "DVBC/ANNEX_A",
+        DeliverySystem::DvbCAnnexB => "DVBC/ANNEX_B",
+        DeliverySystem::DvbCAnnexC => "DVBC/ANNEX_C",
+        DeliverySystem::DvbC2 => "DVBC2",
+        DeliverySystem::DvbT => "DVBT",
+        DeliverySystem::DvbT2 => "DVBT2",
+        DeliverySystem::DvbS => "DVBS",
+        DeliverySystem::DvbS2 => "DVBS2",
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        conf::dvbv5::from_list_str,
+        frontend::{DeliverySystem, properties::set::BandwidthHz},
+    };
+
+    #[test]
+    fn round_trip() {
+        let example = "[France 2]\n\tSERVICE_ID = 1025\n\tVIDEO_PID = 120\n\tAUDIO_PID = 130 131\n\tFREQUENCY = 474166000\n\tBANDWIDTH_HZ = 8000000\n\tDELIVERY_SYSTEM = DVBT\n\tMODULATION = QAM/AUTO\n";
+
+        let channels = from_list_str(example).unwrap();
+        assert_eq!(channels.len(), 1);
+        let channel = &channels[0];
+        assert_eq!(channel.name, "France 2");
+        assert_eq!(channel.audio_pids, [130, 131]);
+        assert_eq!(channel.bandwidth, Some(BandwidthHz::_8MHz));
+        assert_eq!(channel.delivery_system, Some(DeliverySystem::DvbT));
+        assert_eq!(channel.format(), example);
+
+        assert!(from_list_str("FREQUENCY = 474166000").is_err());
+    }
+}
@@ -1,3 +1,5 @@
 //! Channel configuration files for use with other programs or with this library
 
+pub mod channels_conf;
+pub mod dvbv5;
 pub mod vdr;
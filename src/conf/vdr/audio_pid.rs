@@ -91,7 +91,6 @@ impl AudioPIDList {
         if self.regular_pids.is_empty() {
             list.push('0')
         } else {
-            let mut list = String::new();
             let mut first = true;
             for pid in &self.regular_pids {
                 if first {
@@ -122,20 +121,25 @@ impl AudioPIDList {
 
 impl AudioPID {
     pub fn format(&self) -> String {
+        let mut text = self.pid.to_string();
+
+        // Languages, like "104=deu" or "104=deu+eng"
         match (
             !self.language_code.is_empty(),
             !self.second_language_code.is_empty(),
-            self.audio_type,
         ) {
-            (false, false, None) => self.pid.to_string(),
-            (false, false, Some(audio_type)) => format!("{}@{}", self.pid, audio_type),
-            (true, false, None) => {
-                format!("{}={}", self.pid, self.language_code)
-            }
-            (true, false, Some(audio_type)) => {
-                format!("{}={}@{}", self.pid, self.language_code, audio_type)
-            }
-            _ => todo!(),
+            (false, false) => {}
+            (true, false) => text.push_str(&format!("={}", self.language_code)),
+            (_, true) => text.push_str(&format!(
+                "={}+{}",
+                self.language_code, self.second_language_code
+            )),
         }
+
+        if let Some(audio_type) = self.audio_type {
+            text.push_str(&format!("@{}", audio_type));
+        }
+
+        text
     }
 }
@@ -0,0 +1,77 @@
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    num::ParseIntError,
+    str::FromStr,
+};
+
+/// List of Conditional Access systems required to watch a channel, in the order they were written.
+///
+/// An empty list means the channel is Free-To-Air, written as `0`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConditionalAccess(pub Vec<CaEntry>);
+
+/// A single entry of the Conditional Access field
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CaEntry {
+    /// Conditional Access system ID
+    System(u16),
+    /// CAM slot the channel is tied to, written with a `@` prefix
+    Slot(u16),
+}
+
+impl ConditionalAccess {
+    /// Whether no Conditional Access system is required to watch the channel.
+    pub fn is_free_to_air(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Return the Conditional Access system IDs, leaving out slots
+    pub fn systems(&self) -> impl Iterator<Item = u16> + '_ {
+        self.0.iter().filter_map(|entry| match entry {
+            CaEntry::System(id) => Some(*id),
+            CaEntry::Slot(_) => None,
+        })
+    }
+}
+
+impl FromStr for ConditionalAccess {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "0" {
+            return Ok(ConditionalAccess::default());
+        }
+
+        // IDs and slots are written in hexadecimal, separated by commas
+        let mut entries = Vec::new();
+        for entry in s.split(',') {
+            entries.push(match entry.strip_prefix('@') {
+                Some(slot) => CaEntry::Slot(u16::from_str_radix(slot, 16)?),
+                None => CaEntry::System(u16::from_str_radix(entry, 16)?),
+            });
+        }
+        Ok(ConditionalAccess(entries))
+    }
+}
+
+impl Display for ConditionalAccess {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.0.is_empty() {
+            return write!(f, "0");
+        }
+
+        let mut first = true;
+        for entry in &self.0 {
+            if first {
+                first = false;
+            } else {
+                write!(f, ",")?;
+            }
+            match entry {
+                CaEntry::System(id) => write!(f, "{:X}", id)?,
+                CaEntry::Slot(slot) => write!(f, "@{:X}", slot)?,
+            }
+        }
+        Ok(())
+    }
+}
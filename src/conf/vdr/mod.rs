@@ -1,14 +1,21 @@
 //! VDR-style configuration import/export
 
 pub mod audio_pid;
+pub mod conditional_access;
 pub mod parameters;
 pub mod teletext_pid;
 pub mod video_pid;
 
 use std::str::FromStr;
 
-use crate::{error::VdrParseError, frontend::DeliverySystem, interpret::ChannelInformation};
+use crate::{
+    error::{UnknownVdrSource, VdrParseError},
+    frontend::DeliverySystem,
+    interpret::ChannelInformation,
+    mpeg::descriptors::satellite_delivery_system::SatelliteDeliverySystem,
+};
 use audio_pid::AudioPIDList;
+use conditional_access::ConditionalAccess;
 use parameters::Parameters;
 use teletext_pid::TeletextPIDList;
 use video_pid::VideoPID;
@@ -17,8 +24,8 @@ use video_pid::VideoPID;
 // -----
 
 /// Parse an entire VDR file.
-pub fn from_list_str(s: &str) -> Vec<ChannelDefinition> {
-    let mut channels = Vec::new();
+pub fn from_list_str(s: &str) -> Result<Vec<VdrEntry>, VdrParseError> {
+    let mut entries = Vec::new();
     for line in s.lines() {
         // Skip empty lines
         if line.is_empty() {
@@ -32,15 +39,79 @@ pub fn from_list_str(s: &str) -> Vec<ChannelDefinition> {
 
         // Groups and channel numbers
         if line.starts_with(':') {
-            // TODO: Parse
+            entries.push(VdrEntry::Group(GroupDefinition::from_str(line)?));
             continue;
         }
 
-        let channel = ChannelDefinition::from_str(line).unwrap();
-        channels.push(channel);
+        entries.push(VdrEntry::Channel(ChannelDefinition::from_str(line)?));
     }
 
-    channels
+    Ok(entries)
+}
+
+/// Write an entire VDR file, the inverse of [from_list_str].
+///
+/// Every entry is written on its own line, each ending with a line feed.
+pub fn to_list_str(entries: &[VdrEntry]) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        match entry {
+            VdrEntry::Channel(channel) => text.push_str(&channel.format()),
+            VdrEntry::Group(group) => text.push_str(&group.format()),
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// A single meaningful line of a VDR-style configuration file.
+#[derive(Debug, Clone)]
+pub enum VdrEntry {
+    Channel(ChannelDefinition),
+    Group(GroupDefinition),
+}
+
+/// A group separator line, starting with `:`.
+///
+/// It may also set the number of the next channel, like ```:@201 Radio```.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupDefinition {
+    pub name: String,
+    pub channel_number: Option<u32>,
+}
+
+impl FromStr for GroupDefinition {
+    type Err = VdrParseError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let rest = line
+            .strip_prefix(':')
+            .ok_or(VdrParseError::UnexpectedParameterValue)?;
+
+        // Channel number comes first if any
+        let (channel_number, name) = match rest.strip_prefix('@') {
+            Some(numbered) => {
+                let (number, name) = numbered.split_once(' ').unwrap_or((numbered, ""));
+                (Some(number.parse().map_err(VdrParseError::IntParse)?), name)
+            }
+            None => (None, rest),
+        };
+
+        Ok(GroupDefinition {
+            name: name.to_string(),
+            channel_number,
+        })
+    }
+}
+
+impl GroupDefinition {
+    pub fn format(&self) -> String {
+        match self.channel_number {
+            Some(number) if self.name.is_empty() => format!(":@{}", number),
+            Some(number) => format!(":@{} {}", number, self.name),
+            None => format!(":{}", self.name),
+        }
+    }
 }
 
 //
@@ -64,8 +135,7 @@ pub struct ChannelDefinition {
     pub audio_pid: AudioPIDList,
     pub teletext_pid: TeletextPIDList,
     // `0` for free-to-air
-    // TODO: Proper structure
-    pub conditional_access: String,
+    pub conditional_access: ConditionalAccess,
     // program_number in PMT, found in NIT
     pub service_id: u16,
     // Found in NIT
@@ -111,7 +181,11 @@ impl FromStr for ChannelDefinition {
             .ok_or(VdrParseError::MissingColumn)?
             .parse()
             .map_err(VdrParseError::IntParse)?;
-        let conditional_access = iter.next().ok_or(VdrParseError::MissingColumn)?.to_string();
+        let conditional_access = iter
+            .next()
+            .ok_or(VdrParseError::MissingColumn)?
+            .parse()
+            .map_err(VdrParseError::IntParse)?;
         let service_id = iter
             .next()
             .ok_or(VdrParseError::MissingColumn)?
@@ -205,14 +279,19 @@ impl ChannelDefinition {
     }
 }
 
-impl From<ChannelInformation> for ChannelDefinition {
-    fn from(value: ChannelInformation) -> Self {
+/// Fails for satellite channels whose NIT doesn't tell the orbital position.
+impl TryFrom<ChannelInformation> for ChannelDefinition {
+    type Error = UnknownVdrSource;
+
+    fn try_from(value: ChannelInformation) -> Result<Self, Self::Error> {
+        let source = system_to_source(&value.delivery_system, value.satellite.as_ref())
+            .ok_or(UnknownVdrSource(value.delivery_system))?;
         let parameters = Parameters {
-            bandwidth: Some(value.bandwidth.into()),
+            bandwidth: value.bandwidth.map(Into::into),
             code_rate_high_priority: None,
             code_rate_low_priority: None,
             guard_interval: None,
-            polarization: None,
+            polarization: value.satellite.as_ref().map(|s| s.polarization),
             inversion: None,
             modulation: None,
             pilot_mode: None,
@@ -225,31 +304,48 @@ impl From<ChannelInformation> for ChannelDefinition {
             hierarchy: None,
         };
 
-        ChannelDefinition {
+        Ok(ChannelDefinition {
             name: value.name,
             short_name: String::new(),
-            bouquet: String::new(),
+            bouquet: value.bouquet.unwrap_or_default(),
             frequency: value.frequency,
             parameters,
-            source: system_to_source(&value.delivery_system).to_string(),
-            symbol_rate: value.symbol_rate.unwrap_or(0), // Should the default be per-system ?
+            source,
+            // VDR wants kSym/s, and 0 when there is no symbol rate
+            symbol_rate: value.symbol_rate.map(|s| s / 1000).unwrap_or(0),
             video_pid: value.video_pid,
             audio_pid: value.audio_pid_list,
-            teletext_pid: TeletextPIDList::default(), // TODO: Teletext/Subtitles
-            conditional_access: "0".to_string(),      // TODO: CA
+            teletext_pid: value.teletext_pid_list,
+            conditional_access: ConditionalAccess::default(), // TODO: CA
             service_id: value.service_id,
             network_id: value.original_network_id,
             transport_stream_id: value.transport_stream_id,
             radio_id: 0, // IT'S!! TV!! TiME!!
-        }
+        })
     }
 }
 
-fn system_to_source(system: &DeliverySystem) -> &'static str {
-    match system {
-        DeliverySystem::DvbT | DeliverySystem::DvbT2 => "T",
-        _ => unimplemented!(),
-    }
+/// Source column, like `T` or `S19.2E` for satellites.
+fn system_to_source(
+    system: &DeliverySystem,
+    satellite: Option<&SatelliteDeliverySystem>,
+) -> Option<String> {
+    Some(match system {
+        DeliverySystem::DvbT | DeliverySystem::DvbT2 => "T".to_string(),
+        DeliverySystem::DvbCAnnexA
+        | DeliverySystem::DvbCAnnexB
+        | DeliverySystem::DvbCAnnexC
+        | DeliverySystem::DvbC2 => "C".to_string(),
+        DeliverySystem::DvbS | DeliverySystem::DvbS2 => {
+            let satellite = satellite?;
+            format!(
+                "S{}.{}{}",
+                satellite.orbital_position / 10,
+                satellite.orbital_position % 10,
+                if satellite.west_east_flag { 'E' } else { 'W' }
+            )
+        }
+    })
 }
 
 //
@@ -259,13 +355,145 @@ fn system_to_source(system: &DeliverySystem) -> &'static str {
 mod tests {
     use std::str::FromStr;
 
-    use crate::conf::vdr::ChannelDefinition;
+    use rdvb_os_linux::frontend::data::FeModulation;
+
+    use crate::conf::vdr::{
+        ChannelDefinition, GroupDefinition, VdrEntry,
+        audio_pid::AudioPIDList,
+        conditional_access::{CaEntry, ConditionalAccess},
+        from_list_str,
+        parameters::{Modulation, Polarization},
+        system_to_source, to_list_str,
+    };
+    use crate::{
+        frontend::DeliverySystem,
+        mpeg::descriptors::satellite_delivery_system::SatelliteDeliverySystem,
+    };
 
     #[test]
     fn parse() {
         let example = "RTL Television,RTL;RTL World:12187:hC34M2O0S0:S19.2E:27500:163=2:104=deu;106=deu:105:0:12003:1:1089:0";
 
         let parsed = ChannelDefinition::from_str(example).unwrap();
+        assert_eq!(parsed.conditional_access, ConditionalAccess::default());
+    }
+
+    #[test]
+    fn sources() {
+        let astra = SatelliteDeliverySystem {
+            frequency: 1_136_200,
+            orbital_position: 192,
+            west_east_flag: true,
+            polarization: Polarization::Horizontal,
+            roll_off: 0,
+            modulation_system: true,
+            modulation_type: 2,
+            symbol_rate: 220_000,
+            fec_inner: 2,
+        };
+
+        assert_eq!(
+            system_to_source(&DeliverySystem::DvbS2, Some(&astra)).as_deref(),
+            Some("S19.2E")
+        );
+        let hispasat = SatelliteDeliverySystem {
+            orbital_position: 300,
+            west_east_flag: false,
+            ..astra
+        };
+        assert_eq!(
+            system_to_source(&DeliverySystem::DvbS, Some(&hispasat)).as_deref(),
+            Some("S30.0W")
+        );
+        // Without the NIT, there is no way to tell which satellite it is
+        assert_eq!(system_to_source(&DeliverySystem::DvbS2, None), None);
+        assert_eq!(
+            system_to_source(&DeliverySystem::DvbT2, None).as_deref(),
+            Some("T")
+        );
+    }
+
+    #[test]
+    fn conditional_access() {
+        let free = ConditionalAccess::from_str("0").unwrap();
+        assert_eq!(free, ConditionalAccess(vec![]));
+        assert_eq!(free.to_string(), "0");
+        assert!(free.is_free_to_air());
+
+        let encrypted = ConditionalAccess::from_str("1702,1801").unwrap();
+        assert_eq!(
+            encrypted,
+            ConditionalAccess(vec![CaEntry::System(0x1702), CaEntry::System(0x1801)])
+        );
+        assert_eq!(encrypted.to_string(), "1702,1801");
+        assert!(!encrypted.is_free_to_air());
+
+        let slotted = ConditionalAccess::from_str("@2,1702").unwrap();
+        assert_eq!(
+            slotted,
+            ConditionalAccess(vec![CaEntry::Slot(2), CaEntry::System(0x1702)])
+        );
+        assert_eq!(slotted.to_string(), "@2,1702");
+        assert_eq!(slotted.systems().collect::<Vec<_>>(), vec![0x1702]);
+    }
+
+    #[test]
+    fn audio_pids() {
+        for text in [
+            "104=deu;106=deu",
+            "0;106=deu",
+            "104=deu+eng@3,105@106;106=deu",
+        ] {
+            let list = AudioPIDList::from_str(text).unwrap();
+            assert_eq!(list.format(), text);
+        }
+
+        let dual = AudioPIDList::from_str("104=deu+eng@3").unwrap();
+        assert_eq!(dual.regular_pids[0].second_language_code, "eng");
+    }
+
+    #[test]
+    fn groups() {
+        let list = ":Television\nRTL Television,RTL;RTL World:12187:hC34M2O0S0:S19.2E:27500:163=2:104=deu;106=deu:105:0:12003:1:1089:0\n:@201 Radio\n";
+
+        let entries = from_list_str(list).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(matches!(&entries[1], VdrEntry::Channel(c) if c.name == "RTL Television"));
+
+        let group = GroupDefinition::from_str(":@201 Radio").unwrap();
+        assert_eq!(
+            group,
+            GroupDefinition {
+                name: "Radio".to_string(),
+                channel_number: Some(201),
+            }
+        );
+        assert_eq!(group.format(), ":@201 Radio");
+        assert!(matches!(&entries[2], VdrEntry::Group(g) if *g == group));
+
+        assert!(from_list_str(":@abc Broken").is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let list = ":Television\nRTL Television,RTL;RTL World:12187:hC34M2O0S0:S19.2E:27500:163=2:104=deu;106=deu:105:0:12003:1:1089:0\n";
+
+        let written = to_list_str(&from_list_str(list).unwrap());
+        assert!(written.ends_with('\n'));
+        assert!(written.contains(":163=2:104=deu;106=deu:105:0:"));
+
+        let rewritten = to_list_str(&from_list_str(&written).unwrap());
+        assert_eq!(written, rewritten);
+    }
+
+    #[test]
+    fn modulation_conversion() {
+        assert_eq!(FeModulation::from(Modulation::_8Psk), FeModulation::PSK_8);
+        assert!(matches!(
+            Modulation::try_from(FeModulation::QAM_AUTO),
+            Ok(Modulation::Auto)
+        ));
+        assert!(Modulation::try_from(FeModulation::APSK_16_L).is_err());
     }
 
     // TODO: Complete this test
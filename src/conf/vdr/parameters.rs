@@ -1,7 +1,9 @@
 use std::str::FromStr;
 
+use rdvb_os_linux::frontend::data::FeModulation;
+
 use crate::{
-    error::VdrParseError,
+    error::{UnmappableModulation, VdrParseError},
     frontend::{DeliverySystemGeneration, properties::set::BandwidthHz},
 };
 
@@ -74,7 +76,7 @@ pub enum GuardInterval {
     _19_256,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Polarization {
     Horizontal,
     Vertical,
@@ -99,6 +101,50 @@ pub enum Modulation {
     Auto,
 }
 
+impl From<Modulation> for FeModulation {
+    fn from(value: Modulation) -> Self {
+        match value {
+            Modulation::Qpsk => FeModulation::QPSK,
+            Modulation::_8Psk => FeModulation::PSK_8,
+            Modulation::_16Apsk => FeModulation::APSK_16,
+            Modulation::_32Apsk => FeModulation::APSK_32,
+            Modulation::Vsb8 => FeModulation::VSB_8,
+            Modulation::Vsb16 => FeModulation::VSB_16,
+            Modulation::Dqpsk => FeModulation::DQPSK,
+            Modulation::Qam16 => FeModulation::QAM_16,
+            Modulation::Qam32 => FeModulation::QAM_32,
+            Modulation::Qam64 => FeModulation::QAM_64,
+            Modulation::Qam128 => FeModulation::QAM_128,
+            Modulation::Qam256 => FeModulation::QAM_256,
+            Modulation::Auto => FeModulation::QAM_AUTO,
+        }
+    }
+}
+
+impl TryFrom<FeModulation> for Modulation {
+    type Error = UnmappableModulation;
+
+    /// VDR has no value for the newer modulations, like QAM 1024 or the APSK-L variants.
+    fn try_from(value: FeModulation) -> Result<Self, Self::Error> {
+        Ok(match value {
+            FeModulation::QPSK => Modulation::Qpsk,
+            FeModulation::PSK_8 => Modulation::_8Psk,
+            FeModulation::APSK_16 => Modulation::_16Apsk,
+            FeModulation::APSK_32 => Modulation::_32Apsk,
+            FeModulation::VSB_8 => Modulation::Vsb8,
+            FeModulation::VSB_16 => Modulation::Vsb16,
+            FeModulation::DQPSK => Modulation::Dqpsk,
+            FeModulation::QAM_16 => Modulation::Qam16,
+            FeModulation::QAM_32 => Modulation::Qam32,
+            FeModulation::QAM_64 => Modulation::Qam64,
+            FeModulation::QAM_128 => Modulation::Qam128,
+            FeModulation::QAM_256 => Modulation::Qam256,
+            FeModulation::QAM_AUTO => Modulation::Auto,
+            _ => return Err(UnmappableModulation(value)),
+        })
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum PilotMode {
     Off,
@@ -88,6 +88,45 @@ impl TeletextPIDList {
             return "0".to_string();
         }
 
-        todo!()
+        let mut list = String::new();
+
+        if self.teletext.is_empty() {
+            list.push('0')
+        } else {
+            let mut first = true;
+            for pid in &self.teletext {
+                if first {
+                    first = false;
+                } else {
+                    list.push(',');
+                }
+                list.push_str(&pid.to_string());
+            }
+        }
+
+        if !self.subtitles.is_empty() {
+            list.push(';');
+            let mut first = true;
+            for subtitle in &self.subtitles {
+                if first {
+                    first = false;
+                } else {
+                    list.push(',');
+                }
+                list.push_str(&subtitle.format());
+            }
+        }
+
+        list
+    }
+}
+
+impl SubtitlePID {
+    pub fn format(&self) -> String {
+        if self.language.is_empty() {
+            self.pid.to_string()
+        } else {
+            format!("{}={}", self.pid, self.language)
+        }
     }
 }
@@ -1,72 +1,616 @@
-use std::{fs::File, io::Read, os::fd::AsFd, path::Path, time::Duration};
+use std::{
+    fs::File,
+    io::Read,
+    os::{fd::AsFd, unix::fs::OpenOptionsExt},
+    path::Path,
+    time::{Duration, Instant},
+};
 
+use nix::{
+    errno::Errno,
+    poll::{PollFd, PollFlags, PollTimeout, poll},
+};
 use rdvb_os_linux::demux::{
-    data::{DmxFilter, DmxSctFilterParams},
-    functions::{set_filter, start, stop},
+    data::{
+        DmxFilter, DmxInput, DmxOutput, DmxPesFilterParams, DmxSctFilterParams, DmxStc, DmxTsPes,
+    },
+    functions::{
+        add_pid, get_pes_pids, get_stc, remove_pid, set_buffer_size, set_filter, set_pes_filter,
+        start, stop,
+    },
 };
 
-use crate::mpeg::{DMX_CHECK_CRC, DMX_IMMEDIATE_START, DMX_ONESHOT, Packet};
+use crate::{
+    adapter::demux_path,
+    error::{DemuxError, PacketError},
+    mpeg::{
+        DMX_CHECK_CRC, DMX_IMMEDIATE_START, DMX_ONESHOT, MAX_SECTION_LENGTH, Packet, Pid,
+        TS_PACKET_LENGTH, assembler::SectionAssembler,
+    },
+    si::TableId,
+};
 
 pub struct Demux {
     file: File,
+    /// Check CRC here instead of in the kernel, for filters asking for it
+    user_space_crc: bool,
+    /// Whether the current filter asked for its sections CRC to be checked here
+    check_crc: bool,
+    errors: SectionErrors,
+}
+
+/// Counts of sections that arrived but were rejected, see [`Demux::errors`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SectionErrors {
+    /// Only counted with [`check_crc_in_user_space`](Demux::check_crc_in_user_space), the kernel drops them silently otherwise.
+    pub crc_failed: u64,
+    /// Sections that couldn't even be parsed.
+    pub malformed: u64,
+    /// Times the kernel buffer overflowed, losing sections that weren't read in time.
+    pub overflows: u64,
 }
 
 impl Demux {
+    /// Open a demux device read-only, see [open](Self::open).
     pub fn new(demux: &Path) -> Result<Demux, std::io::Error> {
-        let file = File::open(demux)?;
-        Ok(Demux { file })
+        Demux::open(demux, false)
+    }
+
+    /// Open a demux device like ```/dev/dvb/adapterX/demuxX```.
+    ///
+    /// Read-only is enough for filtering, writing is only needed for some modes like feeding data through `DMX_IN_DVR`.
+    pub fn open(demux: &Path, writeable: bool) -> Result<Demux, std::io::Error> {
+        Demux::open_file(demux, writeable, false)
+    }
+
+    /// Open the demux of an adapter by index, see [new](Self::new).
+    pub fn open_adapter(adapter: u32, demux: u32) -> Result<Demux, std::io::Error> {
+        Demux::new(&demux_path(adapter, demux))
     }
 
+    /// Same as [new](Self::new), but reads never block.
+    ///
+    /// When no data is available, reading fails with [`WouldBlock`](std::io::ErrorKind::WouldBlock).
+    /// Use [poll](Self::poll) to wait for data, or integrate the demux in an event loop.
+    pub fn new_nonblocking(demux: &Path) -> Result<Demux, std::io::Error> {
+        Demux::open_nonblocking(demux, false)
+    }
+
+    /// Same as [open](Self::open), but reads never block, see [new_nonblocking](Self::new_nonblocking).
+    pub fn open_nonblocking(demux: &Path, writeable: bool) -> Result<Demux, std::io::Error> {
+        Demux::open_file(demux, writeable, true)
+    }
+
+    fn open_file(
+        demux: &Path,
+        writeable: bool,
+        nonblocking: bool,
+    ) -> Result<Demux, std::io::Error> {
+        let mut options = File::options();
+        options.read(true).write(writeable);
+        if nonblocking {
+            options.custom_flags(nix::libc::O_NONBLOCK);
+        }
+        let file = options.open(demux)?;
+        Ok(Demux {
+            file,
+            user_space_crc: false,
+            check_crc: false,
+            errors: SectionErrors::default(),
+        })
+    }
+
+    /// Wait until data is available for reading or `timeout` expires.
+    ///
+    /// Returns `true` if data can be read, `false` on timeout.
+    pub fn poll(&self, timeout: Duration) -> Result<bool, std::io::Error> {
+        let mut fds = [PollFd::new(self.file.as_fd(), PollFlags::POLLIN)];
+        let timeout = PollTimeout::try_from(timeout).unwrap_or(PollTimeout::MAX);
+        let ready = poll(&mut fds, timeout)?;
+        Ok(ready > 0)
+    }
+
+    /// Read raw data from the demux. If opened with [new_nonblocking](Self::new_nonblocking), this fails with [`WouldBlock`](std::io::ErrorKind::WouldBlock) when no data is available.
     pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
         self.file.read(buf)
     }
 
-    pub fn start(&mut self) {
-        start(self.file.as_fd()).unwrap()
+    pub fn start(&mut self) -> Result<(), DemuxError> {
+        start(self.file.as_fd()).map_err(DemuxError::Start)
+    }
+
+    /// Stop filtering.
+    ///
+    /// This may be called again, or without a filter ever being set, the kernel ignores stopping a filter that isn't running.
+    pub fn stop(&mut self) -> Result<(), DemuxError> {
+        stop(self.file.as_fd()).map_err(DemuxError::Stop)
+    }
+
+    /// Set the size of the kernel buffer holding received data for this instance.
+    ///
+    /// The default buffer is small (8KB) and overflows easily when tapping a whole multiplex, which makes reads fail with `EOVERFLOW` and packets get dropped.
+    /// This should be called before a filter starts, so before [start](Self::start) or setting up a filter that starts immediately.
+    pub fn set_buffer_size(&mut self, bytes: u32) -> Result<(), DemuxError> {
+        set_buffer_size(self.file.as_fd(), bytes as u64).map_err(DemuxError::BufferSize)
+    }
+
+    /// Read the System Time Counter of given decoder, useful for A/V sync or correlating PCR against the decoder clock.
+    ///
+    /// Many USB devices don't implement a hardware STC, in which case this fails with [`NoHardwareStc`](DemuxError::NoHardwareStc).
+    pub fn get_stc(&self, num: u32) -> Result<Stc, DemuxError> {
+        let raw = get_stc(self.file.as_fd(), num).map_err(|e| match e {
+            Errno::ENODEV => DemuxError::NoHardwareStc,
+            e => DemuxError::StcQuery(e),
+        })?;
+        Ok(Stc::from(raw))
     }
 
-    pub fn stop(&mut self) {
-        stop(self.file.as_fd()).unwrap()
+    /// Get the PIDs currently routed to the first hardware decoder.
+    pub fn get_pes_pids(&self) -> Result<DecoderPids, DemuxError> {
+        let pids = get_pes_pids(self.file.as_fd()).map_err(DemuxError::PesPidsQuery)?;
+        Ok(DecoderPids::from(pids))
     }
 
     /// Setup a general filter to let some packets through.
-    pub fn set_filter(&mut self, filter: &DmxSctFilterParams) {
-        set_filter(self.file.as_fd(), filter).unwrap()
+    pub fn set_filter(&mut self, filter: &DmxSctFilterParams) -> Result<(), DemuxError> {
+        set_filter(self.file.as_fd(), filter).map_err(DemuxError::SetFilter)
+    }
+
+    /// Setup a filter to let the PES packets of an elementary stream through, starting immediately.
+    ///
+    /// Packets are taken from the frontend and sent to given output, for example the decoder or the DVR device for playback or recording.
+    pub fn set_pes_filter(
+        &mut self,
+        pid: Pid,
+        pes_type: DmxTsPes,
+        output: DmxOutput,
+    ) -> Result<(), DemuxError> {
+        let filter = DmxPesFilterParams {
+            pid: pid.value(),
+            input: DmxInput::DMX_IN_FRONTEND,
+            output,
+            pes_type,
+            flags: DMX_IMMEDIATE_START,
+        };
+
+        set_pes_filter(self.file.as_fd(), &filter).map_err(DemuxError::SetFilter)
+    }
+
+    /// Multiplex all packets of given PIDs into a single stream of raw TS packets, starting immediately.
+    ///
+    /// Use [read_ts_packets](Self::read_ts_packets) to receive them. At least one PID is needed.
+    pub fn tap_pids(&mut self, pids: &[Pid]) -> Result<(), DemuxError> {
+        let (first, rest) = pids.split_first().ok_or(DemuxError::NoPid)?;
+        self.set_pes_filter(
+            *first,
+            DmxTsPes::DMX_PES_OTHER,
+            DmxOutput::DMX_OUT_TSDEMUX_TAP,
+        )?;
+        for pid in rest {
+            add_pid(self.file.as_fd(), pid.value()).map_err(DemuxError::Pid)?;
+        }
+        Ok(())
+    }
+
+    /// Stop receiving packets for a PID previously added with [tap_pids](Self::tap_pids).
+    pub fn untap_pid(&mut self, pid: Pid) -> Result<(), DemuxError> {
+        remove_pid(self.file.as_fd(), pid.value()).map_err(DemuxError::Pid)
     }
 
-    /// Setup this instance to only filter a single valid packet with provided PID and optional Table ID, starting immediately.
-    pub fn filter_one(&mut self, pid: u16, table_id: Option<u8>, timeout: Option<Duration>) {
+    /// Receive up to `count` whole TS packets from a tap set with [tap_pids](Self::tap_pids).
+    pub fn read_ts_packets(
+        &mut self,
+        count: usize,
+    ) -> Result<Vec<[u8; TS_PACKET_LENGTH]>, std::io::Error> {
+        let mut buf = vec![0; count * TS_PACKET_LENGTH];
+        let read = self.read(&mut buf)?;
+        // The kernel only ever hands out whole packets, but make sure of it
+        buf.truncate(read - (read % TS_PACKET_LENGTH));
+
+        Ok(buf
+            .chunks_exact(TS_PACKET_LENGTH)
+            .map(|packet| packet.try_into().unwrap())
+            .collect())
+    }
+
+    /// Setup this instance to only filter a single valid packet with provided PID and optional Table ID.
+    ///
+    /// With default `options`, the CRC is checked and filtering starts immediately.
+    pub fn filter_one(
+        &mut self,
+        pid: Pid,
+        table_id: Option<TableId>,
+        timeout: Option<Duration>,
+        options: FilterOptions,
+    ) -> Result<(), DemuxError> {
+        self.filter_table_id(pid, table_id, timeout, options.flags())
+    }
+
+    /// Setup this instance to keep filtering valid packets with provided PID and optional Table ID, starting immediately.
+    ///
+    /// This is required for receiving all sections of a table spanning multiple sections.
+    pub fn filter_continuous(
+        &mut self,
+        pid: Pid,
+        table_id: Option<TableId>,
+        timeout: Option<Duration>,
+    ) -> Result<(), DemuxError> {
+        let options = FilterOptions {
+            oneshot: false,
+            ..FilterOptions::default()
+        };
+        self.filter_table_id(pid, table_id, timeout, options.flags())
+    }
+
+    /// Keep filtering sections with provided PID and optional Table ID, and iterate over them as they arrive.
+    ///
+    /// The demux is moved into the returned stream, which is equivalent to [`SectionStream::new`].
+    pub fn filter_sections(
+        self,
+        pid: Pid,
+        table_id: Option<TableId>,
+    ) -> Result<SectionStream, DemuxError> {
+        SectionStream::new(self, pid, table_id)
+    }
+
+    fn filter_table_id(
+        &mut self,
+        pid: Pid,
+        table_id: Option<TableId>,
+        timeout: Option<Duration>,
+        flags: u32,
+    ) -> Result<(), DemuxError> {
         // Table ID is always the first byte for SI packets.
         // Therefore, add a filter that checks this first byte against provided table_id.
-        let mut inner_filter = DmxFilter::default();
+        let mut builder = SectionFilterBuilder::new();
         if let Some(table_id) = table_id {
-            inner_filter.first_byte_mask(table_id);
+            builder = builder.table_id(table_id);
         }
 
+        self.set_section_filter(pid, builder, timeout, flags)
+    }
+
+    /// Setup a section filter built with a [SectionFilterBuilder] for given PID.
+    ///
+    /// `flags` are a combination of [DMX_CHECK_CRC], [DMX_ONESHOT] and [DMX_IMMEDIATE_START].
+    /// Without `timeout`, reads block until a section arrives. Otherwise, they fail with [`TimedOut`](std::io::ErrorKind::TimedOut) once it expires.
+    pub fn set_section_filter(
+        &mut self,
+        pid: Pid,
+        builder: SectionFilterBuilder,
+        timeout: Option<Duration>,
+        flags: u32,
+    ) -> Result<(), DemuxError> {
+        self.check_crc = self.user_space_crc && flags & DMX_CHECK_CRC != 0;
+        let flags = if self.check_crc {
+            flags & !DMX_CHECK_CRC
+        } else {
+            flags
+        };
         let filter = DmxSctFilterParams {
-            pid,
-            filter: inner_filter,
-            timeout: timeout.map(|d| d.as_millis() as u32).unwrap_or(0),
-            flags: DMX_CHECK_CRC | DMX_ONESHOT | DMX_IMMEDIATE_START, // TODO: Proper thing later
+            pid: pid.value(),
+            filter: builder.build(),
+            timeout: timeout_millis(timeout),
+            flags,
         };
 
-        self.set_filter(&filter);
+        self.set_filter(&filter)
+    }
+
+    /// Check the CRC of sections here instead of letting the kernel do it, for filters set from now on.
+    ///
+    /// The kernel silently drops sections with an invalid CRC. Once checked here, they are reported by [read_one_packet](Self::read_one_packet) and counted in [errors](Self::errors).
+    /// Filters set without [DMX_CHECK_CRC] aren't checked either way.
+    pub fn check_crc_in_user_space(&mut self, enabled: bool) {
+        self.user_space_crc = enabled;
+    }
+
+    /// Sections rejected by [read_one_packet](Self::read_one_packet) since this demux was opened.
+    pub fn errors(&self) -> SectionErrors {
+        self.errors
     }
 
     /// Receive a single data packet from the interface. This implies a properly set-up filter.
+    ///
+    /// A malformed packet is reported as an [`InvalidData`](std::io::ErrorKind::InvalidData) error, holding a [PacketError].
+    /// This includes [`PacketError::CrcMismatch`] when [checking the CRC in user space](Self::check_crc_in_user_space),
+    /// and [`PacketError::Truncated`] when less was received than announced by the section header.
     pub fn read_one_packet(&mut self) -> Result<Packet, std::io::Error> {
-        let mut buf = vec![0; 4096];
-        let read = self.read(&mut buf)?;
+        let mut buf = vec![0; MAX_SECTION_LENGTH];
+        let mut read = self.read_section_bytes(&mut buf)?;
+
+        // The kernel hands out the rest of a section on the next read, if the first one ever stops short
+        let expected = section_total_length(&buf[..read]);
+        if read < expected {
+            read += self.read_section_bytes(&mut buf[read..expected.min(MAX_SECTION_LENGTH)])?;
+        }
         buf.truncate(read);
-        Ok(Packet::from_buf(&buf))
+
+        if read < expected {
+            self.errors.malformed += 1;
+            let error = PacketError::Truncated {
+                expected,
+                received: read,
+            };
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, error));
+        }
+        let packet = Packet::from_buf(&buf).map_err(|e| {
+            self.errors.malformed += 1;
+            std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+        })?;
+        if self.check_crc && !packet.crc_matches() {
+            self.errors.crc_failed += 1;
+            let error = PacketError::CrcMismatch {
+                received: packet.crc,
+                computed: packet.computed_crc(),
+            };
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, error));
+        }
+        Ok(packet)
+    }
+
+    /// Same as [read](Self::read), counting buffer overflows.
+    fn read_section_bytes(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+        self.read(buf).inspect_err(|e| {
+            if e.raw_os_error() == Some(Errno::EOVERFLOW as i32) {
+                self.errors.overflows += 1;
+            }
+        })
+    }
+}
+
+/// Length of a whole section as announced by its header, or 0 if not even the header's first 3 bytes are there.
+fn section_total_length(buf: &[u8]) -> usize {
+    match buf {
+        [_, high, low, ..] => 3 + u16::from_be_bytes([high & 0x0F, *low]) as usize,
+        _ => 0,
     }
 }
 
 // TODO: Get one packet with trait for specific section ?
 
+/// A reading of a decoder's System Time Counter.
+#[derive(Debug, Copy, Clone)]
+pub struct Stc {
+    /// Counter value, in 90kHz units.
+    pub value: u64,
+    /// Counter value as returned by the kernel.
+    pub raw: u64,
+    /// Divisor applied to the raw value to get 90kHz units.
+    pub base: u32,
+}
+
+impl From<DmxStc> for Stc {
+    fn from(value: DmxStc) -> Self {
+        // Base should never be 0, but don't divide by it if that ever happens
+        let divisor = value.base.max(1) as u64;
+        Stc {
+            value: value.stc / divisor,
+            raw: value.stc,
+            base: value.base,
+        }
+    }
+}
+
+/// PIDs routed to a hardware decoder, as returned by [`Demux::get_pes_pids`].
+///
+/// Unset when nothing is routed for that type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DecoderPids {
+    pub audio: Option<Pid>,
+    pub video: Option<Pid>,
+    pub teletext: Option<Pid>,
+    pub subtitle: Option<Pid>,
+    pub pcr: Option<Pid>,
+}
+
+impl From<[u16; 5]> for DecoderPids {
+    fn from(value: [u16; 5]) -> Self {
+        // The kernel reports unused entries as 0xFFFF, which isn't a valid PID
+        let pid = |pid: u16| Pid::new(pid).ok();
+        DecoderPids {
+            audio: pid(value[0]),
+            video: pid(value[1]),
+            teletext: pid(value[2]),
+            subtitle: pid(value[3]),
+            pcr: pid(value[4]),
+        }
+    }
+}
+
+/// Builds the bytes of a section filter for the kernel to match incoming sections against.
+///
+/// The kernel skips the two bytes holding the section length:
+/// filter byte 0 matches the table ID (section byte 0), but filter byte `n` matches section byte `n + 2` for every other position.
+/// To avoid this pitfall, all methods here take offsets within the section, not within the filter.
+/// This means section bytes 1 and 2 can never be matched, and the last byte that can be matched is section byte 17.
+#[derive(Default)]
+pub struct SectionFilterBuilder {
+    filter: DmxFilter,
+}
+
+impl SectionFilterBuilder {
+    /// Offset in the section of the `version_number`/`current_next_indicator` byte.
+    const VERSION_BYTE: usize = 5;
+
+    pub fn new() -> SectionFilterBuilder {
+        SectionFilterBuilder::default()
+    }
+
+    /// Only let sections with given table ID through.
+    pub fn table_id(mut self, table_id: TableId) -> SectionFilterBuilder {
+        self.filter.first_byte_mask(table_id.to_u8());
+        self
+    }
+
+    /// Only let currently applicable sections through, i.e. sections with `current_next_indicator` set.
+    ///
+    /// Sections describing the next version of a table are ignored.
+    pub fn current_only(mut self) -> SectionFilterBuilder {
+        self.section_byte(Self::VERSION_BYTE, 0b0000_0001, 0b0000_0001)
+    }
+
+    /// Only let sections through if the bits set in `mask` of the byte at `section_offset` are equal to the ones in `value`.
+    ///
+    /// Panics if the offset points to the section length (bytes 1 and 2) or past the filter (after byte 17).
+    pub fn section_byte(
+        mut self,
+        section_offset: usize,
+        value: u8,
+        mask: u8,
+    ) -> SectionFilterBuilder {
+        let index = Self::filter_index(section_offset);
+        self.filter.filter[index] = (self.filter.filter[index] & !mask) | (value & mask);
+        self.filter.mask[index] |= mask;
+        self.filter.mode[index] &= !mask;
+        self
+    }
+
+    /// Only let sections through if at least one of the bits set in `mask` of the byte at `section_offset` differs from the ones in `value`.
+    ///
+    /// Panics if the offset points to the section length (bytes 1 and 2) or past the filter (after byte 17).
+    pub fn section_byte_not(
+        mut self,
+        section_offset: usize,
+        value: u8,
+        mask: u8,
+    ) -> SectionFilterBuilder {
+        let index = Self::filter_index(section_offset);
+        self.filter.filter[index] = (self.filter.filter[index] & !mask) | (value & mask);
+        self.filter.mask[index] |= mask;
+        self.filter.mode[index] |= mask;
+        self
+    }
+
+    /// Convert an offset within a section to the corresponding index within the filter.
+    fn filter_index(section_offset: usize) -> usize {
+        match section_offset {
+            0 => 0,
+            1 | 2 => panic!("section length bytes can't be filtered on"),
+            _ => {
+                let index = section_offset - 2;
+                assert!(
+                    index < DmxFilter::default().filter.len(),
+                    "section offset is past the end of the filter"
+                );
+                index
+            }
+        }
+    }
+
+    pub fn build(self) -> DmxFilter {
+        self.filter
+    }
+}
+
+/// Flags of a section filter, see [`Demux::filter_one`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FilterOptions {
+    /// Drop sections with an invalid CRC. Disable for private tables with a non-standard or missing CRC.
+    pub check_crc: bool,
+    /// Stop filtering after the first section.
+    pub oneshot: bool,
+    /// Start filtering right away, without calling [`Demux::start`].
+    pub immediate_start: bool,
+}
+
+impl Default for FilterOptions {
+    fn default() -> Self {
+        FilterOptions {
+            check_crc: true,
+            oneshot: true,
+            immediate_start: true,
+        }
+    }
+}
+
+impl FilterOptions {
+    /// Combination of [DMX_CHECK_CRC], [DMX_ONESHOT] and [DMX_IMMEDIATE_START].
+    pub fn flags(&self) -> u32 {
+        let mut flags = 0;
+        if self.check_crc {
+            flags |= DMX_CHECK_CRC;
+        }
+        if self.oneshot {
+            flags |= DMX_ONESHOT;
+        }
+        if self.immediate_start {
+            flags |= DMX_IMMEDIATE_START;
+        }
+        flags
+    }
+}
+
+/// Filter timeout as understood by the kernel, where 0 means none.
+fn timeout_millis(timeout: Option<Duration>) -> u32 {
+    match timeout {
+        // Don't let a short timeout round down to 0, which would block forever instead
+        Some(timeout) => u32::try_from(timeout.as_millis())
+            .unwrap_or(u32::MAX)
+            .max(1),
+        None => 0,
+    }
+}
+
+/// Tell an expired filter apart from other problems while reading.
+fn read_error(e: std::io::Error) -> DemuxError {
+    match e.kind() {
+        std::io::ErrorKind::TimedOut => DemuxError::Timeout,
+        _ => DemuxError::Read(e),
+    }
+}
+
+/// Continuously receives sections for a PID and optional Table ID.
+///
+/// Unlike [Demux::filter_one], the filter stays active after the first section, so this can be iterated on to follow a table over time.
+pub struct SectionStream {
+    demux: Demux,
+    pid: Pid,
+    table_id: Option<TableId>,
+}
+
+impl SectionStream {
+    /// Start filtering sections for given PID and optional Table ID on provided demux.
+    pub fn new(
+        mut demux: Demux,
+        pid: Pid,
+        table_id: Option<TableId>,
+    ) -> Result<SectionStream, DemuxError> {
+        demux.filter_continuous(pid, table_id, None)?;
+        Ok(SectionStream {
+            demux,
+            pid,
+            table_id,
+        })
+    }
+
+    /// Only receive currently applicable sections, ignoring the ones describing the next version of a table.
+    pub fn current_only(mut self) -> Result<SectionStream, DemuxError> {
+        let mut builder = SectionFilterBuilder::new().current_only();
+        if let Some(table_id) = self.table_id {
+            builder = builder.table_id(table_id);
+        }
+        self.demux.set_section_filter(
+            self.pid,
+            builder,
+            None,
+            DMX_CHECK_CRC | DMX_IMMEDIATE_START,
+        )?;
+        Ok(self)
+    }
+}
+
+impl Iterator for SectionStream {
+    type Item = Result<Packet, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.demux.read_one_packet())
+    }
+}
+
 pub struct PidTableIdPair {
-    pub pid: u16,
-    pub table_id: Option<u8>,
+    pub pid: Pid,
+    pub table_id: Option<TableId>,
 }
 
 /// Receives a single packet for each specified PID and optional Table ID.
@@ -74,12 +618,12 @@ pub fn receive_multiple_single_packets(
     demux_path: &Path,
     pairs: Vec<PidTableIdPair>,
     timeout: Option<Duration>,
-) -> Result<Vec<Packet>, std::io::Error> {
+) -> Result<Vec<Packet>, DemuxError> {
     // First, setup all demuxers for all requested pairs
     let mut demuxers = Vec::new();
     for pair in pairs {
-        let mut demux = Demux::new(demux_path)?;
-        demux.filter_one(pair.pid, pair.table_id, timeout);
+        let mut demux = Demux::new(demux_path).map_err(DemuxError::Open)?;
+        demux.filter_one(pair.pid, pair.table_id, timeout, FilterOptions::default())?;
         demuxers.push(demux);
     }
 
@@ -88,18 +632,97 @@ pub fn receive_multiple_single_packets(
     // Read all demuxers
     let mut packets = Vec::new();
     for mut demux in demuxers.into_iter() {
-        packets.push(demux.read_one_packet()?);
+        packets.push(demux.read_one_packet().map_err(read_error)?);
     }
     Ok(packets)
 }
 
+/// Receives every section of a table for each specified PID and optional Table ID.
+///
+/// Each returned list of packets is ordered by section number, ready to be parsed as a whole.
+/// `timeout` covers all tables together, [`DemuxError::Timeout`] is returned if any of them is still incomplete by then.
+pub fn receive_multiple_tables(
+    demux_path: &Path,
+    pairs: Vec<PidTableIdPair>,
+    timeout: Option<Duration>,
+) -> Result<Vec<Vec<Packet>>, DemuxError> {
+    // First, setup all demuxers for all requested pairs
+    let mut demuxers = Vec::new();
+    for pair in pairs {
+        let mut demux = Demux::new_nonblocking(demux_path).map_err(DemuxError::Open)?;
+        // A PMT always fits in a single section, no need to keep filling a buffer with its repetitions
+        if pair.table_id == Some(TableId::Pmt) {
+            demux.filter_one(pair.pid, pair.table_id, None, FilterOptions::default())?;
+        } else {
+            demux.filter_continuous(pair.pid, pair.table_id, None)?;
+        }
+        demuxers.push(demux);
+    }
+
+    // Read from all demuxers as sections arrive, so none of them overflows while waiting on another
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut assemblers: Vec<SectionAssembler> =
+        demuxers.iter().map(|_| SectionAssembler::new()).collect();
+    let mut tables: Vec<Option<Vec<Packet>>> = demuxers.iter().map(|_| None).collect();
+    while tables.iter().any(Option::is_none) {
+        let poll_timeout = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(DemuxError::Timeout);
+                }
+                PollTimeout::try_from(remaining).unwrap_or(PollTimeout::MAX)
+            }
+            None => PollTimeout::NONE,
+        };
+
+        let pending: Vec<usize> = (0..demuxers.len())
+            .filter(|&i| tables[i].is_none())
+            .collect();
+        let mut fds: Vec<PollFd> = pending
+            .iter()
+            .map(|&i| PollFd::new(demuxers[i].file.as_fd(), PollFlags::POLLIN))
+            .collect();
+        poll(&mut fds, poll_timeout).map_err(|e| DemuxError::Read(e.into()))?;
+        // Overflows are reported as errors, they are read like the rest to clear them
+        let ready: Vec<usize> = pending
+            .into_iter()
+            .zip(&fds)
+            .filter(|(_, fd)| fd.revents().is_some_and(|r| !r.is_empty()))
+            .map(|(i, _)| i)
+            .collect();
+        drop(fds);
+
+        for i in ready {
+            let packet = match demuxers[i].read_one_packet() {
+                Ok(packet) => packet,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+                // Already counted in the demux errors, the section will be repeated
+                Err(e) if is_bad_section(&e) => continue,
+                Err(e) => return Err(read_error(e)),
+            };
+            if assemblers[i].push(packet) {
+                demuxers[i].stop()?;
+                let assembler = std::mem::take(&mut assemblers[i]);
+                tables[i] = Some(assembler.finish().unwrap_or_default());
+            }
+        }
+    }
+    Ok(tables.into_iter().flatten().collect())
+}
+
+/// Whether a read failed because of a single corrupt or lost section, rather than a problem with the device.
+pub(crate) fn is_bad_section(e: &std::io::Error) -> bool {
+    e.kind() == std::io::ErrorKind::InvalidData || e.raw_os_error() == Some(Errno::EOVERFLOW as i32)
+}
+
 /// Receives a single packet for a PID and optional table ID.
 pub fn receive_single_packet(
     demux_path: &Path,
-    pid: u16,
-    table_id: Option<u8>,
+    pid: Pid,
+    table_id: Option<TableId>,
     timeout: Option<Duration>,
-) -> Result<Packet, std::io::Error> {
+) -> Result<Packet, DemuxError> {
     let packets = receive_multiple_single_packets(
         demux_path,
         vec![PidTableIdPair { pid, table_id }],
@@ -108,3 +731,158 @@ pub fn receive_single_packet(
     let p = packets.into_iter().next().unwrap();
     Ok(p)
 }
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::PacketHeader;
+
+    /// Check a section against a filter the same way the kernel does.
+    fn matches(filter: &DmxFilter, section: &[u8]) -> bool {
+        (0..filter.filter.len()).all(|i| {
+            // Section length bytes are skipped
+            let section_index = if i == 0 { 0 } else { i + 2 };
+            let byte = section.get(section_index).copied().unwrap_or(0);
+            (byte ^ filter.filter[i]) & filter.mask[i] == 0
+        })
+    }
+
+    #[test]
+    fn current_only_filter() {
+        let filter = SectionFilterBuilder::new()
+            .table_id(TableId::SdtActual)
+            .current_only()
+            .build();
+
+        let current = [0x42, 0xF0, 0x10, 0x00, 0x01, 0b1100_0011, 0x00, 0x00];
+        let next = [0x42, 0xF0, 0x10, 0x00, 0x01, 0b1100_0010, 0x00, 0x00];
+        let other_table = [0x46, 0xF0, 0x10, 0x00, 0x01, 0b1100_0011, 0x00, 0x00];
+
+        assert_eq!(filter.filter[3], 0b0000_0001);
+        assert_eq!(filter.mask[3], 0b0000_0001);
+        assert!(matches(&filter, &current));
+        assert!(!matches(&filter, &next));
+        assert!(!matches(&filter, &other_table));
+    }
+
+    #[test]
+    fn section_byte_offsets() {
+        // Event Information of a single service, service ID is in section bytes 3 and 4
+        let filter = SectionFilterBuilder::new()
+            .table_id(TableId::EitPresentFollowingActual)
+            .section_byte(3, 0x01, 0xFF)
+            .section_byte(4, 0x02, 0xFF)
+            .build();
+
+        let wanted = [0x4E, 0xF0, 0x10, 0x01, 0x02, 0b1100_0011, 0x00, 0x00];
+        let other_service = [0x4E, 0xF0, 0x10, 0x01, 0x03, 0b1100_0011, 0x00, 0x00];
+
+        assert_eq!(filter.filter[1], 0x01);
+        assert_eq!(filter.filter[2], 0x02);
+        assert!(matches(&filter, &wanted));
+        assert!(!matches(&filter, &other_service));
+    }
+
+    #[test]
+    #[should_panic]
+    fn section_length_not_filterable() {
+        SectionFilterBuilder::new().section_byte(1, 0x00, 0xFF);
+    }
+
+    #[test]
+    fn filter_flags() {
+        assert_eq!(
+            FilterOptions::default().flags(),
+            DMX_CHECK_CRC | DMX_ONESHOT | DMX_IMMEDIATE_START
+        );
+        let options = FilterOptions {
+            check_crc: false,
+            ..FilterOptions::default()
+        };
+        assert_eq!(options.flags(), DMX_ONESHOT | DMX_IMMEDIATE_START);
+    }
+
+    #[test]
+    fn section_lengths() {
+        assert_eq!(section_total_length(&[0x42, 0xF0, 0x10, 0x00]), 0x13);
+        // Private section of the largest size
+        let mut private = vec![0x80, 0xFF, 0xFD, 0x00, 0x01, 0xC1, 0x00, 0x00];
+        private.resize(MAX_SECTION_LENGTH, 0xFF);
+        assert_eq!(section_total_length(&private), MAX_SECTION_LENGTH);
+        // Whatever fits in the read buffer must also be accepted once parsed
+        let packet = Packet::from_buf(&private).unwrap();
+        assert_eq!(
+            packet.data.len(),
+            MAX_SECTION_LENGTH - PacketHeader::LENGTH - 4
+        );
+
+        assert_eq!(section_total_length(&[0x42, 0xF0]), 0);
+    }
+
+    #[test]
+    fn timeouts() {
+        assert_eq!(timeout_millis(None), 0);
+        assert_eq!(timeout_millis(Some(Duration::from_micros(10))), 1);
+        assert_eq!(timeout_millis(Some(Duration::from_millis(100))), 100);
+
+        let timed_out = std::io::Error::from(std::io::ErrorKind::TimedOut);
+        assert!(matches!(read_error(timed_out), DemuxError::Timeout));
+    }
+
+    #[test]
+    #[ignore = "requires a DVB adapter"]
+    fn empty_pid_times_out() {
+        let start = std::time::Instant::now();
+        let result = receive_single_packet(
+            &crate::adapter::demux_path(0, 0),
+            Pid::new(0x1FFE).unwrap(),
+            None,
+            Some(Duration::from_millis(100)),
+        );
+
+        assert!(matches!(result, Err(DemuxError::Timeout)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    #[ignore = "requires a DVB adapter"]
+    fn stop_twice() {
+        let mut demux = Demux::open_adapter(0, 0).unwrap();
+        demux.stop().unwrap();
+
+        demux.filter_continuous(Pid::PAT, None, None).unwrap();
+        demux.stop().unwrap();
+        demux.stop().unwrap();
+    }
+
+    #[test]
+    fn decoder_pids() {
+        let pids = DecoderPids::from([0x0101, 0x0100, 0xFFFF, 0xFFFF, 0x0100]);
+        assert_eq!(pids.audio, Some(Pid::from_bits(0x0101)));
+        assert_eq!(pids.teletext, None);
+        assert_eq!(pids.pcr, Some(Pid::from_bits(0x0100)));
+    }
+
+    #[test]
+    fn bad_sections() {
+        let corrupt = std::io::Error::new(std::io::ErrorKind::InvalidData, "CRC");
+
+        assert!(is_bad_section(&corrupt));
+        assert!(is_bad_section(&std::io::Error::from_raw_os_error(
+            Errno::EOVERFLOW as i32
+        )));
+        assert!(!is_bad_section(&std::io::Error::from_raw_os_error(
+            Errno::ENODEV as i32
+        )));
+    }
+
+    #[test]
+    fn tap_without_pid() {
+        // Never reaches the device, any file will do
+        let mut demux = Demux::new(Path::new("/dev/null")).unwrap();
+        assert!(matches!(demux.tap_pids(&[]), Err(DemuxError::NoPid)));
+    }
+}
@@ -1,9 +1,14 @@
 use std::{ffi::c_int, num::ParseIntError};
 
 use nix::errno::Errno;
-use rdvb_os_linux::error::PropertyError;
+use rdvb_os_linux::{
+    error::PropertyError,
+    frontend::data::{FeDeliverySystem, FeModulation},
+};
 use thiserror::Error;
 
+use crate::{frontend::DeliverySystem, utils::ValueBounds};
+
 //
 // -----
 
@@ -22,6 +27,150 @@ pub enum VdrParseError {
 //
 // -----
 
+/// A channel can't be written to a VDR channel file, as its source can't be told.
+#[derive(Error, Debug)]
+#[error(
+    "no VDR source for this {0:?} channel, satellite channels need their orbital position from the NIT"
+)]
+pub struct UnknownVdrSource(pub DeliverySystem);
+
+//
+// -----
+
+/// A modulation reported by the frontend has no equivalent in VDR channel files.
+#[derive(Error, Debug)]
+#[error("modulation {0:?} can't be written to a VDR channel file")]
+pub struct UnmappableModulation(pub FeModulation);
+
+//
+// -----
+
+/// A delivery system reported by the kernel that this crate can't tune to.
+#[derive(Error, Debug)]
+#[error("delivery system {0:?} is not supported")]
+pub struct UnsupportedDeliverySystem(pub FeDeliverySystem);
+
+//
+// -----
+
+#[derive(Error, Debug)]
+pub enum ChannelsConfParseError {
+    #[error("the channel line is missing at least 1 column")]
+    MissingColumn,
+    #[error("expected an int for field contents")]
+    IntParse(ParseIntError),
+    #[error("an unexpected token was found as parameter")]
+    UnexpectedValue,
+}
+
+//
+// -----
+
+#[derive(Error, Debug)]
+pub enum Dvbv5ParseError {
+    #[error("a line is neither a channel name nor a property")]
+    UnexpectedLine,
+    #[error("a property was found before any channel name")]
+    PropertyOutsideChannel,
+    #[error("expected an int for property value")]
+    IntParse(ParseIntError),
+    #[error("an unexpected value was found for a property")]
+    UnexpectedValue,
+}
+
+//
+// -----
+
+#[derive(Error, Debug)]
+pub enum DescriptorParseError {
+    #[error("descriptor is shorter than its contents require")]
+    TooShort,
+    #[error("a value outside of accepted variants was found in descriptor")]
+    UnexpectedValue,
+}
+
+//
+// -----
+
+#[derive(Error, Debug)]
+#[error("a language code must be 3 ASCII letters")]
+pub struct LanguageCodeParseError;
+
+//
+// -----
+
+#[derive(Error, Debug)]
+#[error("PID {0:#06X} doesn't fit in 13 bits")]
+pub struct PidOutOfRange(pub u16);
+
+//
+// -----
+
+#[derive(Error, Debug)]
+pub enum PacketError {
+    #[error("packet is shorter than its header and CRC")]
+    TooShort,
+    #[error("section length is outside of the allowed range")]
+    BadSectionLength,
+    #[error("CRC is {received:#010X}, but {computed:#010X} was expected from the section")]
+    CrcMismatch { received: u32, computed: u32 },
+    #[error("section is {expected} bytes long, but only {received} were received")]
+    Truncated { expected: usize, received: usize },
+}
+
+//
+// -----
+
+/// A field of a PSI/SI section lies past the end of its data, usually because the section was truncated.
+#[derive(Error, Debug)]
+#[error("{table} section is truncated at offset {offset}")]
+pub struct SiParseError {
+    /// Short name of the table being parsed, like `"PMT"`
+    pub table: &'static str,
+    /// Offset in the section payload (after the common header) of the field that couldn't be read
+    pub offset: usize,
+}
+
+//
+// -----
+
+#[derive(Error, Debug)]
+pub enum SectionParseError {
+    #[error("invalid section header")]
+    Packet(PacketError),
+    #[error("invalid section contents")]
+    Si(SiParseError),
+}
+
+//
+// -----
+
+#[derive(Error, Debug)]
+pub enum PesError {
+    #[error("PES packet is shorter than its header or announced length")]
+    TooShort,
+    #[error("PES packet doesn't start with the start code prefix")]
+    BadStartCode,
+    #[error("marker bits don't have their expected value")]
+    BadMarker,
+}
+
+//
+// -----
+
+#[derive(Error, Debug)]
+pub enum TsError {
+    #[error("TS packet is shorter than 188 bytes")]
+    TooShort,
+    #[error("TS packet doesn't start with the sync byte")]
+    BadSyncByte,
+    #[error("adaptation field is longer than the packet")]
+    BadAdaptationFieldLength,
+}
+
+//
+// -----
+
 #[derive(Error, Debug)]
 pub enum FrontendError {
     #[error("problem while opening frontend")]
@@ -34,6 +183,70 @@ pub enum FrontendError {
     Property(PropertyError),
     #[error("results of a query indicate an error")]
     Retrieve(DtvError),
+    #[error("frontend was opened as read-only")]
+    ReadOnly,
+    #[error("frequency {0} is outside of the range supported by the frontend ({1})")]
+    FrequencyOutOfRange(u32, ValueBounds),
+    #[error("{0} is not used by the requested delivery system")]
+    UnsupportedTuneParameter(&'static str),
+}
+
+//
+// -----
+
+#[derive(Error, Debug)]
+pub enum DemuxError {
+    #[error("problem while opening demux")]
+    Open(std::io::Error),
+    #[error("problem while reading from demux")]
+    Read(std::io::Error),
+    #[error("no matching section was received before the filter timed out")]
+    Timeout,
+    #[error("failed to start filtering")]
+    Start(Errno),
+    #[error("failed to stop filtering")]
+    Stop(Errno),
+    #[error("failed to set filter, one may already be running")]
+    SetFilter(Errno),
+    #[error("failed to add or remove a PID from filter")]
+    Pid(Errno),
+    #[error("no PID was given to tap")]
+    NoPid,
+    #[error("failed to set size of the kernel buffer")]
+    BufferSize(Errno),
+    #[error("failed to query the system time counter")]
+    StcQuery(Errno),
+    #[error("device has no hardware system time counter")]
+    NoHardwareStc,
+    #[error("failed to query the PIDs routed to the decoder")]
+    PesPidsQuery(Errno),
+}
+
+//
+// -----
+
+/// Problems that stop a scan altogether, like the adapter being disconnected.
+///
+/// Frequencies that just can't be received properly are skipped instead.
+#[derive(Error, Debug)]
+pub enum ScanError {
+    #[error("problem with the frontend")]
+    Frontend(FrontendError),
+    #[error("problem with the demux")]
+    Demux(DemuxError),
+}
+
+//
+// -----
+
+#[derive(Error, Debug)]
+pub enum FetchPmtError {
+    #[error("problem with the demux")]
+    Demux(DemuxError),
+    #[error("received an invalid PAT or PMT")]
+    Si(SiParseError),
+    #[error("service {0} is not listed in the PAT")]
+    ServiceNotFound(u16),
 }
 
 //
@@ -45,4 +258,64 @@ pub enum DtvError {
     NotRan,
     #[error("kernel application returned an error")]
     Reported(c_int),
+    #[error("the kernel returned {0}, which isn't a known value for this property")]
+    UnexpectedValue(u32),
+    #[error(
+        "the statistic was reported as a count where a value was expected, or the other way around"
+    )]
+    UnexpectedScale,
 }
+
+//
+// -----
+
+/// Any error returned by this crate, for callers that chain several steps like scanning then exporting channels.
+///
+/// Functions still return their own error type, this converts from all of them so `?` can be used with [Result].
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Frontend(#[from] FrontendError),
+    #[error(transparent)]
+    Demux(#[from] DemuxError),
+    #[error(transparent)]
+    Scan(#[from] ScanError),
+    #[error(transparent)]
+    FetchPmt(#[from] FetchPmtError),
+    #[error(transparent)]
+    Dtv(#[from] DtvError),
+    #[error(transparent)]
+    VdrParse(#[from] VdrParseError),
+    #[error(transparent)]
+    UnknownVdrSource(#[from] UnknownVdrSource),
+    #[error(transparent)]
+    UnmappableModulation(#[from] UnmappableModulation),
+    #[error(transparent)]
+    UnsupportedDeliverySystem(#[from] UnsupportedDeliverySystem),
+    #[error(transparent)]
+    ChannelsConfParse(#[from] ChannelsConfParseError),
+    #[error(transparent)]
+    Dvbv5Parse(#[from] Dvbv5ParseError),
+    #[error(transparent)]
+    DescriptorParse(#[from] DescriptorParseError),
+    #[error(transparent)]
+    LanguageCodeParse(#[from] LanguageCodeParseError),
+    #[error(transparent)]
+    PidOutOfRange(#[from] PidOutOfRange),
+    #[error(transparent)]
+    Packet(#[from] PacketError),
+    #[error(transparent)]
+    SiParse(#[from] SiParseError),
+    #[error(transparent)]
+    SectionParse(#[from] SectionParseError),
+    #[error(transparent)]
+    Pes(#[from] PesError),
+    #[error(transparent)]
+    Ts(#[from] TsError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Errno(#[from] Errno),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
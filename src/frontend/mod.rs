@@ -2,7 +2,7 @@ pub mod properties;
 
 use std::{
     collections::BTreeSet,
-    ffi::{CStr, c_char},
+    ffi::c_char,
     fmt::{Display, Formatter, Result as FmtResult},
     fs::File,
     mem::MaybeUninit,
@@ -13,20 +13,29 @@ use std::{
 };
 
 use crate::{
-    error::FrontendError,
+    adapter::frontend_path,
+    error::{FrontendError, UnsupportedDeliverySystem},
     frontend::properties::{
-        get::{EnumerateDeliverySystems, PropertyQuery, SignalStrength},
+        get::{
+            CurrentDeliverySystem, DtvStats, EnumerateDeliverySystems, PropertyQuery,
+            SignalStrength, StatResult, ValueStat, checked_union,
+        },
         set::{
-            BandwidthHz, DeliverySystem as DeliverySystemSet, Frequency, SetPropertyQuery, Tune,
+            BandwidthHz, Clear, DeliverySystem as DeliverySystemSet, Frequency, InnerFec,
+            Modulation, SetPropertyQuery, StreamId, SymbolRate, Tone, Tune, Voltage,
         },
     },
     utils::ValueBounds,
 };
+use nix::errno::Errno;
 use properties::get::QueryDescription;
 use rdvb_os_linux::frontend::{
-    data::{DvbFrontendInfo, FeCaps, FeDeliverySystem, FeStatus},
+    data::{
+        DvbFrontendInfo, FeCaps, FeCodeRate, FeDeliverySystem, FeGuardInterval, FeModulation,
+        FeSpectralInversion, FeStatus, FeTransmitMode,
+    },
     functions::{get_info, get_set_properties_raw, read_status},
-    property::DtvProperty,
+    property::{Command, DtvProperty},
 };
 
 //
@@ -36,6 +45,9 @@ pub struct Frontend {
     file: File,
     write: bool,
     info: Info,
+    /// Delivery system and bandwidth last set by [`tune`](Self::tune), if nothing else was set since.
+    tuned_system: Option<(DeliverySystem, BandwidthHz)>,
+    untune_on_drop: bool,
 }
 
 type Result<T> = std::result::Result<T, FrontendError>;
@@ -56,11 +68,18 @@ impl Frontend {
 
         Ok(Frontend {
             file,
-            write: false,
+            write: writeable,
             info,
+            tuned_system: None,
+            untune_on_drop: false,
         })
     }
 
+    /// Open the frontend of an adapter by index, see [open](Self::open).
+    pub fn open_adapter(adapter: u32, frontend: u32, writeable: bool) -> Result<Frontend> {
+        Frontend::open(&frontend_path(adapter, frontend), writeable)
+    }
+
     pub fn is_writeable(&self) -> bool {
         self.write
     }
@@ -69,17 +88,30 @@ impl Frontend {
         &self.info
     }
 
-    // TODO: Should status require mutability ?
+    /// Round `frequency` to the nearest one the frontend accepts, see [`FrequencyInfo::align`].
+    pub fn align_frequency(&self, frequency: u32) -> u32 {
+        self.info.frequency.align(frequency)
+    }
+
     /// Retrieve the status of the frontend.
     ///
+    /// Reading the status doesn't change anything on the device, so this does not require mutability.
+    ///
     /// If this fails while the frontend isn't tuned, this may mean that the system is missing a required firmware.
-    /// Check `dmesg` if that is the case.
+    /// See [`diagnose`](Self::diagnose) to tell these cases apart.
     pub fn status(&self) -> Result<FeStatus> {
         Ok(FeStatus::from(
             read_status(self.file.as_fd()).map_err(FrontendError::StatusQuery)?,
         ))
     }
 
+    /// Retrieve the status of the frontend, interpreting the usual failures.
+    ///
+    /// Errors that don't match a known pattern are returned as-is.
+    pub fn diagnose(&self) -> Result<FrontendDiagnosis> {
+        diagnose_status(self.status())
+    }
+
     pub fn properties(&mut self, props: &mut [QueryDescription]) -> Result<()> {
         // Build requests
         let mut memory = props
@@ -112,7 +144,19 @@ impl Frontend {
 
     // For now, it is convenient to just have a slice of DtvProperty as it already is setup in memory correctly for IOCTL
     // TODO: That should require &mut self, look into File to see how they do it
+    /// Send properties to the frontend, like tuning parameters.
+    ///
+    /// Fails with [`FrontendError::ReadOnly`] if the frontend wasn't opened as writeable.
     pub fn set_properties(&mut self, props: &mut [DtvProperty]) -> Result<()> {
+        // Anything may be changed from here, don't assume the last tuned system is still set
+        self.tuned_system = None;
+        self.write_properties(props)
+    }
+
+    fn write_properties(&mut self, props: &mut [DtvProperty]) -> Result<()> {
+        if !self.write {
+            return Err(FrontendError::ReadOnly);
+        }
         get_set_properties_raw(self.file.as_fd(), true, props.len(), props.as_mut_ptr())
             .map_err(FrontendError::Property)?;
         Ok(())
@@ -120,54 +164,152 @@ impl Frontend {
 
     /// Tunes the frontend for a given system, bandwidth and frequency.
     ///
-    /// This is equivalent to using [`set_properties`](Self::set_properties) with [`Frequency`], [`DeliverySystem`], [`BandwidthHz`] and [`Tune`] properties.
-    /// This function is here for convenience.
+    /// This is equivalent to executing a [`TuneRequest`] with only a bandwidth set.
+    /// If the system and bandwidth are the same as the last call, only the frequency is sent, like [`set_frequency`](Self::set_frequency).
+    ///
+    /// `frequency` is first [aligned](Self::align_frequency) on the step size of the frontend, as some drivers reject other ones.
+    /// Fails with [`FrontendError::FrequencyOutOfRange`] if the frontend reports it can't receive `frequency`.
     pub fn tune(
         &mut self,
         frequency: u32,
         delivery_system: DeliverySystem,
         bandwidth: BandwidthHz,
+    ) -> Result<()> {
+        let range = self.info.frequency.frequency_range;
+        // Some drivers don't report a range at all
+        if range.max != 0 && !range.contains(frequency) {
+            return Err(FrontendError::FrequencyOutOfRange(frequency, range));
+        }
+        let frequency = self.align_frequency(frequency);
+
+        if self.tuned_system == Some((delivery_system, bandwidth)) {
+            return self.set_frequency(frequency);
+        }
+
+        TuneRequest::new(frequency, delivery_system)
+            .bandwidth(bandwidth)
+            .execute(self)?;
+        self.tuned_system = Some((delivery_system, bandwidth));
+        Ok(())
+    }
+
+    /// Release the transponder the frontend is tuned to.
+    ///
+    /// `DTV_CLEAR` resets every parameter cached by the kernel, which is the proper way to leave the adapter for the next user.
+    /// Otherwise, the tuner may stay powered and locked after the frontend is closed.
+    pub fn untune(&mut self) -> Result<()> {
+        self.set_properties(&mut [Clear {}.property()])
+    }
+
+    /// Whether to [untune](Self::untune) when this is dropped, disabled by default.
+    ///
+    /// Only has an effect on writeable frontends. Errors while dropping are ignored.
+    pub fn set_untune_on_drop(&mut self, untune_on_drop: bool) {
+        self.untune_on_drop = untune_on_drop;
+    }
+
+    /// Tunes the frontend to another frequency, keeping every other parameter as it was.
+    ///
+    /// This only sends the [`Frequency`] and [`Tune`] properties, which is quicker when going through many frequencies of the same system.
+    pub fn set_frequency(&mut self, frequency: u32) -> Result<()> {
+        let freq = Frequency::new(frequency);
+        let tune = Tune {};
+        self.write_properties(&mut [freq.property(), tune.property()])
+    }
+
+    /// Tune the frontend to a satellite transponder.
+    ///
+    /// `frequency` is the intermediate frequency in kHz, after the LNB. `voltage` and `tone` are sent to the LNB to select polarization and band.
+    pub fn tune_satellite(
+        &mut self,
+        frequency: u32,
+        delivery_system: DeliverySystem,
+        symbol_rate: u32,
+        voltage: Voltage,
+        tone: Tone,
     ) -> Result<()> {
         let freq = Frequency::new(frequency);
         let del_sys = DeliverySystemSet::new(delivery_system.into());
+        let symbol_rate = SymbolRate::new(symbol_rate);
         let tune = Tune {};
         self.set_properties(&mut [
-            freq.property(),
-            bandwidth.property(),
             del_sys.property(),
+            voltage.property(),
+            tone.property(),
+            freq.property(),
+            symbol_rate.property(),
             tune.property(),
         ])
     }
 
+    /// Tune to `request` after resetting everything, for switching between delivery systems.
+    ///
+    /// Parameters of the previous system (like the PLP or guard interval) may otherwise be kept by some drivers, which then fail to lock.
+    /// Each step is sent separately, in the order the kernel expects:
+    /// 1. `DTV_CLEAR`, to reset all cached parameters,
+    /// 2. The delivery system alone, as it decides which other parameters are accepted,
+    /// 3. All parameters of the request, followed by `DTV_TUNE`.
+    pub fn retune(&mut self, request: &TuneRequest) -> Result<()> {
+        request.validate()?;
+
+        self.set_properties(&mut [Clear {}.property()])?;
+        self.set_properties(&mut [
+            DeliverySystemSet::new(request.delivery_system.into()).property()
+        ])?;
+        let mut props = request.parameter_properties();
+        props.push(Tune {}.property());
+        self.set_properties(&mut props)
+    }
+
+    /// Tunes the frontend like [`tune`](Self::tune), then blocks execution until it has a lock on a transponder.
+    ///
+    /// The returned outcome holds the last status read from the frontend, so there is no need to query it again right after.
+    pub fn tune_and_wait(
+        &mut self,
+        frequency: u32,
+        delivery_system: DeliverySystem,
+        bandwidth: BandwidthHz,
+        timeout: Option<Duration>,
+        poll_interval: Option<Duration>,
+    ) -> Result<TuneOutcome> {
+        self.tune(frequency, delivery_system, bandwidth)?;
+        wait_for_lock_outcome(|| self.status(), timeout, poll_interval)
+    }
+
     /// Blocks execution until the tuned frontend has a lock on a transponder.
     ///
-    /// Returns `true` if the frontend locked in successfully, `false` otherwise.
+    /// On timeout, the returned outcome tells whether any signal was present, to tell an empty frequency from wrong parameters.
     pub fn wait_for_lock(
         &self,
         timeout: Option<Duration>,
         poll_interval: Option<Duration>,
-    ) -> Result<bool> {
-        let poll_interval = poll_interval.unwrap_or(Duration::from_millis(50));
+    ) -> Result<LockOutcome> {
+        Ok(wait_for_lock_outcome(|| self.status(), timeout, poll_interval)?.into())
+    }
 
-        let start_time = Instant::now();
-        loop {
-            // Check if locked
-            if self.status()?.has_lock() {
-                return Ok(true);
-            }
-            if let Some(timeout) = timeout {
-                // Timeout
-                if (Instant::now() - start_time) > timeout {
-                    return Ok(false);
-                }
-            }
-            sleep(poll_interval);
-        }
+    /// Blocks execution until the tuned frontend detects any signal on the current frequency.
+    ///
+    /// This is much quicker to settle than a lock, which makes it a good way to skip empty frequencies early.
+    /// Returns `true` if a signal was found, `false` otherwise.
+    pub fn wait_for_signal(
+        &self,
+        timeout: Option<Duration>,
+        poll_interval: Option<Duration>,
+    ) -> Result<bool> {
+        poll_until(
+            || {
+                let status = self.status()?;
+                Ok(status.has_signal() || status.has_lock())
+            },
+            timeout,
+            poll_interval,
+        )
     }
 
     /// Return a list of all delivery systems (DVB-T, DVB-T2, SVB-S...) this frontend supports.
     ///
     /// This is equivalent to using `properties` with `EnumerateDeliverySystems` property query. This function is for convenience.
+    /// Systems without a [DeliverySystem] equivalent, such as ATSC or ISDB, are left out.
     pub fn list_systems(&mut self) -> Result<BTreeSet<DeliverySystem>> {
         let mut enumerate_systems = EnumerateDeliverySystems::query();
         self.properties(&mut [enumerate_systems.desc()])?;
@@ -175,7 +317,22 @@ impl Frontend {
             .retrieve()
             .map_err(FrontendError::Retrieve)?
             .0;
-        Ok(enumerate.iter().map(|s| (*s).into()).collect())
+        Ok(enumerate
+            .iter()
+            .filter_map(|s| DeliverySystem::try_from(*s).ok())
+            .collect())
+    }
+
+    /// Return the delivery system the frontend is currently set to.
+    ///
+    /// Some drivers silently fall back to another system than the one requested, this allows checking after tuning.
+    pub fn delivery_system(&mut self) -> Result<FeDeliverySystem> {
+        let mut system = CurrentDeliverySystem::query();
+        self.properties(&mut [system.desc()])?;
+        system
+            .retrieve()
+            .map(|system| system.0)
+            .map_err(FrontendError::Retrieve)
     }
 
     /// Get a reading of the strength of the signal being received.
@@ -186,6 +343,430 @@ impl Frontend {
         self.properties(&mut [strength.desc()])?;
         strength.retrieve().map_err(FrontendError::Retrieve)
     }
+
+    /// Read a single property for any command, as the raw `data` field.
+    ///
+    /// This is an escape hatch for commands without a dedicated [`PropertyQuery`].
+    /// Whether `data` is the right interpretation of the property for `command` is up to the caller.
+    pub fn get_raw(&mut self, command: Command) -> Result<u32> {
+        let property = self.query_batch(&[command])?.remove(0);
+        let u = checked_union(property).map_err(FrontendError::Retrieve)?;
+        // SAFETY: Any value is valid for a u32
+        Ok(unsafe { u.data })
+    }
+
+    /// Set a single property for any command, from a raw `data` value.
+    ///
+    /// This is an escape hatch for commands without a dedicated [`SetPropertyQuery`].
+    pub fn set_raw(&mut self, command: Command, data: u32) -> Result<()> {
+        self.set_properties(&mut [DtvProperty::new_data(command, data)])
+    }
+
+    /// Read every layer of a statistic, like `DTV_STAT_SIGNAL_STRENGTH` or `DTV_STAT_CNR`.
+    ///
+    /// Unlike the single-value queries such as [`signal_strength`](Self::signal_strength), this works for systems reporting one statistic per layer.
+    pub fn read_stats(&mut self, command: Command) -> Result<DtvStats> {
+        Ok(self.read_stats_batch(&[command])?.remove(0))
+    }
+
+    /// Read several statistics like [`read_stats`](Self::read_stats), in a single request to the frontend.
+    pub fn read_stats_batch(&mut self, commands: &[Command]) -> Result<Vec<DtvStats>> {
+        self.query_batch(commands)?
+            .into_iter()
+            .map(|property| {
+                checked_union(property)
+                    .and_then(DtvStats::from_property)
+                    .map_err(FrontendError::Retrieve)
+            })
+            .collect()
+    }
+
+    /// Read back all parameters the frontend is currently tuned with, in a single request.
+    ///
+    /// Parameters that don't apply to the current delivery system, or that the driver doesn't report, are left unset.
+    pub fn current_parameters(&mut self) -> Result<TuningParameters> {
+        let mut values = self
+            .query_batch(&[
+                Command::DTV_FREQUENCY,
+                Command::DTV_DELIVERY_SYSTEM,
+                Command::DTV_MODULATION,
+                Command::DTV_BANDWIDTH_HZ,
+                Command::DTV_INVERSION,
+                Command::DTV_SYMBOL_RATE,
+                Command::DTV_INNER_FEC,
+                Command::DTV_CODE_RATE_HP,
+                Command::DTV_CODE_RATE_LP,
+                Command::DTV_GUARD_INTERVAL,
+                Command::DTV_TRANSMISSION_MODE,
+                Command::DTV_STREAM_ID,
+            ])?
+            .into_iter()
+            // SAFETY: Any value is valid for a u32
+            .map(|property| checked_union(property).ok().map(|u| unsafe { u.data }));
+        let mut next = || values.next().flatten();
+
+        Ok(TuningParameters {
+            frequency: next(),
+            delivery_system: next().and_then(|v| FeDeliverySystem::try_from(v).ok()),
+            modulation: next().and_then(|v| FeModulation::try_from(v).ok()),
+            bandwidth: next().and_then(BandwidthHz::from_value),
+            inversion: next().and_then(|v| FeSpectralInversion::try_from(v).ok()),
+            symbol_rate: next(),
+            inner_fec: next().and_then(|v| FeCodeRate::try_from(v).ok()),
+            code_rate_hp: next().and_then(|v| FeCodeRate::try_from(v).ok()),
+            code_rate_lp: next().and_then(|v| FeCodeRate::try_from(v).ok()),
+            guard_interval: next().and_then(|v| FeGuardInterval::try_from(v).ok()),
+            transmission_mode: next().and_then(|v| FeTransmitMode::try_from(v).ok()),
+            stream_id: next(),
+            frequency_step_size: self.info.frequency.frequency_step_size,
+        })
+    }
+
+    /// Query several commands in a single request, returning the raw properties in the same order.
+    fn query_batch(&mut self, commands: &[Command]) -> Result<Vec<Option<DtvProperty>>> {
+        let mut memory: Vec<Option<DtvProperty>> = commands.iter().map(|_| None).collect();
+        let mut descs = commands
+            .iter()
+            .zip(memory.iter_mut())
+            .map(|(&command, property)| QueryDescription { command, property })
+            .collect::<Vec<_>>();
+        self.properties(&mut descs)?;
+        Ok(memory)
+    }
+
+    /// Read the status and all signal statistics every `interval`, forever.
+    ///
+    /// Useful to aim an antenna. All statistics are read in a single request per report.
+    pub fn monitor(&mut self, interval: Duration) -> impl Iterator<Item = Result<SignalReport>> {
+        let mut first = true;
+        std::iter::from_fn(move || {
+            if !first {
+                sleep(interval);
+            }
+            first = false;
+            Some(self.signal_report())
+        })
+    }
+
+    fn signal_report(&mut self) -> Result<SignalReport> {
+        let status = self.status()?;
+        let mut stats = self
+            .read_stats_batch(&[
+                Command::DTV_STAT_SIGNAL_STRENGTH,
+                Command::DTV_STAT_CNR,
+                Command::DTV_STAT_PRE_ERROR_BIT_COUNT,
+                Command::DTV_STAT_PRE_TOTAL_BIT_COUNT,
+                Command::DTV_STAT_POST_ERROR_BIT_COUNT,
+                Command::DTV_STAT_POST_TOTAL_BIT_COUNT,
+            ])?
+            .into_iter()
+            .map(DtvStats::first);
+        let mut next = || stats.next().flatten();
+
+        Ok(SignalReport {
+            status,
+            strength: value_stat(next()),
+            cnr: value_stat(next()),
+            pre_ber: bit_error_rate(next(), next()),
+            post_ber: bit_error_rate(next(), next()),
+        })
+    }
+}
+
+/// Parameters a frontend is tuned with, as returned by [`Frontend::current_parameters`].
+#[derive(Debug)]
+pub struct TuningParameters {
+    pub frequency: Option<u32>,
+    pub delivery_system: Option<FeDeliverySystem>,
+    pub modulation: Option<FeModulation>,
+    pub bandwidth: Option<BandwidthHz>,
+    pub inversion: Option<FeSpectralInversion>,
+    pub symbol_rate: Option<u32>,
+    pub inner_fec: Option<FeCodeRate>,
+    pub code_rate_hp: Option<FeCodeRate>,
+    pub code_rate_lp: Option<FeCodeRate>,
+    pub guard_interval: Option<FeGuardInterval>,
+    pub transmission_mode: Option<FeTransmitMode>,
+    /// PLP or input stream ID
+    pub stream_id: Option<u32>,
+    /// Step size of the frontend, `frequency` is always a multiple of it (when not zero)
+    pub frequency_step_size: u32,
+}
+
+/// A single reading of the signal, as yielded by [`Frontend::monitor`].
+#[derive(Debug)]
+pub struct SignalReport {
+    pub status: FeStatus,
+    pub strength: Option<ValueStat>,
+    /// Carrier to noise ratio
+    pub cnr: Option<ValueStat>,
+    /// Bit error rate before the inner code correction
+    pub pre_ber: Option<f64>,
+    /// Bit error rate after the inner code correction
+    pub post_ber: Option<f64>,
+}
+
+fn value_stat(stat: Option<StatResult>) -> Option<ValueStat> {
+    match stat {
+        Some(StatResult::Value(value)) => Some(value),
+        _ => None,
+    }
+}
+
+fn bit_error_rate(errors: Option<StatResult>, total: Option<StatResult>) -> Option<f64> {
+    match (errors, total) {
+        (Some(StatResult::Count(errors)), Some(StatResult::Count(total))) if total > 0 => {
+            Some(errors as f64 / total as f64)
+        }
+        _ => None,
+    }
+}
+
+/// Calls `check` every `poll_interval` until it returns `true` or `timeout` expires.
+///
+/// Returns `true` if `check` succeeded before timing out, `false` otherwise.
+pub(crate) fn poll_until<F>(
+    mut check: F,
+    timeout: Option<Duration>,
+    poll_interval: Option<Duration>,
+) -> Result<bool>
+where
+    F: FnMut() -> Result<bool>,
+{
+    let poll_interval = poll_interval.unwrap_or(Duration::from_millis(50));
+
+    let start_time = Instant::now();
+    loop {
+        if check()? {
+            return Ok(true);
+        }
+        if let Some(timeout) = timeout {
+            // Timeout
+            if (Instant::now() - start_time) > timeout {
+                return Ok(false);
+            }
+        }
+        sleep(poll_interval);
+    }
+}
+
+/// Polls `status` until it reports a lock or `timeout` expires, keeping the last status read.
+pub(crate) fn wait_for_lock_outcome<F>(
+    mut status: F,
+    timeout: Option<Duration>,
+    poll_interval: Option<Duration>,
+) -> Result<TuneOutcome>
+where
+    F: FnMut() -> Result<FeStatus>,
+{
+    let start_time = Instant::now();
+    let mut last_status = None;
+    let locked = poll_until(
+        || {
+            let current = status()?;
+            let locked = current.has_lock();
+            last_status = Some(current);
+            Ok(locked)
+        },
+        timeout,
+        poll_interval,
+    )?;
+
+    Ok(TuneOutcome {
+        locked,
+        // poll_until always runs the check at least once
+        status: last_status.expect("status was never read"),
+        elapsed: start_time.elapsed(),
+    })
+}
+
+/// Interprets the result of a status query, see [`Frontend::diagnose`].
+pub(crate) fn diagnose_status(status: Result<FeStatus>) -> Result<FrontendDiagnosis> {
+    match status {
+        Ok(status) => Ok(FrontendDiagnosis::Tuned(status)),
+        Err(FrontendError::StatusQuery(Errno::ENODEV)) => Ok(FrontendDiagnosis::NoFirmware),
+        Err(FrontendError::StatusQuery(Errno::EAGAIN)) => Ok(FrontendDiagnosis::NotTuned),
+        Err(e) => Err(e),
+    }
+}
+
+/// State of a frontend as reported by [`Frontend::diagnose`].
+#[derive(Debug)]
+pub enum FrontendDiagnosis {
+    /// The device is there but can't operate, the system is most likely missing a firmware. Check `dmesg`.
+    NoFirmware,
+    /// The frontend was never tuned, so it has no status to report yet.
+    NotTuned,
+    /// The frontend is tuned, holds its current status.
+    Tuned(FeStatus),
+}
+
+/// Result of tuning a frontend and waiting for it to lock, returned by [`Frontend::tune_and_wait`].
+#[derive(Debug)]
+pub struct TuneOutcome {
+    /// Whether the frontend locked in before timing out.
+    pub locked: bool,
+    /// Last status read from the frontend.
+    pub status: FeStatus,
+    /// Time spent waiting for the lock. Useful for scan diagnostics.
+    pub elapsed: Duration,
+}
+
+/// How waiting for a lock ended, returned by [`Frontend::wait_for_lock`].
+#[derive(Debug)]
+pub enum LockOutcome {
+    Locked,
+    /// Some signal was present, but the frontend never locked on it. Tuning parameters are likely wrong.
+    SignalOnly {
+        last_status: FeStatus,
+    },
+    /// Nothing was received at all, the frequency is likely empty.
+    TimedOut {
+        last_status: FeStatus,
+    },
+}
+
+impl LockOutcome {
+    pub fn is_locked(&self) -> bool {
+        matches!(self, LockOutcome::Locked)
+    }
+}
+
+impl From<TuneOutcome> for LockOutcome {
+    fn from(outcome: TuneOutcome) -> Self {
+        let last_status = outcome.status;
+        if outcome.locked {
+            LockOutcome::Locked
+        } else if last_status.has_signal() || last_status.has_carrier() {
+            LockOutcome::SignalOnly { last_status }
+        } else {
+            LockOutcome::TimedOut { last_status }
+        }
+    }
+}
+
+impl Drop for Frontend {
+    fn drop(&mut self) {
+        if self.untune_on_drop && self.write {
+            let _ = self.untune();
+        }
+    }
+}
+
+//
+// ----- Tune request
+
+/// All parameters to tune a frontend with, sent at once.
+///
+/// Previous parameters are cleared first, so nothing set for another delivery system lingers in the kernel.
+/// Parameters left unset are detected automatically by the frontend, when it can.
+#[derive(Debug)]
+pub struct TuneRequest {
+    frequency: u32,
+    delivery_system: DeliverySystem,
+    bandwidth: Option<BandwidthHz>,
+    modulation: Option<FeModulation>,
+    symbol_rate: Option<u32>,
+    inner_fec: Option<FeCodeRate>,
+    stream_id: Option<u32>,
+}
+
+impl TuneRequest {
+    pub fn new(frequency: u32, delivery_system: DeliverySystem) -> TuneRequest {
+        TuneRequest {
+            frequency,
+            delivery_system,
+            bandwidth: None,
+            modulation: None,
+            symbol_rate: None,
+            inner_fec: None,
+            stream_id: None,
+        }
+    }
+
+    /// Only for terrestrial systems and DVB-C2.
+    pub fn bandwidth(mut self, bandwidth: BandwidthHz) -> TuneRequest {
+        self.bandwidth = Some(bandwidth);
+        self
+    }
+
+    pub fn modulation(mut self, modulation: FeModulation) -> TuneRequest {
+        self.modulation = Some(modulation);
+        self
+    }
+
+    /// Only for cable and satellite systems, except DVB-C2.
+    pub fn symbol_rate(mut self, symbol_rate: u32) -> TuneRequest {
+        self.symbol_rate = Some(symbol_rate);
+        self
+    }
+
+    pub fn inner_fec(mut self, inner_fec: FeCodeRate) -> TuneRequest {
+        self.inner_fec = Some(inner_fec);
+        self
+    }
+
+    /// PLP for DVB-T2 and DVB-C2, input stream for DVB-S2 multistream.
+    pub fn plp(mut self, stream_id: u32) -> TuneRequest {
+        self.stream_id = Some(stream_id);
+        self
+    }
+
+    /// Make sure no parameter was set that the delivery system doesn't use.
+    pub fn validate(&self) -> Result<()> {
+        use DeliverySystem::*;
+        let system = self.delivery_system;
+
+        if self.bandwidth.is_some() && !matches!(system, DvbT | DvbT2 | DvbC2) {
+            return Err(FrontendError::UnsupportedTuneParameter("bandwidth"));
+        }
+        if self.symbol_rate.is_some()
+            && !matches!(system, DvbCAnnexA | DvbCAnnexB | DvbCAnnexC | DvbS | DvbS2)
+        {
+            return Err(FrontendError::UnsupportedTuneParameter("symbol rate"));
+        }
+        if self.stream_id.is_some() && !matches!(system, DvbT2 | DvbC2 | DvbS2) {
+            return Err(FrontendError::UnsupportedTuneParameter("stream ID"));
+        }
+        Ok(())
+    }
+
+    /// Validate, then send all parameters to the frontend and start tuning.
+    ///
+    /// Everything is sent in a single request, see [`Frontend::retune`] when switching between delivery systems.
+    pub fn execute(self, frontend: &mut Frontend) -> Result<()> {
+        self.validate()?;
+
+        let mut props = vec![
+            Clear {}.property(),
+            DeliverySystemSet::new(self.delivery_system.into()).property(),
+        ];
+        props.extend(self.parameter_properties());
+        props.push(Tune {}.property());
+
+        frontend.set_properties(&mut props)
+    }
+
+    /// Frequency and every parameter that was set, excluding the delivery system.
+    fn parameter_properties(&self) -> Vec<DtvProperty> {
+        let mut props = vec![Frequency::new(self.frequency).property()];
+        if let Some(bandwidth) = self.bandwidth {
+            props.push(bandwidth.property());
+        }
+        if let Some(modulation) = self.modulation {
+            props.push(Modulation::new(modulation).property());
+        }
+        if let Some(symbol_rate) = self.symbol_rate {
+            props.push(SymbolRate::new(symbol_rate).property());
+        }
+        if let Some(inner_fec) = self.inner_fec {
+            props.push(InnerFec::new(inner_fec).property());
+        }
+        if let Some(stream_id) = self.stream_id {
+            props.push(StreamId::new(stream_id).property());
+        }
+        props
+    }
 }
 
 //
@@ -216,6 +797,36 @@ pub struct FrequencyInfo {
     pub frequency_tolerance: u32,
 }
 
+impl FrequencyInfo {
+    /// Round `frequency` to the nearest step, counting from the lowest frequency of the frontend.
+    ///
+    /// Returned as-is if the frontend doesn't report any step size, or if it is already within the frequency tolerance of a step.
+    /// This keeps centers that are slightly off the grid, like the 166kHz offsets of some countries, when the frontend can receive them.
+    pub fn align(&self, frequency: u32) -> u32 {
+        let step = self.frequency_step_size as i64;
+        if step == 0 {
+            return frequency;
+        }
+        let base = self.frequency_range.min as i64;
+        let mut aligned = (frequency as i64 - base + step / 2).div_euclid(step) * step + base;
+        // Rounding up may go past the largest value, step down in this case
+        if aligned > u32::MAX as i64 {
+            aligned -= step;
+        }
+        // Stay within the range of the frontend, some drivers don't report one at all
+        let max = match self.frequency_range.max {
+            0 => u32::MAX,
+            max => max,
+        };
+        let aligned = u32::try_from(aligned.clamp(base, max as i64))
+            .expect("frequency was clamped within u32 bounds");
+        if aligned.abs_diff(frequency) <= self.frequency_tolerance {
+            return frequency;
+        }
+        aligned
+    }
+}
+
 /// Information related to Cable and Satellite systems.
 #[derive(Debug, Copy, Clone)]
 pub struct SymbolRateInfo {
@@ -227,15 +838,10 @@ pub struct SymbolRateInfo {
 
 impl From<DvbFrontendInfo> for Info {
     fn from(value: DvbFrontendInfo) -> Self {
-        // TODO: This probably breaks if there is a name of size 128 bytes
-        let str_ptr = &value.name as *const c_char;
-        let c_str = unsafe { CStr::from_ptr(str_ptr) };
-        let name = c_str.to_string_lossy().into_owned();
-
         Self {
-            name,
+            name: decode_name(&value.name),
             frequency: FrequencyInfo {
-                frequency_range: ValueBounds::new(value.symbol_rate_min, value.symbol_rate_max),
+                frequency_range: ValueBounds::new(value.frequency_min, value.frequency_max),
                 frequency_step_size: value.frequency_stepsize,
                 frequency_tolerance: value.frequency_tolerance,
             },
@@ -248,6 +854,13 @@ impl From<DvbFrontendInfo> for Info {
     }
 }
 
+/// Decode a NUL-terminated name, without reading past the end of the array if the driver filled all of it.
+fn decode_name(name: &[c_char]) -> String {
+    let bytes = name.iter().map(|&c| c as u8).collect::<Vec<_>>();
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
 //
 // ----- Delivery System
 
@@ -263,30 +876,32 @@ pub enum DeliverySystem {
     DvbS2,
 }
 
-impl From<FeDeliverySystem> for DeliverySystem {
-    fn from(value: FeDeliverySystem) -> Self {
-        match value {
-            FeDeliverySystem::UNDEFINED => panic!(),
+impl TryFrom<FeDeliverySystem> for DeliverySystem {
+    type Error = UnsupportedDeliverySystem;
+
+    fn try_from(value: FeDeliverySystem) -> std::result::Result<Self, Self::Error> {
+        Ok(match value {
             FeDeliverySystem::DVBC_ANNEX_A => Self::DvbCAnnexA,
             FeDeliverySystem::DVBC_ANNEX_B => Self::DvbCAnnexB,
             FeDeliverySystem::DVBT => Self::DvbT,
-            FeDeliverySystem::DSS => unimplemented!(),
             FeDeliverySystem::DVBS => Self::DvbS,
             FeDeliverySystem::DVBS2 => Self::DvbS2,
-            FeDeliverySystem::DVBH => unimplemented!(),
-            FeDeliverySystem::ISDBT => unimplemented!(),
-            FeDeliverySystem::ISDBS => unimplemented!(),
-            FeDeliverySystem::ISDBC => unimplemented!(),
-            FeDeliverySystem::ATSC => unimplemented!(),
-            FeDeliverySystem::ATSCMH => unimplemented!(),
-            FeDeliverySystem::DTMB => unimplemented!(),
-            FeDeliverySystem::CMMB => unimplemented!(),
-            FeDeliverySystem::DAB => unimplemented!(),
             FeDeliverySystem::DVBT2 => Self::DvbT2,
-            FeDeliverySystem::TURBO => unimplemented!(),
             FeDeliverySystem::DVBC_ANNEX_C => Self::DvbCAnnexC,
             FeDeliverySystem::DVBC2 => Self::DvbC2,
-        }
+            FeDeliverySystem::UNDEFINED
+            | FeDeliverySystem::DSS
+            | FeDeliverySystem::DVBH
+            | FeDeliverySystem::ISDBT
+            | FeDeliverySystem::ISDBS
+            | FeDeliverySystem::ISDBC
+            | FeDeliverySystem::ATSC
+            | FeDeliverySystem::ATSCMH
+            | FeDeliverySystem::DTMB
+            | FeDeliverySystem::CMMB
+            | FeDeliverySystem::DAB
+            | FeDeliverySystem::TURBO => return Err(UnsupportedDeliverySystem(value)),
+        })
     }
 }
 
@@ -338,6 +953,11 @@ impl DeliverySystem {
         }
     }
 
+    /// Unit of the frequencies used to tune to this system
+    pub fn frequency_unit(&self) -> FrequencyUnit {
+        FeDeliverySystem::from(*self).frequency_unit()
+    }
+
     pub fn pretty_name(&self) -> &'static str {
         match self {
             DeliverySystem::DvbCAnnexA => "DVB-C Annex A",
@@ -357,3 +977,266 @@ impl Display for DeliverySystem {
         write!(f, "{}", self.pretty_name())
     }
 }
+
+/// Unit of the frequency property, which depends on the delivery system
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrequencyUnit {
+    Hz,
+    /// Satellite systems, where the frequency is the intermediate one after the LNB
+    KHz,
+}
+
+/// Families of the kernel delivery systems, including the ones that have no [DeliverySystem] equivalent.
+pub trait FeDeliverySystemExt {
+    /// `None` for [FeDeliverySystem::UNDEFINED]
+    fn mode(&self) -> Option<DeliverySystemMode>;
+    /// `None` for [FeDeliverySystem::UNDEFINED]
+    fn generation(&self) -> Option<DeliverySystemGeneration>;
+    fn frequency_unit(&self) -> FrequencyUnit;
+
+    fn is_terrestrial(&self) -> bool {
+        self.mode() == Some(DeliverySystemMode::Terrestrial)
+    }
+
+    fn is_satellite(&self) -> bool {
+        self.mode() == Some(DeliverySystemMode::Satellite)
+    }
+
+    fn is_cable(&self) -> bool {
+        self.mode() == Some(DeliverySystemMode::Cable)
+    }
+}
+
+impl FeDeliverySystemExt for FeDeliverySystem {
+    fn mode(&self) -> Option<DeliverySystemMode> {
+        Some(match self {
+            FeDeliverySystem::UNDEFINED => return None,
+            FeDeliverySystem::DVBT
+            | FeDeliverySystem::DVBT2
+            | FeDeliverySystem::DVBH
+            | FeDeliverySystem::ISDBT
+            | FeDeliverySystem::ATSC
+            | FeDeliverySystem::ATSCMH
+            | FeDeliverySystem::DTMB
+            | FeDeliverySystem::CMMB
+            | FeDeliverySystem::DAB => DeliverySystemMode::Terrestrial,
+            FeDeliverySystem::DSS
+            | FeDeliverySystem::DVBS
+            | FeDeliverySystem::DVBS2
+            | FeDeliverySystem::ISDBS
+            | FeDeliverySystem::TURBO => DeliverySystemMode::Satellite,
+            FeDeliverySystem::DVBC_ANNEX_A
+            | FeDeliverySystem::DVBC_ANNEX_B
+            | FeDeliverySystem::DVBC_ANNEX_C
+            | FeDeliverySystem::DVBC2
+            | FeDeliverySystem::ISDBC => DeliverySystemMode::Cable,
+        })
+    }
+
+    fn generation(&self) -> Option<DeliverySystemGeneration> {
+        Some(match self {
+            FeDeliverySystem::UNDEFINED => return None,
+            FeDeliverySystem::DVBT2 | FeDeliverySystem::DVBS2 | FeDeliverySystem::DVBC2 => {
+                DeliverySystemGeneration::SecondGeneration
+            }
+            FeDeliverySystem::DVBC_ANNEX_A
+            | FeDeliverySystem::DVBC_ANNEX_B
+            | FeDeliverySystem::DVBC_ANNEX_C
+            | FeDeliverySystem::DVBT
+            | FeDeliverySystem::DVBH
+            | FeDeliverySystem::DSS
+            | FeDeliverySystem::DVBS
+            | FeDeliverySystem::ISDBT
+            | FeDeliverySystem::ISDBS
+            | FeDeliverySystem::ISDBC
+            | FeDeliverySystem::ATSC
+            | FeDeliverySystem::ATSCMH
+            | FeDeliverySystem::DTMB
+            | FeDeliverySystem::CMMB
+            | FeDeliverySystem::DAB
+            | FeDeliverySystem::TURBO => DeliverySystemGeneration::FirstGeneration,
+        })
+    }
+
+    /// "The frequencies are specified in kHz for Satellite systems."
+    fn frequency_unit(&self) -> FrequencyUnit {
+        if self.is_satellite() {
+            FrequencyUnit::KHz
+        } else {
+            FrequencyUnit::Hz
+        }
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FE_HAS_SIGNAL: u32 = 0x01;
+    const FE_HAS_LOCK: u32 = 0x1F;
+
+    #[test]
+    fn tune_outcome_after_delayed_lock() {
+        let lock_delay = Duration::from_millis(100);
+        let start = Instant::now();
+        let outcome = wait_for_lock_outcome(
+            || {
+                if start.elapsed() > lock_delay {
+                    Ok(FeStatus::from(FE_HAS_LOCK))
+                } else {
+                    Ok(FeStatus::from(FE_HAS_SIGNAL))
+                }
+            },
+            Some(Duration::from_secs(1)),
+            Some(Duration::from_millis(10)),
+        )
+        .unwrap();
+
+        assert!(outcome.locked);
+        assert!(outcome.status.has_lock());
+        assert!(outcome.elapsed >= lock_delay);
+        assert!(outcome.elapsed < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn lock_outcomes() {
+        let outcome = |locked, status| {
+            LockOutcome::from(TuneOutcome {
+                locked,
+                status: FeStatus::from(status),
+                elapsed: Duration::ZERO,
+            })
+        };
+
+        assert!(outcome(true, FE_HAS_LOCK).is_locked());
+        assert!(matches!(
+            outcome(false, FE_HAS_SIGNAL),
+            LockOutcome::SignalOnly { last_status } if last_status.has_signal()
+        ));
+        assert!(matches!(outcome(false, 0), LockOutcome::TimedOut { .. }));
+    }
+
+    #[test]
+    fn bit_error_rates() {
+        assert_eq!(
+            bit_error_rate(Some(StatResult::Count(5)), Some(StatResult::Count(1000))),
+            Some(0.005)
+        );
+        assert_eq!(
+            bit_error_rate(Some(StatResult::Count(5)), Some(StatResult::Count(0))),
+            None
+        );
+        assert_eq!(bit_error_rate(None, Some(StatResult::Count(1000))), None);
+    }
+
+    #[test]
+    fn tune_request_validation() {
+        assert!(
+            TuneRequest::new(474_000_000, DeliverySystem::DvbT2)
+                .bandwidth(BandwidthHz::_8MHz)
+                .plp(1)
+                .validate()
+                .is_ok()
+        );
+        assert!(matches!(
+            TuneRequest::new(474_000_000, DeliverySystem::DvbT)
+                .symbol_rate(6_900_000)
+                .validate(),
+            Err(FrontendError::UnsupportedTuneParameter(_))
+        ));
+        assert!(
+            TuneRequest::new(11_362_000, DeliverySystem::DvbS)
+                .bandwidth(BandwidthHz::_8MHz)
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn frequency_alignment() {
+        let mut info = FrequencyInfo {
+            frequency_range: ValueBounds::new(174_000_000, 862_000_000),
+            frequency_step_size: 62_500,
+            frequency_tolerance: 0,
+        };
+        assert_eq!(info.align(474_166_000), 474_187_500);
+        assert_eq!(info.align(474_031_249), 474_000_000);
+        assert_eq!(info.align(u32::MAX), 862_000_000);
+        assert_eq!(info.align(0), 174_000_000);
+
+        // A 166kHz offset channel is close enough to a step for the frontend
+        info.frequency_tolerance = 25_000;
+        assert_eq!(info.align(474_166_000), 474_166_000);
+        assert_eq!(info.align(474_130_000), 474_130_000);
+        assert_eq!(info.align(474_090_000), 474_062_500);
+
+        // Steps start from the lowest frequency, not from 0
+        info.frequency_tolerance = 0;
+        info.frequency_range = ValueBounds::new(174_166_000, 862_000_000);
+        info.frequency_step_size = 250_000;
+        assert_eq!(info.align(474_166_000), 474_166_000);
+        assert_eq!(info.align(474_100_000), 474_166_000);
+        // The nearest step would be below 0
+        assert_eq!(info.align(0), 174_166_000);
+
+        info.frequency_range = ValueBounds::new(0, 0);
+        assert_eq!(info.align(u32::MAX), 4_294_750_000);
+
+        info.frequency_step_size = 0;
+        assert_eq!(info.align(474_166_000), 474_166_000);
+    }
+
+    #[test]
+    fn unterminated_name() {
+        let mut name = [b'A' as c_char; 128];
+        assert_eq!(decode_name(&name), "A".repeat(128));
+
+        name[4] = 0;
+        assert_eq!(decode_name(&name), "AAAA");
+    }
+
+    #[test]
+    fn diagnosis() {
+        assert!(matches!(
+            diagnose_status(Err(FrontendError::StatusQuery(Errno::ENODEV))),
+            Ok(FrontendDiagnosis::NoFirmware)
+        ));
+        assert!(matches!(
+            diagnose_status(Err(FrontendError::StatusQuery(Errno::EAGAIN))),
+            Ok(FrontendDiagnosis::NotTuned)
+        ));
+        assert!(matches!(
+            diagnose_status(Ok(FeStatus::from(FE_HAS_LOCK))),
+            Ok(FrontendDiagnosis::Tuned(s)) if s.has_lock()
+        ));
+        assert!(diagnose_status(Err(FrontendError::StatusQuery(Errno::EIO))).is_err());
+    }
+
+    #[test]
+    fn delivery_system_families() {
+        for system in [
+            DeliverySystem::DvbCAnnexA,
+            DeliverySystem::DvbC2,
+            DeliverySystem::DvbT,
+            DeliverySystem::DvbT2,
+            DeliverySystem::DvbS,
+            DeliverySystem::DvbS2,
+        ] {
+            let kernel = FeDeliverySystem::from(system);
+            assert_eq!(kernel.mode(), Some(system.mode()));
+            assert_eq!(kernel.generation(), Some(system.generation()));
+            assert_eq!(DeliverySystem::try_from(kernel).unwrap(), system);
+        }
+        assert!(DeliverySystem::try_from(FeDeliverySystem::ATSC).is_err());
+        assert!(DeliverySystem::try_from(FeDeliverySystem::UNDEFINED).is_err());
+
+        assert_eq!(DeliverySystem::DvbS2.frequency_unit(), FrequencyUnit::KHz);
+        assert_eq!(DeliverySystem::DvbT.frequency_unit(), FrequencyUnit::Hz);
+        assert!(FeDeliverySystem::ISDBC.is_cable());
+        assert!(FeDeliverySystem::ATSC.is_terrestrial());
+        assert_eq!(FeDeliverySystem::UNDEFINED.mode(), None);
+    }
+}
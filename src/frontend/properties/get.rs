@@ -1,7 +1,12 @@
-use std::{collections::BTreeSet, marker::PhantomData};
+use std::{
+    cmp::Ordering,
+    collections::BTreeSet,
+    fmt::{Display, Formatter, Result as FmtResult},
+    marker::PhantomData,
+};
 
 use rdvb_os_linux::frontend::{
-    data::{FeDeliverySystem, FeModulation},
+    data::{FeDeliverySystem, FeModulation, FeSpectralInversion},
     property::{Command, DtvProperty, DtvPropertyUnion, DtvStatsValue, FeCapScaleParams},
 };
 
@@ -14,7 +19,9 @@ use crate::error::DtvError;
 
 pub trait PropertyQuery {
     fn associated_command() -> Command;
-    fn from_property(u: DtvPropertyUnion) -> Self;
+    fn from_property(u: DtvPropertyUnion) -> Result<Self, DtvError>
+    where
+        Self: Sized;
 
     /// Create a PendingQuery that can be passed to the properties method of a Frontend.
     ///
@@ -43,11 +50,7 @@ pub struct QueryDescription<'a> {
 
 impl<T: PropertyQuery> PendingQuery<T> {
     pub fn retrieve(self) -> Result<T, DtvError> {
-        let property = self.memory.ok_or(DtvError::NotRan)?;
-        if property.result < 0 {
-            return Err(DtvError::Reported(property.result));
-        }
-        Ok(T::from_property(property.u))
+        T::from_property(checked_union(self.memory)?)
     }
 
     pub fn desc(&mut self) -> QueryDescription {
@@ -58,6 +61,16 @@ impl<T: PropertyQuery> PendingQuery<T> {
     }
 }
 
+/// Get the value out of a property filled by the kernel, if it reports no error.
+pub(crate) fn checked_union(memory: Option<DtvProperty>) -> Result<DtvPropertyUnion, DtvError> {
+    let property = memory.ok_or(DtvError::NotRan)?;
+    if property.result < 0 {
+        return Err(DtvError::Reported(property.result));
+    }
+    Ok(property.u)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum StatResult {
     Value(ValueStat),
     Count(u64),
@@ -93,18 +106,78 @@ impl StatResult {
     }
 }
 
-impl PartialOrd for ValueStat {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+/// Every layer of a statistic, as returned by [`Frontend::read_stats`](crate::frontend::Frontend::read_stats).
+///
+/// Most systems report a single layer, but ISDB-T and hierarchical DVB-T report one per layer.
+#[derive(Debug)]
+pub struct DtvStats(pub Vec<Option<StatResult>>);
+
+impl DtvStats {
+    pub(crate) fn from_property(u: DtvPropertyUnion) -> Result<Self, DtvError> {
+        let stats = unsafe { u.st };
+        stats
+            .stat
+            .iter()
+            .take(stats.len as usize)
+            .map(|stat| {
+                let scale = FeCapScaleParams::try_from(stat.scale)
+                    .map_err(|_| DtvError::UnexpectedValue(u32::from(stat.scale)))?;
+                Ok(StatResult::from(scale, stat.value))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    /// Return the first layer, which is the only one for most systems.
+    pub fn first(self) -> Option<StatResult> {
+        self.0.into_iter().next().flatten()
+    }
+}
+
+impl ValueStat {
+    /// Return the value as a percentage, if it is on the relative scale.
+    ///
+    /// "0 means 0% and 65535 means 100%"
+    pub fn percentage(&self) -> Option<f64> {
+        match self {
+            ValueStat::Relative(value) => Some(*value as f64 * 100.0 / 65535.0),
+            ValueStat::Decibel(_) => None,
+        }
+    }
+}
+
+impl Display for ValueStat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            // Reported in thousandths of dB
+            ValueStat::Decibel(value) => write!(f, "{:.1} dB", *value as f64 / 1000.0),
+            ValueStat::Relative(_) => write!(f, "{:.0}%", self.percentage().unwrap_or_default()),
+        }
+    }
+}
+
+/// Values on the same scale are compared directly.
+///
+/// There is no way to convert between both scales, so when they're mixed, the decibel reading is always considered greater.
+/// It is an actual measurement, while the relative scale is up to each driver.
+impl Ord for ValueStat {
+    fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
-            (ValueStat::Decibel(_a), ValueStat::Decibel(_b)) => {
-                todo!("no idea how the dB info is encoded")
-            }
-            (ValueStat::Relative(a), ValueStat::Relative(b)) => Some(a.cmp(b)),
-            _ => None,
+            // Both in thousandths of dB
+            (ValueStat::Decibel(a), ValueStat::Decibel(b)) => a.cmp(b),
+            (ValueStat::Relative(a), ValueStat::Relative(b)) => a.cmp(b),
+            (ValueStat::Decibel(_), ValueStat::Relative(_)) => Ordering::Greater,
+            (ValueStat::Relative(_), ValueStat::Decibel(_)) => Ordering::Less,
         }
     }
 }
 
+impl PartialOrd for ValueStat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 //
 // ----- Individual queries
 
@@ -120,16 +193,37 @@ impl PropertyQuery for EnumerateDeliverySystems {
         Command::DTV_ENUM_DELSYS
     }
 
-    fn from_property(u: DtvPropertyUnion) -> Self {
+    fn from_property(u: DtvPropertyUnion) -> Result<Self, DtvError> {
         let len = unsafe { u.buffer.len } as usize;
 
+        // Values newer than this crate's list of systems are left out
         let mut systems = BTreeSet::new();
         for i in 0..len {
             let data = unsafe { u.buffer.data[i] };
-            systems.insert(FeDeliverySystem::try_from(data).unwrap());
+            if let Ok(system) = FeDeliverySystem::try_from(data) {
+                systems.insert(system);
+            }
         }
 
-        EnumerateDeliverySystems(systems)
+        Ok(EnumerateDeliverySystems(systems))
+    }
+}
+
+// ---
+
+/// Delivery system the frontend is currently set to.
+#[derive(Debug)]
+pub struct CurrentDeliverySystem(pub FeDeliverySystem);
+impl PropertyQuery for CurrentDeliverySystem {
+    fn associated_command() -> Command {
+        Command::DTV_DELIVERY_SYSTEM
+    }
+
+    fn from_property(u: DtvPropertyUnion) -> Result<Self, DtvError> {
+        let value = unsafe { u.data };
+        FeDeliverySystem::try_from(value)
+            .map(Self)
+            .map_err(|_| DtvError::UnexpectedValue(value))
     }
 }
 
@@ -142,8 +236,8 @@ impl PropertyQuery for Frequency {
         Command::DTV_FREQUENCY
     }
 
-    fn from_property(u: DtvPropertyUnion) -> Self {
-        Self(unsafe { u.data })
+    fn from_property(u: DtvPropertyUnion) -> Result<Self, DtvError> {
+        Ok(Self(unsafe { u.data }))
     }
 }
 
@@ -158,10 +252,28 @@ impl PropertyQuery for Modulation {
         Command::DTV_MODULATION
     }
 
-    fn from_property(u: DtvPropertyUnion) -> Self {
-        Self(unsafe {
-            FeModulation::try_from(u.data).expect("unexpected value for modulation type")
-        })
+    fn from_property(u: DtvPropertyUnion) -> Result<Self, DtvError> {
+        let value = unsafe { u.data };
+        FeModulation::try_from(value)
+            .map(Self)
+            .map_err(|_| DtvError::UnexpectedValue(value))
+    }
+}
+
+// ---
+
+#[derive(Debug)]
+pub struct Inversion(pub FeSpectralInversion);
+impl PropertyQuery for Inversion {
+    fn associated_command() -> Command {
+        Command::DTV_INVERSION
+    }
+
+    fn from_property(u: DtvPropertyUnion) -> Result<Self, DtvError> {
+        let value = unsafe { u.data };
+        FeSpectralInversion::try_from(value)
+            .map(Self)
+            .map_err(|_| DtvError::UnexpectedValue(value))
     }
 }
 
@@ -173,48 +285,58 @@ impl PropertyQuery for SymbolRate {
         Command::DTV_SYMBOL_RATE
     }
 
-    fn from_property(u: DtvPropertyUnion) -> Self {
+    fn from_property(u: DtvPropertyUnion) -> Result<Self, DtvError> {
         // SAFETY: No matter what data is provided, a u32 always has a valid value
-        Self(unsafe { u.data })
+        Ok(Self(unsafe { u.data }))
     }
 }
 
 // ---
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SignalStrength(pub Option<ValueStat>);
 impl PropertyQuery for SignalStrength {
     fn associated_command() -> Command {
         Command::DTV_STAT_SIGNAL_STRENGTH
     }
 
-    fn from_property(u: DtvPropertyUnion) -> Self {
-        let stats = unsafe { u.st };
-        assert_eq!(stats.len, 1);
-        let stat = stats.stat[0];
-        let scale = FeCapScaleParams::try_from(stat.scale).expect("unexpected value for stat type");
-        let res = match StatResult::from(scale, stat.value) {
-            Some(v) => v,
-            None => return Self(None),
-        };
-        match res {
-            StatResult::Value(value_stat) => Self(Some(value_stat)),
-            StatResult::Count(_) => panic!("expected a value, not a count"),
+    fn from_property(u: DtvPropertyUnion) -> Result<Self, DtvError> {
+        Self::from_stat(DtvStats::from_property(u)?.first())
+    }
+}
+
+impl SignalStrength {
+    fn from_stat(stat: Option<StatResult>) -> Result<Self, DtvError> {
+        match stat {
+            Some(StatResult::Value(value_stat)) => Ok(Self(Some(value_stat))),
+            Some(StatResult::Count(_)) => Err(DtvError::UnexpectedScale),
+            None => Ok(Self(None)),
         }
     }
 }
 
-impl PartialOrd for SignalStrength {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match (self.0, other.0) {
-            (None, None) => None,
-            (None, Some(_)) => Some(std::cmp::Ordering::Less),
-            (Some(_), None) => Some(std::cmp::Ordering::Greater),
-            (Some(a), Some(b)) => a.partial_cmp(&b),
+impl Display for SignalStrength {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.0 {
+            Some(value) => write!(f, "{}", value),
+            None => write!(f, "unknown"),
         }
     }
 }
 
+/// No reading at all is weaker than any reading, see [`ValueStat`] for mixed scales.
+impl Ord for SignalStrength {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for SignalStrength {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 // --
 
 #[derive(Debug)]
@@ -229,18 +351,76 @@ impl PropertyQuery for TotalBlockCount {
         Command::DTV_STAT_TOTAL_BLOCK_COUNT
     }
 
-    fn from_property(u: DtvPropertyUnion) -> Self {
-        let stats = unsafe { u.st };
-        assert_eq!(stats.len, 1);
-        let stat = stats.stat[0];
-        let scale = FeCapScaleParams::try_from(stat.scale).expect("unexpected value for stat type");
-        let res = match StatResult::from(scale, stat.value) {
-            Some(v) => v,
-            None => return Self(None),
-        };
-        match res {
-            StatResult::Value(_) => panic!("expected a count, not a value"),
-            StatResult::Count(count) => Self(Some(count)),
+    fn from_property(u: DtvPropertyUnion) -> Result<Self, DtvError> {
+        Self::from_stat(DtvStats::from_property(u)?.first())
+    }
+}
+
+impl TotalBlockCount {
+    fn from_stat(stat: Option<StatResult>) -> Result<Self, DtvError> {
+        match stat {
+            Some(StatResult::Value(_)) => Err(DtvError::UnexpectedScale),
+            Some(StatResult::Count(count)) => Ok(Self(Some(count))),
+            None => Ok(Self(None)),
         }
     }
 }
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strength_ordering() {
+        let weak = SignalStrength(Some(ValueStat::Relative(10_000)));
+        let strong = SignalStrength(Some(ValueStat::Relative(50_000)));
+        let decibel = SignalStrength(Some(ValueStat::Decibel(-60_000)));
+        let none = SignalStrength(None);
+
+        assert!(strong > weak);
+        assert!(decibel > strong);
+        assert!(weak > none);
+        assert_eq!(none.cmp(&SignalStrength(None)), Ordering::Equal);
+        assert!(ValueStat::Decibel(-40_000) > ValueStat::Decibel(-60_000));
+    }
+
+    #[test]
+    fn mismatched_stat_scales() {
+        let count = Some(StatResult::Count(42));
+        let value = Some(StatResult::Value(ValueStat::Decibel(-60_000)));
+
+        assert!(matches!(
+            SignalStrength::from_stat(count),
+            Err(DtvError::UnexpectedScale)
+        ));
+        assert_eq!(
+            SignalStrength::from_stat(value).unwrap(),
+            SignalStrength(Some(ValueStat::Decibel(-60_000)))
+        );
+        assert!(matches!(
+            TotalBlockCount::from_stat(value),
+            Err(DtvError::UnexpectedScale)
+        ));
+        assert_eq!(TotalBlockCount::from_stat(count).unwrap().0, Some(42));
+        assert_eq!(TotalBlockCount::from_stat(None).unwrap().0, None);
+    }
+
+    #[test]
+    fn unknown_delivery_system() {
+        let property =
+            DtvProperty::new_data(Command::DTV_DELIVERY_SYSTEM, FeDeliverySystem::DVBT2 as u32);
+        assert_eq!(
+            CurrentDeliverySystem::from_property(property.u).unwrap().0,
+            FeDeliverySystem::DVBT2
+        );
+
+        let property = DtvProperty::new_data(Command::DTV_DELIVERY_SYSTEM, 0xFFFF);
+        assert!(matches!(
+            CurrentDeliverySystem::from_property(property.u),
+            Err(DtvError::UnexpectedValue(0xFFFF))
+        ));
+    }
+}
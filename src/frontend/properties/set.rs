@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
 use rdvb_os_linux::frontend::{
     data::{
         FeCodeRate, FeDeliverySystem, FeGuardInterval, FeModulation, FeSpectralInversion,
@@ -73,6 +75,19 @@ pub enum BandwidthHz {
     _10MHz,
 }
 impl BandwidthHz {
+    /// Return the variant for a bandwidth in Hz, if it is one of the supported ones.
+    pub fn from_value(hz: u32) -> Option<BandwidthHz> {
+        Some(match hz {
+            1712000 => BandwidthHz::_1_172MHz,
+            5000000 => BandwidthHz::_5MHz,
+            6000000 => BandwidthHz::_6MHz,
+            7000000 => BandwidthHz::_7MHz,
+            8000000 => BandwidthHz::_8MHz,
+            10000000 => BandwidthHz::_10MHz,
+            _ => return None,
+        })
+    }
+
     pub fn value(&self) -> u32 {
         match self {
             BandwidthHz::_1_172MHz => 1712000,
@@ -84,6 +99,11 @@ impl BandwidthHz {
         }
     }
 }
+impl Display for BandwidthHz {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} MHz", self.value() as f64 / 1_000_000.0)
+    }
+}
 impl SetPropertyQuery for BandwidthHz {
     fn property(self) -> DtvProperty {
         DtvProperty::new_data(Command::DTV_BANDWIDTH_HZ, self.value())
@@ -106,7 +126,17 @@ impl SetPropertyQuery for Inversion {
 
 // --
 
-pub struct SymbolRate {}
+pub struct SymbolRate(u32);
+impl SymbolRate {
+    pub fn new(symbol_rate: u32) -> SymbolRate {
+        SymbolRate(symbol_rate)
+    }
+}
+impl SetPropertyQuery for SymbolRate {
+    fn property(self) -> DtvProperty {
+        DtvProperty::new_data(Command::DTV_SYMBOL_RATE, self.0)
+    }
+}
 
 // --
 
@@ -146,13 +176,43 @@ impl SetPropertyQuery for DeliverySystem {
 
 // --
 
-// Special
-pub struct Voltage {}
+/// LNB supply voltage, which selects the polarization on most LNBs
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Voltage {
+    _13V,
+    _18V,
+    Off,
+}
+impl SetPropertyQuery for Voltage {
+    fn property(self) -> DtvProperty {
+        // Values of fe_sec_voltage
+        let value = match self {
+            Voltage::_13V => 0,
+            Voltage::_18V => 1,
+            Voltage::Off => 2,
+        };
+        DtvProperty::new_data(Command::DTV_VOLTAGE, value)
+    }
+}
 
 // --
 
-// Special
-pub struct Tone {}
+/// 22kHz tone, which selects the high band on universal LNBs
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Tone {
+    On,
+    Off,
+}
+impl SetPropertyQuery for Tone {
+    fn property(self) -> DtvProperty {
+        // Values of fe_sec_tone_mode
+        let value = match self {
+            Tone::On => 0,
+            Tone::Off => 1,
+        };
+        DtvProperty::new_data(Command::DTV_TONE, value)
+    }
+}
 
 // --
 
@@ -198,6 +258,21 @@ impl SetPropertyQuery for GuardInterval {
 
 // --
 
+/// Stream to select within a multiplex, like the PLP of DVB-T2 or the input stream of DVB-S2 multistream
+pub struct StreamId(u32);
+impl StreamId {
+    pub fn new(id: u32) -> StreamId {
+        StreamId(id)
+    }
+}
+impl SetPropertyQuery for StreamId {
+    fn property(self) -> DtvProperty {
+        DtvProperty::new_data(Command::DTV_STREAM_ID, self.0)
+    }
+}
+
+// --
+
 pub struct TransmissionMode {}
 
 // --
@@ -1,16 +1,32 @@
 //! Interpret data received from SI into more useable things, like a channel config file.
 
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
 use crate::{
     conf::vdr::{
         audio_pid::{AudioPID, AudioPIDList},
+        teletext_pid::{SubtitlePID, TeletextPIDList},
         video_pid::VideoPID,
     },
     frontend::{DeliverySystem, properties::set::BandwidthHz},
-    mpeg::{decode_stupid_string, descriptors::Descriptor},
+    mpeg::{
+        LanguageCode,
+        descriptors::{
+            Descriptor, DescriptorLoop,
+            hd_simulcast_logical_channel::HdSimulcastLogicalChannel,
+            iso639_language::Iso639Language,
+            logical_channel::{LogicalChannel, LogicalChannelDescriptorElement},
+            multilingual_service_name::MultilingualServiceName,
+            satellite_delivery_system::SatelliteDeliverySystem,
+            service_list::ServiceList,
+            subtitling::Subtitling,
+        },
+    },
     scan::Transponder,
     si::{
+        bat::BouquetAssociation,
         nit::{NetworkInformation, NitElement},
-        pmt::{ProgramMap, StreamType},
+        pmt::ProgramMap,
     },
 };
 
@@ -20,9 +36,13 @@ use crate::{
 #[derive(Clone, Debug)]
 pub struct ChannelInformation {
     pub frequency: u32,
-    pub bandwidth: BandwidthHz,
+    /// Unset for satellite channels
+    pub bandwidth: Option<BandwidthHz>,
     pub delivery_system: DeliverySystem,
+    /// In symbols per second, unset for terrestrial channels
     pub symbol_rate: Option<u32>,
+    /// Satellite delivery system of the transponder as advertised in the NIT, for its orbital position and polarization
+    pub satellite: Option<SatelliteDeliverySystem>,
     pub name: String,
     pub logical_channel_number: Option<u16>,
     pub service_id: u16,
@@ -30,31 +50,70 @@ pub struct ChannelInformation {
     pub transport_stream_id: u16,
     pub video_pid: VideoPID, // TODO: Should have own generic types instead of using VDR ones
     pub audio_pid_list: AudioPIDList,
+    pub teletext_pid_list: TeletextPIDList,
+    /// Set by [assign_bouquets], from the name of the first bouquet containing this channel
+    pub bouquet: Option<String>,
+}
+
+impl Display for ChannelInformation {
+    /// Single line summary, like `2 France 2 (service 257): video 120, audio 130 131`
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if let Some(lcn) = self.logical_channel_number {
+            write!(f, "{} ", lcn)?;
+        }
+        write!(
+            f,
+            "{} (service {}): video {}, audio",
+            self.name,
+            self.service_id,
+            self.video_pid.video_pid.unwrap_or(self.video_pid.pcr_pid)
+        )?;
+
+        let mut audio_pids = self
+            .audio_pid_list
+            .regular_pids
+            .iter()
+            .chain(self.audio_pid_list.dolby_pids.iter())
+            .peekable();
+        if audio_pids.peek().is_none() {
+            return write!(f, " none");
+        }
+        for audio in audio_pids {
+            write!(f, " {}", audio.pid)?;
+        }
+        Ok(())
+    }
 }
 
 impl ChannelInformation {
     /// Get all channels from a single transponder
     pub fn from_transponder(transponder: &Transponder) -> Vec<ChannelInformation> {
+        Self::from_transponder_in_language(transponder, None)
+    }
+
+    /// Get all channels from a single transponder, named in given language when the network provides it.
+    ///
+    /// Names in other languages come from the multilingual service name descriptor, the regular name is used if it is missing.
+    pub fn from_transponder_in_language(
+        transponder: &Transponder,
+        language_code: Option<LanguageCode>,
+    ) -> Vec<ChannelInformation> {
         let mut channels = Vec::new();
 
         for service in &transponder.service_description.services {
-            // Find the service descriptor
-            // TODO: Being able to store that specific descriptor would be easier
-            let mut service_descriptor = None;
-            for descriptor in &service.descriptors {
-                if let Descriptor::Service(service) = descriptor {
-                    service_descriptor = Some(service);
-                    break;
-                }
-            }
-
-            let service_data = if let Some(d) = service_descriptor {
-                d
-            } else {
-                // No service descriptor, no idea what this service is about
-                continue;
+            let multilingual = service.descriptors.find::<MultilingualServiceName>();
+            let preferred_name = language_code
+                .zip(multilingual)
+                .and_then(|(language_code, names)| names.service_name(&language_code));
+            let name = match preferred_name.or(service.service_name()) {
+                Some(name) => name.to_string(),
+                // Still better than nothing
+                None => match multilingual.and_then(|m| m.names.first()) {
+                    Some(first) => first.service.clone(),
+                    // No service descriptor, no idea what this service is about
+                    None => continue,
+                },
             };
-            let name = service_data.service.clone();
 
             // Match corresponding NITElement
             let nit_element = if let Some(e) =
@@ -75,13 +134,15 @@ impl ChannelInformation {
             };
 
             let logical_channel_number =
-                find_lcn_from_nit_element_by_service_id(nit_element, service.service_id);
+                resolve_logical_channel(nit_element, service.service_id).map(|(number, _)| number);
 
             channels.push(ChannelInformation {
                 frequency: transponder.frequency,
                 bandwidth: transponder.bandwidth,
                 delivery_system: transponder.system,
-                symbol_rate: None, // TODO: Symbol rate properly
+                symbol_rate: find_symbol_rate_from_nit_element(nit_element)
+                    .or(transponder.symbol_rate),
+                satellite: find_satellite_from_nit_element(nit_element).cloned(),
                 name,
                 logical_channel_number,
                 service_id: service.service_id,
@@ -89,6 +150,8 @@ impl ChannelInformation {
                 transport_stream_id: nit_element.transport_stream_id,
                 video_pid: pmt_to_video_pid(pmt_element).unwrap(),
                 audio_pid_list: pmt_to_audio_pids(pmt_element),
+                teletext_pid_list: pmt_to_teletext_pids(pmt_element),
+                bouquet: None,
             })
         }
 
@@ -107,6 +170,27 @@ pub fn to_channels(all_transponders: &[Transponder]) -> Vec<ChannelInformation>
     channels
 }
 
+/// Name the bouquet of each channel, using the BATs received with [receive_bouquets](crate::scan::receive_bouquets).
+///
+/// Bouquets without a name are ignored, and channels that aren't part of any bouquet are left as-is.
+pub fn assign_bouquets(channels: &mut [ChannelInformation], bouquets: &[BouquetAssociation]) {
+    for channel in channels {
+        let bouquet = bouquets
+            .iter()
+            .filter(|b| {
+                b.contains_service(
+                    channel.original_network_id,
+                    channel.transport_stream_id,
+                    channel.service_id,
+                )
+            })
+            .find_map(BouquetAssociation::name);
+        if let Some(name) = bouquet {
+            channel.bouquet = Some(name.to_string());
+        }
+    }
+}
+
 /// Sort a list of channels by their logical channel
 pub fn sort_by_lcn(channels: &mut [ChannelInformation]) {
     channels.sort_by(
@@ -123,135 +207,133 @@ fn find_nit_element_by_service_id(
     nit: &NetworkInformation,
     service_id: u16,
 ) -> Option<&NitElement> {
-    for element in &nit.elements {
-        for descriptor in &element.transport_descriptors {
-            if let Descriptor::ServiceList(service_list) = descriptor {
-                for e in &service_list.services {
-                    if e.service_id == service_id {
-                        return Some(element);
-                    }
-                }
-            }
-        }
-    }
+    nit.elements.iter().find(|element| {
+        element
+            .transport_descriptors
+            .filter::<ServiceList>()
+            .flat_map(|service_list| service_list.services.iter())
+            .any(|e| e.service_id == service_id)
+    })
+}
 
-    None
+/// Descriptor a logical channel number was found in, see [resolve_logical_channel].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LcnSource {
+    LogicalChannel,
+    HdSimulcastLogicalChannel,
 }
 
-fn find_lcn_from_nit_element_by_service_id(
-    nit_elements: &NitElement,
+/// Find the logical channel number of a service in the descriptors of its transport stream, and where it was found.
+///
+/// HD simulcast numbers are preferred, as receivers able to decode HD are expected to use them.
+pub fn resolve_logical_channel(
+    nit_element: &NitElement,
     service_id: u16,
-) -> Option<u16> {
-    for descriptor in &nit_elements.transport_descriptors {
-        let logical_channel = if let Descriptor::LogicalChannel(l) = descriptor {
-            l
-        } else {
-            continue;
-        };
-
-        for lc_element in &logical_channel.elements {
-            if lc_element.service_id == service_id {
-                return Some(lc_element.logical_channel_number);
-            }
-        }
+) -> Option<(u16, LcnSource)> {
+    let descriptors = &nit_element.transport_descriptors;
+    let find = |elements: &mut dyn Iterator<Item = &LogicalChannelDescriptorElement>| {
+        elements
+            .find(|lc_element| lc_element.service_id == service_id)
+            .map(|lc_element| lc_element.logical_channel_number)
+    };
+
+    let hd = find(
+        &mut descriptors
+            .filter::<HdSimulcastLogicalChannel>()
+            .flat_map(|hd| hd.elements.iter()),
+    );
+    if let Some(number) = hd {
+        return Some((number, LcnSource::HdSimulcastLogicalChannel));
     }
+    find(
+        &mut descriptors
+            .filter::<LogicalChannel>()
+            .flat_map(|logical_channel| logical_channel.elements.iter()),
+    )
+    .map(|number| (number, LcnSource::LogicalChannel))
+}
+
+/// Only cable and satellite delivery system descriptors hold a symbol rate
+fn find_symbol_rate_from_nit_element(nit_element: &NitElement) -> Option<u32> {
+    nit_element
+        .transport_descriptors
+        .iter()
+        .find_map(|descriptor| match descriptor {
+            Descriptor::CableDeliverySystem(c) => Some(c.symbols_per_second()),
+            Descriptor::SatelliteDeliverySystem(s) => Some(s.symbols_per_second()),
+            _ => None,
+        })
+}
 
-    None
+fn find_satellite_from_nit_element(nit_element: &NitElement) -> Option<&SatelliteDeliverySystem> {
+    nit_element
+        .transport_descriptors
+        .iter()
+        .find_map(|descriptor| match descriptor {
+            Descriptor::SatelliteDeliverySystem(s) => Some(s),
+            _ => None,
+        })
 }
 
 fn find_pmt_by_service_id(program_map: &[ProgramMap], service_id: u16) -> Option<&ProgramMap> {
     program_map.iter().find(|&e| e.program_number == service_id)
 }
 
-// TODO: Could merge all PID searches into a single fn
-
 fn pmt_to_video_pid(pmt_element: &ProgramMap) -> Option<VideoPID> {
-    // Search through all Elementary Streams and look for Video streams
-    for elementary_stream in &pmt_element.elementary_streams {
-        // Skip non-video streams
-        if !elementary_stream.stream_type.is_video() {
-            continue;
-        }
-
-        // Check if video PID is different from PCR
-        let video_pid = if pmt_element.pcr_pid != elementary_stream.elementary_pid {
-            Some(elementary_stream.elementary_pid)
-        } else {
-            None
-        };
-
-        return Some(VideoPID {
-            pcr_pid: pmt_element.pcr_pid,
-            video_pid,
-            video_mode: elementary_stream.stream_type.to_u8() as u16,
-        });
-    }
-
-    None
+    let elementary_stream = pmt_element.video_streams().next()?;
+
+    // Check if video PID is different from PCR
+    let video_pid = if pmt_element.pcr_pid() != elementary_stream.elementary_pid {
+        Some(elementary_stream.elementary_pid)
+    } else {
+        None
+    };
+
+    Some(VideoPID {
+        pcr_pid: pmt_element.pcr_pid(),
+        video_pid,
+        video_mode: elementary_stream.stream_type.to_u8() as u16,
+    })
 }
 
 fn pmt_to_audio_pids(pmt_element: &ProgramMap) -> AudioPIDList {
     let mut regular_pids = Vec::new();
     let mut dolby_pids = Vec::new();
 
-    // Same strategy as w_scan2 scan.c parse_pmt
-    for elementary_stream in &pmt_element.elementary_streams {
-        // Find language code for audio if any
-        let mut language_code = String::new();
-        for descriptor in &elementary_stream.descriptors {
-            if let Descriptor::Iso639Language(lang) = descriptor {
-                // TODO: This may not be in the same encoding, idk
-                language_code = decode_stupid_string(&lang.language).unwrap()
-            }
-        }
-
-        match &elementary_stream.stream_type {
-            // Regular Audio
-            StreamType::IsoIec11172Audio | StreamType::IsoIec13818_3Audio => {
-                regular_pids.push(AudioPID {
-                    pid: elementary_stream.elementary_pid,
-                    language_code,
-                    second_language_code: String::new(), // TODO: Not sure where the secondary language codes come from
-                    audio_type: Some(elementary_stream.stream_type.to_u8() as u16),
-                });
-            }
+    for elementary_stream in pmt_element.audio_streams() {
+        // Find language codes for audio if any, the last descriptor wins
+        // Dual-mono audio has one language per channel, the second one goes to the secondary language
+        let languages = elementary_stream
+            .descriptors
+            .filter::<Iso639Language>()
+            .last()
+            .map(|lang| lang.languages.as_slice())
+            .unwrap_or_default();
+        let language = |index: usize| {
+            languages
+                .get(index)
+                .map(|l| l.language.to_string())
+                .unwrap_or_default()
+        };
+        let (language_code, second_language_code) = (language(0), language(1));
 
+        match elementary_stream.dolby_audio_type() {
             // Enhanced (Dolby) Audio
-            StreamType::ItuTRecH2220IsoIec13818_1PrivateSections
-            | StreamType::ItuTRecH2220IsoIec13818_1PESPacketsContainingPrivateData => {
-                // Further check if this stream actually contains audio by checking descriptors
-                // TODO: This does not work for AC4, as there is no new descriptor. Seems like Extension is used instead.
-                let mut audio_type = None;
-                for descriptor in &elementary_stream.descriptors {
-                    match descriptor {
-                        Descriptor::Ac3(_) => {
-                            audio_type = Some(descriptor.descriptor_id());
-                            break;
-                        }
-                        Descriptor::EnhancedAc3(_) => audio_type = Some(descriptor.descriptor_id()),
-                        _ => {}
-                    }
-                }
-                let audio_type = if let Some(a) = audio_type {
-                    a
-                } else {
-                    continue;
-                };
-
-                dolby_pids.push(AudioPID {
-                    pid: elementary_stream.elementary_pid,
-                    language_code,
-                    second_language_code: String::new(),
-                    // TODO: audio_type is weird, w_scan2 and other data I found isn't coherent
-                    //audio_type: Some(elementary_stream.stream_type.to_u8() as u16),
-                    audio_type: Some(audio_type as u16),
-                });
-            }
-
-            // TODO: Remaining audio types
-            // StreamType::IsoIec13818_7AudioWithAdtsTransportSyntax => {}
-            // StreamType::IsoIec14496_3AudioWithTheLatmTransportSyntaxAsDefinedInIsoIec14496_3Amd1 => {}
-            _ => {}
+            Some(audio_type) => dolby_pids.push(AudioPID {
+                pid: elementary_stream.elementary_pid,
+                language_code,
+                second_language_code,
+                // TODO: audio_type is weird, w_scan2 and other data I found isn't coherent
+                //audio_type: Some(elementary_stream.stream_type.to_u8() as u16),
+                audio_type: Some(audio_type as u16),
+            }),
+            // Regular Audio, including AAC
+            None => regular_pids.push(AudioPID {
+                pid: elementary_stream.elementary_pid,
+                language_code,
+                second_language_code,
+                audio_type: Some(elementary_stream.stream_type.to_u8() as u16),
+            }),
         }
     }
 
@@ -260,3 +342,349 @@ fn pmt_to_audio_pids(pmt_element: &ProgramMap) -> AudioPIDList {
         dolby_pids,
     }
 }
+
+fn pmt_to_teletext_pids(pmt_element: &ProgramMap) -> TeletextPIDList {
+    TeletextPIDList {
+        teletext: pmt_element
+            .teletext_streams()
+            .map(|e| e.elementary_pid)
+            .collect(),
+        subtitles: pmt_to_subtitle_pids(pmt_element),
+    }
+}
+
+fn pmt_to_subtitle_pids(pmt_element: &ProgramMap) -> Vec<SubtitlePID> {
+    let mut subtitles = Vec::new();
+
+    for elementary_stream in pmt_element.subtitle_streams() {
+        if let Some(subtitling) = elementary_stream.descriptors.find::<Subtitling>() {
+            // A single stream may carry multiple languages, only keep the first one like w_scan2
+            let language = subtitling
+                .elements
+                .first()
+                .map(|e| e.language_code.to_string())
+                .unwrap_or_default();
+            subtitles.push(SubtitlePID {
+                pid: elementary_stream.elementary_pid,
+                language,
+            });
+        }
+    }
+
+    subtitles
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        frontend::{
+            DeliverySystem,
+            properties::{get::SignalStrength, set::BandwidthHz},
+        },
+        interpret::{ChannelInformation, LcnSource, assign_bouquets, resolve_logical_channel},
+        mpeg::{Packet, descriptors::Descriptor},
+        scan::Transponder,
+        si::{
+            bat::BouquetAssociation,
+            nit::{NetworkInformation, NitElement},
+            pmt::{ProgramMap, StreamCategory},
+            sdt::ServiceDescription,
+        },
+    };
+
+    /// Build a section with a valid header around given payload. CRC is left empty.
+    fn section(
+        table_id: u8,
+        identifier: u16,
+        section_number: u8,
+        last_section_number: u8,
+        payload: &[u8],
+    ) -> Packet {
+        let section_length = (5 + payload.len() + 4) as u16;
+        let mut buf = vec![
+            table_id,
+            0b1011_0000 | (section_length >> 8) as u8,
+            section_length as u8,
+            (identifier >> 8) as u8,
+            identifier as u8,
+            0b1100_0001,
+            section_number,
+            last_section_number,
+        ];
+        buf.extend_from_slice(payload);
+        buf.extend_from_slice(&[0; 4]);
+        Packet::from_buf(&buf).unwrap()
+    }
+
+    fn sdt_service(service_id: u16, name: &str) -> Vec<u8> {
+        let mut descriptor = vec![0x48, 3 + name.len() as u8, 0x01, 0, name.len() as u8];
+        descriptor.extend_from_slice(name.as_bytes());
+
+        let mut service = vec![
+            (service_id >> 8) as u8,
+            service_id as u8,
+            0b1111_1100,
+            0b1000_0000,
+            descriptor.len() as u8,
+        ];
+        service.extend(descriptor);
+        service
+    }
+
+    fn pmt(service_id: u16, video_pid: u16) -> ProgramMap {
+        let payload = [
+            0b1110_0000 | (video_pid >> 8) as u8,
+            video_pid as u8,
+            0b1111_0000,
+            0,
+            0x1B,
+            0b1110_0000 | (video_pid >> 8) as u8,
+            video_pid as u8,
+            0b1111_0000,
+            0,
+        ];
+        ProgramMap::from_packet(&section(0x02, service_id, 0, 0, &payload)).unwrap()
+    }
+
+    #[test]
+    fn multi_section_sdt() {
+        let mut sdt_0 = vec![0x20, 0x85, 0xFF];
+        sdt_0.extend(sdt_service(0x0101, "First"));
+        let mut sdt_1 = vec![0x20, 0x85, 0xFF];
+        sdt_1.extend(sdt_service(0x0102, "Second"));
+        let service_description = ServiceDescription::from_packets(&[
+            section(0x42, 0x0001, 0, 1, &sdt_0),
+            section(0x42, 0x0001, 1, 1, &sdt_1),
+        ])
+        .unwrap();
+
+        let nit = [
+            0b1111_0000,
+            0,
+            0b1111_0000,
+            14,
+            0x00,
+            0x01,
+            0x20,
+            0x85,
+            0b1111_0000,
+            8,
+            0x41,
+            6,
+            0x01,
+            0x01,
+            0x01,
+            0x01,
+            0x02,
+            0x01,
+        ];
+        let network_information =
+            NetworkInformation::from_packets(&[section(0x40, 0x3001, 0, 0, &nit)]).unwrap();
+
+        let transponder = Transponder {
+            transport_stream_id: 0x3001,
+            frequency: 474_166_000,
+            system: DeliverySystem::DvbT,
+            bandwidth: Some(BandwidthHz::_8MHz),
+            symbol_rate: None,
+            strength: SignalStrength(None),
+            program_map: vec![pmt(0x0101, 0x0100), pmt(0x0102, 0x0200)],
+            service_description,
+            network_information,
+        };
+
+        assert_eq!(
+            transponder.to_string(),
+            "474.166 MHz, DVB-T, 8 MHz, strength unknown, 2 services"
+        );
+
+        let channels = ChannelInformation::from_transponder(&transponder);
+        let names: Vec<&str> = channels.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["First", "Second"]);
+        assert_eq!(
+            channels[0].to_string(),
+            "First (service 257): video 256, audio none"
+        );
+
+        // Second service also has a German name
+        let mut transponder = transponder;
+        let names = Descriptor::read(0x5D, b"deu\x00\x06Zweite").unwrap();
+        transponder.service_description.services[1]
+            .descriptors
+            .push(names);
+        let channels =
+            ChannelInformation::from_transponder_in_language(&transponder, "deu".parse().ok());
+        let names: Vec<&str> = channels.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["First", "Zweite"]);
+
+        // Only the first service is part of a bouquet
+        let mut channels = channels;
+        let bat = [
+            0xF0, 0x05, 0x47, 0x03, b'T', b'N', b'T', // Bouquet name
+            0xF0, 0x0B, 0x00, 0x01, 0x20, 0x85, 0xF0, 0x05, // Transport stream
+            0x41, 0x03, 0x01, 0x01, 0x19, // Service list
+        ];
+        let bouquet =
+            BouquetAssociation::from_packets(&[section(0x4A, 0x0042, 0, 0, &bat)]).unwrap();
+        assign_bouquets(&mut channels, &[bouquet]);
+        assert_eq!(channels[0].bouquet.as_deref(), Some("TNT"));
+        assert_eq!(channels[1].bouquet, None);
+    }
+
+    #[test]
+    fn symbol_rate() {
+        // Cable delivery system at 346MHz, 6.9Msym/s
+        let cable = Descriptor::read(
+            0x44,
+            &[
+                0x03, 0x46, 0x00, 0x00, 0xFF, 0xF2, 0x03, 0x00, 0x69, 0x00, 0x0F,
+            ],
+        )
+        .unwrap();
+        let mut element = NitElement {
+            transport_stream_id: 1,
+            original_network_id: 1,
+            transport_descriptors: vec![cable],
+        };
+        assert_eq!(
+            super::find_symbol_rate_from_nit_element(&element),
+            Some(6_900_000)
+        );
+
+        element.transport_descriptors.clear();
+        assert_eq!(super::find_symbol_rate_from_nit_element(&element), None);
+    }
+
+    #[test]
+    fn hd_simulcast_lcn() {
+        let lcn = Descriptor::read(0x83, &[0x01, 0x01, 0xFC, 0x01]).unwrap();
+        let hd = Descriptor::read(0x88, &[0x01, 0x01, 0xFC, 0x0B]).unwrap();
+        let mut element = NitElement {
+            transport_stream_id: 1,
+            original_network_id: 1,
+            transport_descriptors: vec![lcn, hd],
+        };
+        assert_eq!(
+            resolve_logical_channel(&element, 0x0101),
+            Some((11, LcnSource::HdSimulcastLogicalChannel))
+        );
+        assert_eq!(resolve_logical_channel(&element, 0x0102), None);
+
+        element.transport_descriptors.pop();
+        assert_eq!(
+            resolve_logical_channel(&element, 0x0101),
+            Some((1, LcnSource::LogicalChannel))
+        );
+    }
+
+    #[test]
+    fn aac_and_ac4_audio() {
+        let payload = [
+            0xE1, 0x00, 0xF0, 0x00, // PCR PID, no program info
+            0x1B, 0xE1, 0x00, 0xF0, 0x00, // Video
+            0x0F, 0xE1, 0x10, 0xF0, 0x00, // AAC ADTS
+            0x11, 0xE1, 0x11, 0xF0, 0x00, // AAC LATM
+            0x06, 0xE1, 0x20, 0xF0, 0x04, // Private data
+            0x7F, 0x02, 0x15, 0x00, // AC-4
+        ];
+        let pmt = ProgramMap::from_packet(&section(0x02, 0x0101, 0, 0, &payload)).unwrap();
+
+        let list = super::pmt_to_audio_pids(&pmt);
+        let regular: Vec<(u16, Option<u16>)> = list
+            .regular_pids
+            .iter()
+            .map(|a| (a.pid, a.audio_type))
+            .collect();
+        assert_eq!(regular, [(0x0110, Some(0x0F)), (0x0111, Some(0x11))]);
+        assert_eq!(list.dolby_pids.len(), 1);
+        assert_eq!(list.dolby_pids[0].pid, 0x0120);
+        assert_eq!(list.dolby_pids[0].audio_type, Some(0x15));
+
+        let codecs: Vec<(&str, StreamCategory)> = pmt
+            .elementary_streams
+            .iter()
+            .map(|e| (e.codec_name(), e.category()))
+            .collect();
+        assert_eq!(
+            codecs,
+            [
+                ("H.264/AVC Video", StreamCategory::Video),
+                ("AAC (ADTS)", StreamCategory::Audio),
+                ("AAC (LATM)", StreamCategory::Audio),
+                ("AC-4", StreamCategory::Audio),
+            ]
+        );
+    }
+
+    #[test]
+    fn registration_audio() {
+        let payload = [
+            0xE1, 0x00, 0xF0, 0x00, // PCR PID, no program info
+            0x81, 0xE1, 0x40, 0xF0, 0x06, // User private, like ATSC AC-3
+            0x05, 0x04, b'A', b'C', b'-', b'3', // Registration
+            0x06, 0xE1, 0x41, 0xF0, 0x06, // Private data
+            0x05, 0x04, b'E', b'A', b'C', b'3', // Registration
+            0x06, 0xE1, 0x42, 0xF0, 0x06, // Private data
+            0x05, 0x04, b'K', b'L', b'V', b'A', // Registration, not audio
+        ];
+        let pmt = ProgramMap::from_packet(&section(0x02, 0x0101, 0, 0, &payload)).unwrap();
+
+        let list = super::pmt_to_audio_pids(&pmt);
+        assert!(list.regular_pids.is_empty());
+        let dolby: Vec<(u16, Option<u16>)> = list
+            .dolby_pids
+            .iter()
+            .map(|a| (a.pid, a.audio_type))
+            .collect();
+        assert_eq!(dolby, [(0x0140, Some(0x6A)), (0x0141, Some(0x7A))]);
+    }
+
+    #[test]
+    fn dual_language_audio() {
+        let payload = [
+            0xE1, 0x00, 0xF0, 0x00, // PCR PID, no program info
+            0x03, 0xE1, 0x10, 0xF0, 0x0A, // MPEG audio
+            0x0A, 0x08, // ISO 639 language
+            b'd', b'e', b'u', 0x00, b'e', b'n', b'g', 0x00, // One language per channel
+        ];
+        let pmt = ProgramMap::from_packet(&section(0x02, 0x0101, 0, 0, &payload)).unwrap();
+
+        let list = super::pmt_to_audio_pids(&pmt);
+        assert_eq!(list.regular_pids[0].language_code, "deu");
+        assert_eq!(list.regular_pids[0].second_language_code, "eng");
+    }
+
+    #[test]
+    fn teletext_pids() {
+        let payload = [
+            0xE1, 0x00, 0xF0, 0x00, // PCR PID, no program info
+            0x1B, 0xE1, 0x00, 0xF0, 0x00, // Video
+            0x06, 0xE1, 0x30, 0xF0, 0x07, // Private data
+            0x56, 0x05, b'f', b'r', b'a', 0x09, 0x00, // Teletext, initial page 100
+        ];
+        let pmt = ProgramMap::from_packet(&section(0x02, 0x0101, 0, 0, &payload)).unwrap();
+
+        let list = super::pmt_to_teletext_pids(&pmt);
+        assert_eq!(list.teletext, [0x0130]);
+        assert!(list.subtitles.is_empty());
+    }
+
+    #[test]
+    fn subtitle_pids() {
+        let payload = [
+            0xE1, 0x00, 0xF0, 0x00, // PCR PID, no program info
+            0x1B, 0xE1, 0x00, 0xF0, 0x00, // Video
+            0x06, 0xE1, 0x40, 0xF0, 0x0A, // Private data
+            0x59, 0x08, b'f', b'r', b'a', 0x10, 0x00, 0x01, 0x00, 0x01, // Subtitling
+        ];
+        let pmt = ProgramMap::from_packet(&section(0x02, 0x0101, 0, 0, &payload)).unwrap();
+
+        let subtitles = super::pmt_to_subtitle_pids(&pmt);
+        assert_eq!(subtitles.len(), 1);
+        assert_eq!(subtitles[0].pid, 0x0140);
+        assert_eq!(subtitles[0].language, "fra");
+    }
+}
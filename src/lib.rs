@@ -23,7 +23,7 @@
 //! The user, through the Linux kernel IOCTLs, tells the frontend to tune for a particular [delivery system](frontend::DeliverySystem) (DVB-T, DVB-T2, DVB-S...) with a specific bandwidth and frequency.
 //! The frontend should then handle all of the radio-frequency business.
 //!
-//! If everything works out, the frontend should enter a locked state ([`has_lock`](frontend::sys::FeStatus::has_lock)) and start streaming the whole multiplex to the demuxer.
+//! If everything works out, the frontend should enter a locked state (see [`wait_for_lock`](frontend::Frontend::wait_for_lock)) and start streaming the whole multiplex to the demuxer.
 //! A multiplex is a single MPEG Transport Stream (TS) containing multiple TV channels, including multiple audio and video streams, along with other data.
 //!
 //! As far as I know, there is no direct way to access that raw multiplex from software (I may be wrong here).
@@ -32,6 +32,7 @@
 //! From there, they can set a filter that will selectively choose specific packets to send over to the program.
 //!
 
+pub mod adapter;
 pub mod bands;
 pub mod conf;
 pub mod demux;
@@ -42,3 +43,8 @@ pub mod mpeg;
 pub mod scan;
 pub mod si;
 pub mod utils;
+
+pub use adapter::{enumerate, list_adapters};
+pub use error::{Error, Result};
+pub use scan::fetch_pmt;
+pub use si::{ParsedSection, parse_section};
@@ -0,0 +1,153 @@
+//! Reassembly of tables that span multiple sections.
+
+use crate::mpeg::Packet;
+
+/// Collects all sections of a single table.
+///
+/// Tables like the NIT or SDT may be too large for a single section, in which case they are split over sections numbered from 0 to `last_section_number`.
+/// Feed every received section to [push](Self::push) until it reports the table as complete.
+///
+/// Sections describing the next version of a table (`current_next_indicator` cleared) are ignored by default,
+/// so a table that is about to change doesn't get mixed with the one currently applicable.
+#[derive(Default)]
+pub struct SectionAssembler {
+    version_number: Option<u8>,
+    sections: Vec<Option<Packet>>,
+    accept_next: bool,
+}
+
+impl SectionAssembler {
+    pub fn new() -> SectionAssembler {
+        SectionAssembler::default()
+    }
+
+    /// Create an assembler that also collects sections of the next version of a table, not applicable yet.
+    ///
+    /// A table is still assembled from sections of a single version, so this is mostly useful to see changes in advance.
+    pub fn accepting_next() -> SectionAssembler {
+        SectionAssembler {
+            accept_next: true,
+            ..SectionAssembler::default()
+        }
+    }
+
+    /// Add a received section. Returns `true` once every section of the table was received.
+    ///
+    /// If the version of the table changes mid-collection, all previously received sections are discarded.
+    pub fn push(&mut self, packet: Packet) -> bool {
+        if !packet.header.current_next_indicator && !self.accept_next {
+            return self.is_complete();
+        }
+
+        let version_number = packet.header.version_number;
+        let section_count = packet.header.last_section_number as usize + 1;
+
+        if (self.version_number != Some(version_number)) || (self.sections.len() != section_count) {
+            self.version_number = Some(version_number);
+            self.sections = (0..section_count).map(|_| None).collect();
+        }
+
+        if let Some(slot) = self.sections.get_mut(packet.header.section_number as usize) {
+            *slot = Some(packet);
+        }
+
+        self.is_complete()
+    }
+
+    /// Whether every section from 0 to `last_section_number` was received.
+    pub fn is_complete(&self) -> bool {
+        !self.sections.is_empty() && self.sections.iter().all(Option::is_some)
+    }
+
+    /// Returns the payloads of all sections concatenated in section number order, or `None` if some are still missing.
+    ///
+    /// This is only meaningful for tables whose sections simply continue the same loop, like the PAT.
+    /// Tables restarting their loops in every section (NIT, SDT...) should be parsed section by section with [finish](Self::finish) instead.
+    pub fn payload(&self) -> Option<Vec<u8>> {
+        if !self.is_complete() {
+            return None;
+        }
+        Some(
+            self.sections
+                .iter()
+                .flatten()
+                .flat_map(|packet| packet.data.iter().copied())
+                .collect(),
+        )
+    }
+
+    /// Returns all sections ordered by section number, or `None` if some are still missing.
+    pub fn finish(self) -> Option<Vec<Packet>> {
+        if !self.is_complete() {
+            return None;
+        }
+        Some(self.sections.into_iter().flatten().collect())
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(version_number: u8, section_number: u8, last_section_number: u8) -> Packet {
+        Packet::from_buf(&[
+            0x00,
+            0xB0,
+            0x0A,
+            0x00,
+            0x01,
+            0b1100_0001 | (version_number << 1),
+            section_number,
+            last_section_number,
+            section_number,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn out_of_order_sections() {
+        let mut assembler = SectionAssembler::new();
+        assert!(!assembler.push(section(3, 1, 2)));
+        assert!(!assembler.push(section(3, 0, 2)));
+        assert!(assembler.push(section(3, 2, 2)));
+
+        assert_eq!(assembler.payload().unwrap(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn version_change_resets() {
+        let mut assembler = SectionAssembler::new();
+        assert!(!assembler.push(section(3, 0, 1)));
+        // New version of the table, section 0 from previous version must be dropped
+        assert!(!assembler.push(section(4, 1, 1)));
+        assert!(assembler.payload().is_none());
+        assert!(assembler.push(section(4, 0, 1)));
+
+        let sections = assembler.finish().unwrap();
+        assert!(sections.iter().all(|s| s.header.version_number == 4));
+    }
+
+    #[test]
+    fn next_sections() {
+        let next = || {
+            let mut next = section(5, 0, 0);
+            next.header.current_next_indicator = false;
+            next
+        };
+
+        let mut assembler = SectionAssembler::new();
+        assert!(!assembler.push(next()));
+        assert!(assembler.push(section(4, 0, 0)));
+
+        let mut assembler = SectionAssembler::accepting_next();
+        assert!(assembler.push(next()));
+        assert_eq!(assembler.finish().unwrap()[0].header.version_number, 5);
+    }
+}
@@ -1,6 +1,8 @@
+use crate::{error::DescriptorParseError, mpeg::descriptors::check_length};
+
 pub const DESCRIPTOR_ID: u8 = 0x6A;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Ac3 {
     pub component_type: Option<u8>,
     pub bsid: Option<u8>,
@@ -10,13 +12,21 @@ pub struct Ac3 {
 }
 
 impl Ac3 {
-    pub fn from_buf(buf: &[u8]) -> Ac3 {
+    pub fn from_buf(buf: &[u8]) -> Result<Ac3, DescriptorParseError> {
+        check_length(buf, 1)?;
+
         let component_type_flag = (buf[0] & 0b1000_0000) != 0;
         let bsid_flag = (buf[0] & 0b0100_0000) != 0;
         let mainid_flag = (buf[0] & 0b0010_0000) != 0;
         let asvc_flag = (buf[0] & 0b0001_0000) != 0;
         let _reserved = (buf[0] & 0b0000_1111) != 0;
 
+        let flag_count = [component_type_flag, bsid_flag, mainid_flag, asvc_flag]
+            .iter()
+            .filter(|f| **f)
+            .count();
+        check_length(buf, 1 + flag_count)?;
+
         let mut offset = 1;
 
         let component_type = if component_type_flag {
@@ -55,12 +65,27 @@ impl Ac3 {
 
         // TODO: Re-use some of the stuff used below for Enhanced AC3
 
-        Ac3 {
+        Ok(Ac3 {
             component_type,
             bsid,
             mainid,
             asvc,
             additional_info_byte,
+        })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = vec![0];
+
+        let fields = [self.component_type, self.bsid, self.mainid, self.asvc];
+        for (i, field) in fields.iter().enumerate() {
+            if let Some(value) = field {
+                buf[0] |= 0b1000_0000 >> i;
+                buf.push(*value);
+            }
         }
+
+        buf.extend_from_slice(&self.additional_info_byte);
+        buf
     }
 }
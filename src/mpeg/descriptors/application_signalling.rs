@@ -1,23 +1,26 @@
+use crate::{error::DescriptorParseError, mpeg::descriptors::check_length};
+
 pub const DESCRIPTOR_ID: u8 = 0x6F;
 
 // ETSI TS 102 809 page 37
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ApplicationSignalling {
     pub elements: Vec<ApplicationSignallingElement>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ApplicationSignallingElement {
     pub application_type: u16,
     pub ait_version_number: u8,
 }
 
 impl ApplicationSignalling {
-    pub fn from_buf(buf: &[u8]) -> ApplicationSignalling {
+    pub fn from_buf(buf: &[u8]) -> Result<ApplicationSignalling, DescriptorParseError> {
         let mut elements = Vec::new();
 
         let mut offset = 0;
         while offset < buf.len() {
+            check_length(buf, offset + 3)?;
             let _reserved = (buf[offset] & 0b1000_0000) != 0;
             let application_type = u16::from_be_bytes([buf[offset] & 0b0111_1111, buf[offset + 1]]);
             offset += 2;
@@ -30,6 +33,17 @@ impl ApplicationSignalling {
             });
         }
 
-        ApplicationSignalling { elements }
+        Ok(ApplicationSignalling { elements })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.elements.len() * 3);
+        for element in &self.elements {
+            let application_type = element.application_type.to_be_bytes();
+            buf.push(0b1000_0000 | application_type[0]);
+            buf.push(application_type[1]);
+            buf.push(0b1110_0000 | element.ait_version_number);
+        }
+        buf
     }
 }
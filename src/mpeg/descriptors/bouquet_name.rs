@@ -0,0 +1,23 @@
+use crate::{error::DescriptorParseError, mpeg::decode_stupid_string};
+
+pub const DESCRIPTOR_ID: u8 = 0x47;
+
+// ETSI EN 300 468 page 46
+/// Name of a bouquet, found in the bouquet descriptor loop of the BAT.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BouquetName {
+    pub name: String,
+}
+
+impl BouquetName {
+    pub fn from_buf(buf: &[u8]) -> Result<BouquetName, DescriptorParseError> {
+        Ok(BouquetName {
+            name: decode_stupid_string(buf).ok_or(DescriptorParseError::UnexpectedValue)?,
+        })
+    }
+
+    /// Written as-is, without a character table prefix.
+    pub fn to_buf(&self) -> Vec<u8> {
+        self.name.as_bytes().to_vec()
+    }
+}
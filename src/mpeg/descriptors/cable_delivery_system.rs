@@ -0,0 +1,57 @@
+use crate::{
+    error::DescriptorParseError,
+    mpeg::descriptors::{check_length, read_bcd, write_bcd},
+};
+
+pub const DESCRIPTOR_ID: u8 = 0x44;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CableDeliverySystem {
+    /// In units of 100Hz, see [frequency_hz](Self::frequency_hz)
+    pub frequency: u32,
+    pub fec_outer: u8,
+    pub modulation: u8,
+    /// In units of 100 symbols per second, see [symbols_per_second](Self::symbols_per_second)
+    pub symbol_rate: u32,
+    pub fec_inner: u8,
+}
+
+impl CableDeliverySystem {
+    pub fn from_buf(buf: &[u8]) -> Result<CableDeliverySystem, DescriptorParseError> {
+        check_length(buf, 11)?;
+
+        let frequency = read_bcd(&buf[0..4], 8);
+        let _reserved = u16::from_be_bytes([buf[4], buf[5]]) >> 4;
+        let fec_outer = buf[5] & 0b0000_1111;
+        let modulation = buf[6];
+        let symbol_rate = read_bcd(&buf[7..11], 7);
+        let fec_inner = buf[10] & 0b0000_1111;
+
+        Ok(CableDeliverySystem {
+            frequency,
+            fec_outer,
+            modulation,
+            symbol_rate,
+            fec_inner,
+        })
+    }
+
+    /// Frequency in Hz, ready to tune to
+    pub fn frequency_hz(&self) -> u32 {
+        self.frequency.saturating_mul(100)
+    }
+
+    pub fn symbols_per_second(&self) -> u32 {
+        self.symbol_rate.saturating_mul(100)
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = write_bcd(self.frequency, 8);
+        buf.push(0xFF);
+        buf.push(0xF0 | self.fec_outer);
+        buf.push(self.modulation);
+        buf.extend(write_bcd(self.symbol_rate, 7));
+        buf[10] |= self.fec_inner;
+        buf
+    }
+}
@@ -1,13 +1,15 @@
+use crate::{error::DescriptorParseError, mpeg::descriptors::check_length};
+
 pub const DESCRIPTOR_ID: u8 = 0x13;
 
 // ETSI TS 102 809 page 125
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CarouselIdentifier {
     pub carousel_id: u32,
     pub identifier: Identifier,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Identifier {
     Standard {
         private_data_bytes: Vec<u8>,
@@ -27,7 +29,9 @@ pub enum Identifier {
 }
 
 impl CarouselIdentifier {
-    pub fn from_buf(buf: &[u8]) -> CarouselIdentifier {
+    pub fn from_buf(buf: &[u8]) -> Result<CarouselIdentifier, DescriptorParseError> {
+        check_length(buf, 5)?;
+
         let carousel_id = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]);
         let format_id = buf[4];
 
@@ -35,6 +39,7 @@ impl CarouselIdentifier {
             let private_data_bytes = buf[5..].to_vec();
             Identifier::Standard { private_data_bytes }
         } else if format_id == 1 {
+            check_length(buf, 21)?;
             let module_version = buf[5];
             let module_id = u16::from_be_bytes([buf[6], buf[7]]);
             let block_size = u16::from_be_bytes([buf[8], buf[9]]);
@@ -43,6 +48,7 @@ impl CarouselIdentifier {
             let original_size = u32::from_be_bytes([buf[15], buf[16], buf[17], buf[18]]);
             let time_out = buf[19];
             let object_key_length = buf[20];
+            check_length(buf, 21 + object_key_length as usize)?;
             let object_key_data = buf[21..21 + object_key_length as usize].to_vec();
             let private_data_byte = buf[21 + object_key_length as usize..].to_vec();
 
@@ -59,15 +65,47 @@ impl CarouselIdentifier {
                 private_data_byte,
             }
         } else {
-            panic!(
-                "unexpected format id ({}) for CarouselIdentifier descriptor",
-                format_id
-            )
+            return Err(DescriptorParseError::UnexpectedValue);
         };
 
-        CarouselIdentifier {
+        Ok(CarouselIdentifier {
             carousel_id,
             identifier,
+        })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = self.carousel_id.to_be_bytes().to_vec();
+        match &self.identifier {
+            Identifier::Standard { private_data_bytes } => {
+                buf.push(0);
+                buf.extend_from_slice(private_data_bytes);
+            }
+            Identifier::Enhanced {
+                module_version,
+                module_id,
+                block_size,
+                module_size,
+                compression_method,
+                original_size,
+                time_out,
+                object_key_length: _,
+                object_key_data,
+                private_data_byte,
+            } => {
+                buf.push(1);
+                buf.push(*module_version);
+                buf.extend_from_slice(&module_id.to_be_bytes());
+                buf.extend_from_slice(&block_size.to_be_bytes());
+                buf.extend_from_slice(&module_size.to_be_bytes());
+                buf.push(*compression_method);
+                buf.extend_from_slice(&original_size.to_be_bytes());
+                buf.push(*time_out);
+                buf.push(object_key_data.len() as u8);
+                buf.extend_from_slice(object_key_data);
+                buf.extend_from_slice(private_data_byte);
+            }
         }
+        buf
     }
 }
@@ -0,0 +1,63 @@
+use crate::{
+    error::DescriptorParseError,
+    mpeg::{LanguageCode, decode_stupid_string, descriptors::check_length},
+};
+
+pub const DESCRIPTOR_ID: u8 = 0x64;
+
+// ETSI EN 300 468 page 56
+/// Describes a data broadcast component, like an HbbTV or MHEG-5 carousel. [DataBroadcastId](super::data_broadcast_id::DataBroadcastId) is the short version of this.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataBroadcast {
+    /// Defined in ETSI TS 101 162
+    pub data_broadcast_id: u16,
+    /// Same as the one of the [StreamIdentifier](super::stream_identifier::StreamIdentifier) of the component
+    pub component_tag: u8,
+    /// Meaning depends on `data_broadcast_id`
+    pub selector_bytes: Vec<u8>,
+    // ISO 639
+    pub language_code: LanguageCode,
+    pub text: String,
+}
+
+impl DataBroadcast {
+    pub fn from_buf(buf: &[u8]) -> Result<DataBroadcast, DescriptorParseError> {
+        check_length(buf, 4)?;
+
+        let data_broadcast_id = u16::from_be_bytes([buf[0], buf[1]]);
+        let component_tag = buf[2];
+        let selector_length = buf[3] as usize;
+        let mut offset = 4;
+        // Selector, then language code and text length
+        check_length(buf, offset + selector_length + 4)?;
+        let selector_bytes = buf[offset..offset + selector_length].to_vec();
+        offset += selector_length;
+
+        let language_code = LanguageCode([buf[offset], buf[offset + 1], buf[offset + 2]]);
+        let text_length = buf[offset + 3] as usize;
+        offset += 4;
+        check_length(buf, offset + text_length)?;
+        let text = decode_stupid_string(&buf[offset..offset + text_length])
+            .ok_or(DescriptorParseError::UnexpectedValue)?;
+
+        Ok(DataBroadcast {
+            data_broadcast_id,
+            component_tag,
+            selector_bytes,
+            language_code,
+            text,
+        })
+    }
+
+    /// Text is written as-is, without a character table prefix.
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = self.data_broadcast_id.to_be_bytes().to_vec();
+        buf.push(self.component_tag);
+        buf.push(self.selector_bytes.len() as u8);
+        buf.extend_from_slice(&self.selector_bytes);
+        buf.extend_from_slice(&self.language_code.0);
+        buf.push(self.text.len() as u8);
+        buf.extend_from_slice(self.text.as_bytes());
+        buf
+    }
+}
@@ -1,20 +1,30 @@
+use crate::{error::DescriptorParseError, mpeg::descriptors::check_length};
+
 pub const DESCRIPTOR_ID: u8 = 0x66;
 
 // ETSI EN 300 468 page 57
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DataBroadcastId {
     pub data_broadcast_id: u16,
     pub selector_bytes: Vec<u8>,
 }
 
 impl DataBroadcastId {
-    pub fn from_buf(buf: &[u8]) -> DataBroadcastId {
+    pub fn from_buf(buf: &[u8]) -> Result<DataBroadcastId, DescriptorParseError> {
+        check_length(buf, 2)?;
+
         let data_broadcast_id = u16::from_be_bytes([buf[0], buf[1]]);
         let selector_bytes = buf[2..].to_vec();
 
-        DataBroadcastId {
+        Ok(DataBroadcastId {
             data_broadcast_id,
             selector_bytes,
-        }
+        })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = self.data_broadcast_id.to_be_bytes().to_vec();
+        buf.extend_from_slice(&self.selector_bytes);
+        buf
     }
 }
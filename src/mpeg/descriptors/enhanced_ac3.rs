@@ -1,7 +1,9 @@
+use crate::{error::DescriptorParseError, mpeg::descriptors::check_length};
+
 pub const DESCRIPTOR_ID: u8 = 0x7A;
 
 // ETSI EN 300 468 page 156
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EnhancedAc3 {
     pub mixinfoexists: bool,
     pub component_type: Option<EnhancedAc3ComponentType>,
@@ -14,7 +16,7 @@ pub struct EnhancedAc3 {
     pub additional_info: Vec<u8>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EnhancedAc3ComponentType {
     pub enhanced: bool,
     pub full_service: bool,
@@ -22,7 +24,7 @@ pub struct EnhancedAc3ComponentType {
     pub channel_setup: EnhancedAc3ChannelSetup,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EnhancedAc3ServiceType {
     CompleteMain,
     MusicAndEffects,
@@ -36,7 +38,7 @@ pub enum EnhancedAc3ServiceType {
     _Invalid(bool, bool, bool),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EnhancedAc3ChannelSetup {
     Mono,
     TwoIndependent,
@@ -49,7 +51,9 @@ pub enum EnhancedAc3ChannelSetup {
 }
 
 impl EnhancedAc3 {
-    pub fn from_buf(buf: &[u8]) -> EnhancedAc3 {
+    pub fn from_buf(buf: &[u8]) -> Result<EnhancedAc3, DescriptorParseError> {
+        check_length(buf, 1)?;
+
         let mut offset = 0;
 
         let component_type_flag = (buf[0] & 0b1000_0000) != 0;
@@ -62,6 +66,20 @@ impl EnhancedAc3 {
         let substream3_flag = (buf[0] & 0b0000_0001) != 0;
         offset += 1;
 
+        let flag_count = [
+            component_type_flag,
+            bsid_flag,
+            mainid_flag,
+            asvc_flag,
+            substream1_flag,
+            substream2_flag,
+            substream3_flag,
+        ]
+        .iter()
+        .filter(|f| **f)
+        .count();
+        check_length(buf, offset + flag_count)?;
+
         let component_type = if component_type_flag {
             let byte = buf[offset];
             offset += 1;
@@ -89,9 +107,9 @@ impl EnhancedAc3 {
                     (x, y, z, _) => EnhancedAc3ServiceType::_Invalid(x, y, z),
                 },
                 channel_setup: match (
-                    (byte & 0b0010_0000) != 0,
-                    (byte & 0b0001_0000) != 0,
-                    (byte & 0b0000_1000) != 0,
+                    (byte & 0b0000_0100) != 0,
+                    (byte & 0b0000_0010) != 0,
+                    (byte & 0b0000_0001) != 0,
                 ) {
                     (false, false, false) => EnhancedAc3ChannelSetup::Mono,
                     (false, false, true) => EnhancedAc3ChannelSetup::TwoIndependent,
@@ -157,7 +175,7 @@ impl EnhancedAc3 {
 
         let additional_info = buf[offset..].to_vec();
 
-        EnhancedAc3 {
+        Ok(EnhancedAc3 {
             mixinfoexists,
             component_type,
             bsid,
@@ -167,6 +185,66 @@ impl EnhancedAc3 {
             substream2,
             substream3,
             additional_info,
+        })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = vec![(self.mixinfoexists as u8) << 3];
+
+        if let Some(component_type) = &self.component_type {
+            buf[0] |= 0b1000_0000;
+            buf.push(component_type.to_byte());
         }
+
+        let fields = [
+            (0b0100_0000, self.bsid),
+            (0b0010_0000, self.mainid),
+            (0b0001_0000, self.asvc),
+            (0b0000_0100, self.substream1),
+            (0b0000_0010, self.substream2),
+            (0b0000_0001, self.substream3),
+        ];
+        for (flag, field) in fields {
+            if let Some(value) = field {
+                buf[0] |= flag;
+                buf.push(value);
+            }
+        }
+
+        buf.extend_from_slice(&self.additional_info);
+        buf
+    }
+}
+
+impl EnhancedAc3ComponentType {
+    fn to_byte(&self) -> u8 {
+        let service_type = match self.service_type {
+            EnhancedAc3ServiceType::CompleteMain => 0b000,
+            EnhancedAc3ServiceType::MusicAndEffects => 0b001,
+            EnhancedAc3ServiceType::VisuallyImpaired => 0b010,
+            EnhancedAc3ServiceType::HearingImpaired => 0b011,
+            EnhancedAc3ServiceType::Dialogue => 0b100,
+            EnhancedAc3ServiceType::Commentary => 0b101,
+            EnhancedAc3ServiceType::Emergency => 0b110,
+            EnhancedAc3ServiceType::Voiceover | EnhancedAc3ServiceType::Karaoke => 0b111,
+            EnhancedAc3ServiceType::_Invalid(x, y, z) => {
+                ((x as u8) << 2) | ((y as u8) << 1) | z as u8
+            }
+        };
+        let channel_setup = match self.channel_setup {
+            EnhancedAc3ChannelSetup::Mono => 0b000,
+            EnhancedAc3ChannelSetup::TwoIndependent => 0b001,
+            EnhancedAc3ChannelSetup::Stereo => 0b010,
+            EnhancedAc3ChannelSetup::SurroundStereoEncoded => 0b011,
+            EnhancedAc3ChannelSetup::MultichannelOver2 => 0b100,
+            EnhancedAc3ChannelSetup::MultichannelOver5Dot1 => 0b101,
+            EnhancedAc3ChannelSetup::Independent => 0b110,
+            EnhancedAc3ChannelSetup::Reserved => 0b111,
+        };
+
+        ((self.enhanced as u8) << 7)
+            | ((self.full_service as u8) << 6)
+            | (service_type << 3)
+            | channel_setup
     }
 }
@@ -0,0 +1,101 @@
+use crate::{
+    error::DescriptorParseError,
+    mpeg::{LanguageCode, decode_stupid_string, descriptors::check_length},
+};
+
+pub const DESCRIPTOR_ID: u8 = 0x4E;
+
+// ETSI EN 300 468 page 60
+/// One fragment of an event's full description. Long descriptions are chained across multiple of these, see [merge_text](Self::merge_text).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedEvent {
+    pub descriptor_number: u8,
+    pub last_descriptor_number: u8,
+    // ISO 639
+    pub language_code: LanguageCode,
+    pub items: Vec<ExtendedEventItem>,
+    /// Kept raw, as a character may be split between two fragments
+    pub text: Vec<u8>,
+}
+
+/// A pair like "Director" / "Someone"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedEventItem {
+    pub description: Vec<u8>,
+    pub item: Vec<u8>,
+}
+
+impl ExtendedEvent {
+    pub fn from_buf(buf: &[u8]) -> Result<ExtendedEvent, DescriptorParseError> {
+        check_length(buf, 5)?;
+
+        let descriptor_number = (buf[0] & 0b1111_0000) >> 4;
+        let last_descriptor_number = buf[0] & 0b0000_1111;
+        let language_code = LanguageCode([buf[1], buf[2], buf[3]]);
+        let length_of_items = buf[4] as usize;
+        let mut offset = 5;
+        check_length(buf, offset + length_of_items + 1)?;
+
+        let items_end = offset + length_of_items;
+        let mut items = Vec::new();
+        while offset < items_end {
+            let description_length = buf[offset] as usize;
+            offset += 1;
+            if offset + description_length + 1 > items_end {
+                return Err(DescriptorParseError::TooShort);
+            }
+            let description = buf[offset..offset + description_length].to_vec();
+            offset += description_length;
+
+            let item_length = buf[offset] as usize;
+            offset += 1;
+            if offset + item_length > items_end {
+                return Err(DescriptorParseError::TooShort);
+            }
+            let item = buf[offset..offset + item_length].to_vec();
+            offset += item_length;
+
+            items.push(ExtendedEventItem { description, item });
+        }
+
+        let text_length = buf[offset] as usize;
+        offset += 1;
+        check_length(buf, offset + text_length)?;
+        let text = buf[offset..offset + text_length].to_vec();
+
+        Ok(ExtendedEvent {
+            descriptor_number,
+            last_descriptor_number,
+            language_code,
+            items,
+            text,
+        })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut items = Vec::new();
+        for item in &self.items {
+            items.push(item.description.len() as u8);
+            items.extend_from_slice(&item.description);
+            items.push(item.item.len() as u8);
+            items.extend_from_slice(&item.item);
+        }
+
+        let mut buf = vec![(self.descriptor_number << 4) | self.last_descriptor_number];
+        buf.extend_from_slice(&self.language_code.0);
+        buf.push(items.len() as u8);
+        buf.extend(items);
+        buf.push(self.text.len() as u8);
+        buf.extend_from_slice(&self.text);
+        buf
+    }
+
+    /// Put the text of all fragments of a description back together, in order.
+    pub fn merge_text(fragments: &[ExtendedEvent]) -> String {
+        let mut sorted: Vec<&ExtendedEvent> = fragments.iter().collect();
+        sorted.sort_by_key(|f| f.descriptor_number);
+
+        let raw: Vec<u8> = sorted.iter().flat_map(|f| f.text.iter().copied()).collect();
+        decode_stupid_string(&raw).unwrap()
+    }
+}
@@ -1,17 +1,59 @@
+use crate::{
+    error::DescriptorParseError,
+    mpeg::descriptors::{
+        check_length,
+        t2_delivery_system::{self, T2DeliverySystem},
+    },
+};
+
 pub const DESCRIPTOR_ID: u8 = 0x7F;
 
+/// AC-4 has no descriptor of its own, it is signalled with this tag extension instead.
+pub const AC4_TAG_EXTENSION: u8 = 0x15;
+
 // ETSI EN 300 468 page 65
-// TODO: There may be more to this according to w_scan2
-#[derive(Debug, Clone)]
-pub struct Extension {
-    pub selector_bytes: Vec<u8>,
+/// Descriptors sharing a single tag, told apart by their first byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Extension {
+    T2DeliverySystem(T2DeliverySystem),
+    /// Tag extension without a parser, left as-is
+    Other {
+        tag_extension: u8,
+        selector_bytes: Vec<u8>,
+    },
 }
 
 impl Extension {
-    pub fn from_buf(buf: &[u8]) -> Extension {
-        //let tag_extension = buf[0];
-        let selector_bytes = buf[1..].to_vec();
+    pub fn from_buf(buf: &[u8]) -> Result<Extension, DescriptorParseError> {
+        check_length(buf, 1)?;
+
+        let tag_extension = buf[0];
+        let selector_bytes = &buf[1..];
+
+        Ok(match tag_extension {
+            t2_delivery_system::TAG_EXTENSION => {
+                Extension::T2DeliverySystem(T2DeliverySystem::from_buf(selector_bytes)?)
+            }
+            _ => Extension::Other {
+                tag_extension,
+                selector_bytes: selector_bytes.to_vec(),
+            },
+        })
+    }
+
+    pub fn tag_extension(&self) -> u8 {
+        match self {
+            Extension::T2DeliverySystem(_) => t2_delivery_system::TAG_EXTENSION,
+            Extension::Other { tag_extension, .. } => *tag_extension,
+        }
+    }
 
-        Extension { selector_bytes }
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = vec![self.tag_extension()];
+        match self {
+            Extension::T2DeliverySystem(d) => buf.extend(d.to_buf()),
+            Extension::Other { selector_bytes, .. } => buf.extend_from_slice(selector_bytes),
+        }
+        buf
     }
 }
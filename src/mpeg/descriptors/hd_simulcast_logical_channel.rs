@@ -0,0 +1,28 @@
+use crate::{
+    error::DescriptorParseError,
+    mpeg::descriptors::logical_channel::{LogicalChannel, LogicalChannelDescriptorElement},
+};
+
+pub const DESCRIPTOR_ID: u8 = 0x88;
+
+// EACEM private descriptor, same layout as the logical channel descriptor
+/// Logical channel numbers meant for receivers that can decode HD services, taking precedence over the [LogicalChannel] ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HdSimulcastLogicalChannel {
+    pub elements: Vec<LogicalChannelDescriptorElement>,
+}
+
+impl HdSimulcastLogicalChannel {
+    pub fn from_buf(buf: &[u8]) -> Result<HdSimulcastLogicalChannel, DescriptorParseError> {
+        Ok(HdSimulcastLogicalChannel {
+            elements: LogicalChannel::from_buf(buf)?.elements,
+        })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        LogicalChannel {
+            elements: self.elements.clone(),
+        }
+        .to_buf()
+    }
+}
@@ -1,19 +1,47 @@
+use crate::{
+    error::DescriptorParseError,
+    mpeg::{LanguageCode, descriptors::check_length},
+};
+
 pub const DESCRIPTOR_ID: u8 = 0x0A;
 
-#[derive(Debug, Clone)]
+// ISO/IEC 13818-1 page 85
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Iso639Language {
-    pub language: [u8; 4],
+    /// Usually a single one, dual-mono audio has one per channel
+    pub languages: Vec<Iso639LanguageElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Iso639LanguageElement {
+    pub language: LanguageCode,
+    /// Like clean effects or hearing impaired, as defined in ISO/IEC 13818-1 (table 2-60)
+    pub audio_type: u8,
 }
 
 impl Iso639Language {
-    pub fn from_buf(buf: &[u8]) -> Iso639Language {
-        if buf.len() != 4 {
-            // TODO: Error
-            panic!()
+    pub fn from_buf(buf: &[u8]) -> Result<Iso639Language, DescriptorParseError> {
+        let mut languages = Vec::new();
+
+        let mut offset = 0;
+        while offset < buf.len() {
+            check_length(buf, offset + 4)?;
+            languages.push(Iso639LanguageElement {
+                language: LanguageCode([buf[offset], buf[offset + 1], buf[offset + 2]]),
+                audio_type: buf[offset + 3],
+            });
+            offset += 4;
         }
 
-        Self {
-            language: [buf[0], buf[1], buf[2], buf[3]],
+        Ok(Iso639Language { languages })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.languages.len() * 4);
+        for element in &self.languages {
+            buf.extend_from_slice(&element.language.0);
+            buf.push(element.audio_type);
         }
+        buf
     }
 }
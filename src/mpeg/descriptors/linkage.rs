@@ -0,0 +1,108 @@
+use crate::{error::DescriptorParseError, mpeg::descriptors::check_length};
+
+pub const DESCRIPTOR_ID: u8 = 0x4A;
+
+// ETSI EN 300 468 page 67
+/// Points to a service that gives more information about, or replaces, the one this is attached to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linkage {
+    pub transport_stream_id: u16,
+    pub original_network_id: u16,
+    pub service_id: u16,
+    pub linkage_type: LinkageType,
+    /// Contents depend on `linkage_type`, like the target event for [EventLinkage](LinkageType::EventLinkage)
+    pub private_data: Vec<u8>,
+}
+
+/// Table of linkage types, taken from ETSI EN 300 468 page 68 (table 58)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LinkageType {
+    /// Service giving more information about this one
+    Information,
+    ElectronicProgrammeGuide,
+    /// Service to use when this one can't be descrambled
+    CaReplacement,
+    TsContainingCompleteBouquetSi,
+    /// Service to use when this one isn't running
+    ServiceReplacement,
+    DataBroadcast,
+    RcsMap,
+    MobileHandOver,
+    SystemSoftwareUpdate,
+    TsContainingSsuBatOrNit,
+    IpMacNotification,
+    TsContainingIntBatOrNit,
+    /// Event broadcast on another service, like the HD version of a program
+    EventLinkage,
+    ExtendedEventLinkage(u8),
+    UserDefined(u8),
+    Reserved(u8),
+}
+
+impl LinkageType {
+    pub fn from_byte(byte: u8) -> LinkageType {
+        match byte {
+            0x01 => Self::Information,
+            0x02 => Self::ElectronicProgrammeGuide,
+            0x03 => Self::CaReplacement,
+            0x04 => Self::TsContainingCompleteBouquetSi,
+            0x05 => Self::ServiceReplacement,
+            0x06 => Self::DataBroadcast,
+            0x07 => Self::RcsMap,
+            0x08 => Self::MobileHandOver,
+            0x09 => Self::SystemSoftwareUpdate,
+            0x0A => Self::TsContainingSsuBatOrNit,
+            0x0B => Self::IpMacNotification,
+            0x0C => Self::TsContainingIntBatOrNit,
+            0x0D => Self::EventLinkage,
+            0x0E..=0x1F => Self::ExtendedEventLinkage(byte),
+            0x80..=0xFE => Self::UserDefined(byte),
+            _ => Self::Reserved(byte),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            Self::Information => 0x01,
+            Self::ElectronicProgrammeGuide => 0x02,
+            Self::CaReplacement => 0x03,
+            Self::TsContainingCompleteBouquetSi => 0x04,
+            Self::ServiceReplacement => 0x05,
+            Self::DataBroadcast => 0x06,
+            Self::RcsMap => 0x07,
+            Self::MobileHandOver => 0x08,
+            Self::SystemSoftwareUpdate => 0x09,
+            Self::TsContainingSsuBatOrNit => 0x0A,
+            Self::IpMacNotification => 0x0B,
+            Self::TsContainingIntBatOrNit => 0x0C,
+            Self::EventLinkage => 0x0D,
+            Self::ExtendedEventLinkage(byte) | Self::UserDefined(byte) | Self::Reserved(byte) => {
+                *byte
+            }
+        }
+    }
+}
+
+impl Linkage {
+    pub fn from_buf(buf: &[u8]) -> Result<Linkage, DescriptorParseError> {
+        check_length(buf, 7)?;
+
+        Ok(Linkage {
+            transport_stream_id: u16::from_be_bytes([buf[0], buf[1]]),
+            original_network_id: u16::from_be_bytes([buf[2], buf[3]]),
+            service_id: u16::from_be_bytes([buf[4], buf[5]]),
+            linkage_type: LinkageType::from_byte(buf[6]),
+            private_data: buf[7..].to_vec(),
+        })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(7 + self.private_data.len());
+        buf.extend_from_slice(&self.transport_stream_id.to_be_bytes());
+        buf.extend_from_slice(&self.original_network_id.to_be_bytes());
+        buf.extend_from_slice(&self.service_id.to_be_bytes());
+        buf.push(self.linkage_type.to_byte());
+        buf.extend_from_slice(&self.private_data);
+        buf
+    }
+}
@@ -1,14 +1,20 @@
+use crate::{
+    error::DescriptorParseError,
+    mpeg::descriptors::{check_length, private_data_specifier},
+};
+
 pub const DESCRIPTOR_ID: u8 = 0x83;
 
-// According to docs, this is "user-defined"... Where are LCN descriptors "officially" defined ???
+// This is a private descriptor, defined by EACEM (IEC 62216) and reused by NorDig and UK DTT.
+// Other private data specifiers may use the same tag for something else.
 
 // w_scan2
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LogicalChannel {
     pub elements: Vec<LogicalChannelDescriptorElement>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LogicalChannelDescriptorElement {
     pub service_id: u16,
     pub visible_service: bool,
@@ -16,12 +22,27 @@ pub struct LogicalChannelDescriptorElement {
 }
 
 impl LogicalChannel {
-    pub fn from_buf(buf: &[u8]) -> LogicalChannel {
+    /// Return `true` if descriptors with this tag are logical channels under the given private data specifier.
+    ///
+    /// Many networks don't send any specifier at all, in which case the EACEM layout is assumed.
+    pub fn is_defined_by(private_data_specifier: Option<u32>) -> bool {
+        matches!(
+            private_data_specifier,
+            None | Some(
+                private_data_specifier::EACEM
+                    | private_data_specifier::NORDIG
+                    | private_data_specifier::UK_DTT
+            )
+        )
+    }
+
+    pub fn from_buf(buf: &[u8]) -> Result<LogicalChannel, DescriptorParseError> {
         let mut elements = Vec::new();
 
         let mut offset = 0;
 
         while offset < buf.len() {
+            check_length(buf, offset + 4)?;
             let service_id = u16::from_be_bytes([buf[offset], buf[offset + 1]]);
             let visible_service = (buf[offset + 2] & 0b1000_0000) != 0;
             let logical_channel_number =
@@ -36,6 +57,19 @@ impl LogicalChannel {
             });
         }
 
-        LogicalChannel { elements }
+        Ok(LogicalChannel { elements })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.elements.len() * 4);
+        for element in &self.elements {
+            let number = element.logical_channel_number.to_be_bytes();
+            buf.extend_from_slice(&element.service_id.to_be_bytes());
+            buf.push(
+                ((element.visible_service as u8) << 7) | 0b0111_1100 | (number[0] & 0b0000_0011),
+            );
+            buf.push(number[1]);
+        }
+        buf
     }
 }
@@ -1,51 +1,111 @@
-use crate::mpeg::descriptors::{
-    ac3::Ac3, application_signalling::ApplicationSignalling,
-    carousel_identifier::CarouselIdentifier, component::Component,
-    data_broadcast_id::DataBroadcastId, enhanced_ac3::EnhancedAc3, extension::Extension,
-    iso639_language::Iso639Language, logical_channel::LogicalChannel, network_name::NetworkName,
-    private_data_specifier::PrivateDataSpecifier, service::Service, service_list::ServiceList,
-    stream_identifier::StreamIdentifier, subtitling::Subtitling,
-    terrestrial_delivery_system::TerrestrialDeliverySystem,
+use crate::{
+    error::DescriptorParseError,
+    mpeg::descriptors::{
+        ac3::Ac3, application_signalling::ApplicationSignalling, bouquet_name::BouquetName,
+        cable_delivery_system::CableDeliverySystem, carousel_identifier::CarouselIdentifier,
+        component::Component, data_broadcast::DataBroadcast, data_broadcast_id::DataBroadcastId,
+        enhanced_ac3::EnhancedAc3, extended_event::ExtendedEvent, extension::Extension,
+        hd_simulcast_logical_channel::HdSimulcastLogicalChannel, iso639_language::Iso639Language,
+        linkage::Linkage, logical_channel::LogicalChannel,
+        multilingual_service_name::MultilingualServiceName, network_name::NetworkName,
+        private_data_specifier::PrivateDataSpecifier, registration::Registration,
+        satellite_delivery_system::SatelliteDeliverySystem, service::Service,
+        service_list::ServiceList, stream_identifier::StreamIdentifier, subtitling::Subtitling,
+        t2_delivery_system::T2DeliverySystem, teletext::Teletext,
+        terrestrial_delivery_system::TerrestrialDeliverySystem,
+    },
 };
 
 pub mod ac3;
 pub mod application_signalling;
+pub mod bouquet_name;
+pub mod cable_delivery_system;
 pub mod carousel_identifier;
 pub mod component;
+pub mod data_broadcast;
 pub mod data_broadcast_id;
 pub mod enhanced_ac3;
+pub mod extended_event;
 pub mod extension;
+pub mod hd_simulcast_logical_channel;
 pub mod iso639_language;
+pub mod linkage;
 pub mod logical_channel;
+pub mod multilingual_service_name;
 pub mod network_name;
 pub mod private_data_specifier;
+pub mod registration;
+pub mod satellite_delivery_system;
 pub mod service;
 pub mod service_list;
 pub mod stream_identifier;
 pub mod subtitling;
+pub mod t2_delivery_system;
+pub mod teletext;
 pub mod terrestrial_delivery_system;
 
 // -----
 
+/// Make sure a descriptor holds at least `length` bytes before reading them.
+fn check_length(buf: &[u8], length: usize) -> Result<(), DescriptorParseError> {
+    if buf.len() < length {
+        return Err(DescriptorParseError::TooShort);
+    }
+    Ok(())
+}
+
+/// Read `digits` Binary Coded Decimal digits, starting from the high nibble of the first byte.
+fn read_bcd(buf: &[u8], digits: usize) -> u32 {
+    (0..digits).fold(0, |value, i| {
+        let byte = buf[i / 2];
+        let digit = if i % 2 == 0 { byte >> 4 } else { byte & 0x0F };
+        value * 10 + digit as u32
+    })
+}
+
+/// Write `value` as `digits` Binary Coded Decimal digits, starting from the high nibble of the first byte.
+///
+/// With an odd amount of digits, the low nibble of the last byte is left to 0.
+fn write_bcd(value: u32, digits: usize) -> Vec<u8> {
+    let mut buf = vec![0; digits.div_ceil(2)];
+    let mut value = value;
+    for i in (0..digits).rev() {
+        let digit = (value % 10) as u8;
+        value /= 10;
+        buf[i / 2] |= if i % 2 == 0 { digit << 4 } else { digit };
+    }
+    buf
+}
+
 // Also look in vdr si.h DescriptorTag enum
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Descriptor {
     NetworkName(NetworkName),
     ServiceList(ServiceList),
+    BouquetName(BouquetName),
     Service(Service),
+    MultilingualServiceName(MultilingualServiceName),
     StreamIdentifier(StreamIdentifier),
+    SatelliteDeliverySystem(SatelliteDeliverySystem),
+    CableDeliverySystem(CableDeliverySystem),
     TerrestrialDeliverySystem(TerrestrialDeliverySystem),
     LogicalChannel(LogicalChannel),
+    HdSimulcastLogicalChannel(HdSimulcastLogicalChannel),
     EnhancedAc3(EnhancedAc3),
     PrivateDataSpecifier(PrivateDataSpecifier),
     DataBroadcastId(DataBroadcastId),
+    DataBroadcast(DataBroadcast),
     Extension(Extension),
+    Teletext(Teletext),
     Subtitling(Subtitling),
+    ExtendedEvent(ExtendedEvent),
+    Linkage(Linkage),
     Component(Component),
     Iso639Language(Iso639Language),
     ApplicationSignalling(ApplicationSignalling),
     Ac3(Ac3),
     CarouselIdentifier(CarouselIdentifier),
+    Registration(Registration),
     _Unknown(UnknownDescriptor),
 }
 
@@ -56,97 +116,837 @@ pub enum Descriptor {
 //     pub service_type: ServiceType,
 // }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UnknownDescriptor {
     pub descriptor_id: u8,
     pub raw_data: Vec<u8>,
 }
 
 impl Descriptor {
-    pub fn read(descriptor_id: u8, buf: &[u8]) -> Descriptor {
+    /// Read a single descriptor, as if no private data specifier preceded it.
+    pub fn read(descriptor_id: u8, buf: &[u8]) -> Result<Descriptor, DescriptorParseError> {
+        Self::read_private(descriptor_id, buf, None)
+    }
+
+    /// Read a single descriptor, in the context of the last [PrivateDataSpecifier] of its loop.
+    ///
+    /// Private descriptors (tags 0x80 to 0xFE) only have a meaning for some specifiers, and are kept as [`_Unknown`](Descriptor::_Unknown) for others.
+    pub fn read_private(
+        descriptor_id: u8,
+        buf: &[u8],
+        private_data_specifier: Option<u32>,
+    ) -> Result<Descriptor, DescriptorParseError> {
         // TODO: Could write macro
-        match descriptor_id {
-            // 0x05 => {} // In TS 102 809, but does not correspond to the data I'm getting
+        Ok(match descriptor_id {
+            // Also defined in TS 102 809 with another meaning, but the data I'm getting matches ISO/IEC 13818-1
+            registration::DESCRIPTOR_ID => Descriptor::Registration(Registration::from_buf(buf)?),
             iso639_language::DESCRIPTOR_ID => {
-                Descriptor::Iso639Language(Iso639Language::from_buf(buf))
+                Descriptor::Iso639Language(Iso639Language::from_buf(buf)?)
             }
             // 0x09 => {} // In TS 102 809, but does not correspond to the data I'm getting
             // 0x0E => {}, // Seen on a DVB-T2 HEVC channel
             carousel_identifier::DESCRIPTOR_ID => {
-                Descriptor::CarouselIdentifier(CarouselIdentifier::from_buf(buf))
+                Descriptor::CarouselIdentifier(CarouselIdentifier::from_buf(buf)?)
             }
             // 0x38 => {}, // Seen on a DVB-T2 HEVC channel
-            network_name::DESCRIPTOR_ID => Descriptor::NetworkName(NetworkName::from_buf(buf)),
-            service_list::DESCRIPTOR_ID => Descriptor::ServiceList(ServiceList::from_buf(buf)),
-            service::DESCRIPTOR_ID => Descriptor::Service(Service::from_buf(buf)),
+            network_name::DESCRIPTOR_ID => Descriptor::NetworkName(NetworkName::from_buf(buf)?),
+            service_list::DESCRIPTOR_ID => Descriptor::ServiceList(ServiceList::from_buf(buf)?),
+            bouquet_name::DESCRIPTOR_ID => Descriptor::BouquetName(BouquetName::from_buf(buf)?),
+            service::DESCRIPTOR_ID => Descriptor::Service(Service::from_buf(buf)?),
+            multilingual_service_name::DESCRIPTOR_ID => {
+                Descriptor::MultilingualServiceName(MultilingualServiceName::from_buf(buf)?)
+            }
             stream_identifier::DESCRIPTOR_ID => {
-                Descriptor::StreamIdentifier(StreamIdentifier::from_buf(buf))
+                Descriptor::StreamIdentifier(StreamIdentifier::from_buf(buf)?)
+            }
+            satellite_delivery_system::DESCRIPTOR_ID => {
+                Descriptor::SatelliteDeliverySystem(SatelliteDeliverySystem::from_buf(buf)?)
             }
-            component::DESCRIPTOR_ID => Descriptor::Component(Component::from_buf(buf)),
+            cable_delivery_system::DESCRIPTOR_ID => {
+                Descriptor::CableDeliverySystem(CableDeliverySystem::from_buf(buf)?)
+            }
+            linkage::DESCRIPTOR_ID => Descriptor::Linkage(Linkage::from_buf(buf)?),
+            component::DESCRIPTOR_ID => Descriptor::Component(Component::from_buf(buf)?),
             terrestrial_delivery_system::DESCRIPTOR_ID => {
-                Descriptor::TerrestrialDeliverySystem(TerrestrialDeliverySystem::from_buf(buf))
+                Descriptor::TerrestrialDeliverySystem(TerrestrialDeliverySystem::from_buf(buf)?)
+            }
+            extended_event::DESCRIPTOR_ID => {
+                Descriptor::ExtendedEvent(ExtendedEvent::from_buf(buf)?)
             }
-            subtitling::DESCRIPTOR_ID => Descriptor::Subtitling(Subtitling::from_buf(buf)),
+            teletext::DESCRIPTOR_ID => Descriptor::Teletext(Teletext::from_buf(buf)?),
+            subtitling::DESCRIPTOR_ID => Descriptor::Subtitling(Subtitling::from_buf(buf)?),
             private_data_specifier::DESCRIPTOR_ID => {
-                Descriptor::PrivateDataSpecifier(PrivateDataSpecifier::from_buf(buf))
+                Descriptor::PrivateDataSpecifier(PrivateDataSpecifier::from_buf(buf)?)
+            }
+            data_broadcast::DESCRIPTOR_ID => {
+                Descriptor::DataBroadcast(DataBroadcast::from_buf(buf)?)
             }
             data_broadcast_id::DESCRIPTOR_ID => {
-                Descriptor::DataBroadcastId(DataBroadcastId::from_buf(buf))
+                Descriptor::DataBroadcastId(DataBroadcastId::from_buf(buf)?)
             }
-            ac3::DESCRIPTOR_ID => Descriptor::Ac3(Ac3::from_buf(buf)),
+            ac3::DESCRIPTOR_ID => Descriptor::Ac3(Ac3::from_buf(buf)?),
             application_signalling::DESCRIPTOR_ID => {
-                Descriptor::ApplicationSignalling(ApplicationSignalling::from_buf(buf))
+                Descriptor::ApplicationSignalling(ApplicationSignalling::from_buf(buf)?)
+            }
+            enhanced_ac3::DESCRIPTOR_ID => Descriptor::EnhancedAc3(EnhancedAc3::from_buf(buf)?),
+            extension::DESCRIPTOR_ID => Descriptor::Extension(Extension::from_buf(buf)?),
+            logical_channel::DESCRIPTOR_ID
+                if LogicalChannel::is_defined_by(private_data_specifier) =>
+            {
+                Descriptor::LogicalChannel(LogicalChannel::from_buf(buf)?)
             }
-            enhanced_ac3::DESCRIPTOR_ID => Descriptor::EnhancedAc3(EnhancedAc3::from_buf(buf)),
-            extension::DESCRIPTOR_ID => Descriptor::Extension(Extension::from_buf(buf)),
-            // According to docs, this is "user-defined"... Where are LCN descriptors "officially" defined ???
-            logical_channel::DESCRIPTOR_ID => {
-                Descriptor::LogicalChannel(LogicalChannel::from_buf(buf))
+            hd_simulcast_logical_channel::DESCRIPTOR_ID
+                if LogicalChannel::is_defined_by(private_data_specifier) =>
+            {
+                Descriptor::HdSimulcastLogicalChannel(HdSimulcastLogicalChannel::from_buf(buf)?)
             }
             _ => Descriptor::_Unknown(UnknownDescriptor {
                 descriptor_id,
                 raw_data: buf.to_vec(),
             }),
-        }
+        })
     }
 
+    /// Read a whole loop of descriptors.
+    ///
+    /// A descriptor that fails to parse is kept as [`_Unknown`](Descriptor::_Unknown) with its raw data.
+    /// If the loop is truncated, descriptors after the truncation are ignored.
+    /// Private descriptors are read according to the last [PrivateDataSpecifier] before them, see [read_private](Self::read_private).
     pub fn read_many(buf: &[u8]) -> Vec<Descriptor> {
         let mut descriptors = Vec::new();
+        let mut private_data_specifier = None;
 
         let mut offset = 0;
-        while offset < buf.len() {
+        while offset + 2 <= buf.len() {
             let descriptor_id = buf[offset];
-            let length = buf[offset + 1];
+            let length = buf[offset + 1] as usize;
             offset += 2;
 
-            let data = &buf[offset..offset + length as usize];
-            descriptors.push(Descriptor::read(descriptor_id, data));
-            offset += length as usize;
+            if offset + length > buf.len() {
+                break;
+            }
+
+            let data = &buf[offset..offset + length];
+            let descriptor = Descriptor::read_private(descriptor_id, data, private_data_specifier)
+                .unwrap_or_else(|_| {
+                    Descriptor::_Unknown(UnknownDescriptor {
+                        descriptor_id,
+                        raw_data: data.to_vec(),
+                    })
+                });
+            if let Descriptor::PrivateDataSpecifier(d) = &descriptor {
+                private_data_specifier = Some(d.specifier);
+            }
+            descriptors.push(descriptor);
+            offset += length;
         }
 
         descriptors
     }
 
+    /// Write this descriptor back to bytes, including its tag and length.
+    ///
+    /// Panics if the body does not fit in a descriptor.
+    pub fn to_buf(&self) -> Vec<u8> {
+        let body = match self {
+            Descriptor::NetworkName(d) => d.to_buf(),
+            Descriptor::ServiceList(d) => d.to_buf(),
+            Descriptor::BouquetName(d) => d.to_buf(),
+            Descriptor::Service(d) => d.to_buf(),
+            Descriptor::MultilingualServiceName(d) => d.to_buf(),
+            Descriptor::StreamIdentifier(d) => d.to_buf(),
+            Descriptor::SatelliteDeliverySystem(d) => d.to_buf(),
+            Descriptor::CableDeliverySystem(d) => d.to_buf(),
+            Descriptor::TerrestrialDeliverySystem(d) => d.to_buf(),
+            Descriptor::LogicalChannel(d) => d.to_buf(),
+            Descriptor::HdSimulcastLogicalChannel(d) => d.to_buf(),
+            Descriptor::EnhancedAc3(d) => d.to_buf(),
+            Descriptor::PrivateDataSpecifier(d) => d.to_buf(),
+            Descriptor::DataBroadcastId(d) => d.to_buf(),
+            Descriptor::DataBroadcast(d) => d.to_buf(),
+            Descriptor::Extension(d) => d.to_buf(),
+            Descriptor::Teletext(d) => d.to_buf(),
+            Descriptor::Subtitling(d) => d.to_buf(),
+            Descriptor::ExtendedEvent(d) => d.to_buf(),
+            Descriptor::Linkage(d) => d.to_buf(),
+            Descriptor::Component(d) => d.to_buf(),
+            Descriptor::Iso639Language(d) => d.to_buf(),
+            Descriptor::ApplicationSignalling(d) => d.to_buf(),
+            Descriptor::Ac3(d) => d.to_buf(),
+            Descriptor::CarouselIdentifier(d) => d.to_buf(),
+            Descriptor::Registration(d) => d.to_buf(),
+            Descriptor::_Unknown(u) => u.raw_data.clone(),
+        };
+        let length = u8::try_from(body.len()).expect("descriptor body is too long");
+
+        let mut buf = Vec::with_capacity(2 + body.len());
+        buf.push(self.descriptor_id());
+        buf.push(length);
+        buf.extend(body);
+        buf
+    }
+
+    /// Write a whole loop of descriptors, the opposite of [read_many](Self::read_many).
+    pub fn write_many(descriptors: &[Descriptor]) -> Vec<u8> {
+        descriptors.iter().flat_map(|d| d.to_buf()).collect()
+    }
+
     pub const fn descriptor_id(&self) -> u8 {
         // TODO: Macro
         match self {
+            Descriptor::Registration(_) => registration::DESCRIPTOR_ID,
             Descriptor::Iso639Language(_) => iso639_language::DESCRIPTOR_ID,
             Descriptor::CarouselIdentifier(_) => carousel_identifier::DESCRIPTOR_ID,
             Descriptor::NetworkName(_) => network_name::DESCRIPTOR_ID,
             Descriptor::ServiceList(_) => service_list::DESCRIPTOR_ID,
+            Descriptor::BouquetName(_) => bouquet_name::DESCRIPTOR_ID,
             Descriptor::Service(_) => service::DESCRIPTOR_ID,
+            Descriptor::MultilingualServiceName(_) => multilingual_service_name::DESCRIPTOR_ID,
+            Descriptor::Linkage(_) => linkage::DESCRIPTOR_ID,
             Descriptor::Component(_) => component::DESCRIPTOR_ID,
             Descriptor::StreamIdentifier(_) => stream_identifier::DESCRIPTOR_ID,
+            Descriptor::Teletext(_) => teletext::DESCRIPTOR_ID,
             Descriptor::Subtitling(_) => subtitling::DESCRIPTOR_ID,
+            Descriptor::ExtendedEvent(_) => extended_event::DESCRIPTOR_ID,
+            Descriptor::SatelliteDeliverySystem(_) => satellite_delivery_system::DESCRIPTOR_ID,
+            Descriptor::CableDeliverySystem(_) => cable_delivery_system::DESCRIPTOR_ID,
             Descriptor::TerrestrialDeliverySystem(_) => terrestrial_delivery_system::DESCRIPTOR_ID,
             Descriptor::PrivateDataSpecifier(_) => private_data_specifier::DESCRIPTOR_ID,
+            Descriptor::DataBroadcast(_) => data_broadcast::DESCRIPTOR_ID,
             Descriptor::DataBroadcastId(_) => data_broadcast_id::DESCRIPTOR_ID,
             Descriptor::Ac3(_) => ac3::DESCRIPTOR_ID,
             Descriptor::ApplicationSignalling(_) => application_signalling::DESCRIPTOR_ID,
             Descriptor::EnhancedAc3(_) => enhanced_ac3::DESCRIPTOR_ID,
             Descriptor::Extension(_) => extension::DESCRIPTOR_ID,
-            Descriptor::LogicalChannel(_) => extension::DESCRIPTOR_ID,
+            Descriptor::LogicalChannel(_) => logical_channel::DESCRIPTOR_ID,
+            Descriptor::HdSimulcastLogicalChannel(_) => hd_simulcast_logical_channel::DESCRIPTOR_ID,
             Descriptor::_Unknown(u) => u.descriptor_id,
         }
     }
 }
+
+//
+// ----- Lookup by type
+
+/// Implemented by the body of every known descriptor, to look for it in a loop without matching on [Descriptor].
+pub trait DescriptorBody: Sized + 'static {
+    fn from_descriptor(descriptor: &Descriptor) -> Option<&Self>;
+}
+
+macro_rules! impl_descriptor_body {
+    ($($variant:ident),* $(,)?) => {
+        $(
+            impl DescriptorBody for $variant {
+                fn from_descriptor(descriptor: &Descriptor) -> Option<&Self> {
+                    match descriptor {
+                        Descriptor::$variant(d) => Some(d),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_descriptor_body!(
+    NetworkName,
+    ServiceList,
+    BouquetName,
+    Service,
+    MultilingualServiceName,
+    StreamIdentifier,
+    SatelliteDeliverySystem,
+    CableDeliverySystem,
+    TerrestrialDeliverySystem,
+    LogicalChannel,
+    HdSimulcastLogicalChannel,
+    EnhancedAc3,
+    PrivateDataSpecifier,
+    DataBroadcastId,
+    DataBroadcast,
+    Extension,
+    Teletext,
+    Subtitling,
+    ExtendedEvent,
+    Linkage,
+    Component,
+    Iso639Language,
+    ApplicationSignalling,
+    Ac3,
+    CarouselIdentifier,
+    Registration,
+);
+
+impl DescriptorBody for T2DeliverySystem {
+    fn from_descriptor(descriptor: &Descriptor) -> Option<&Self> {
+        match descriptor {
+            Descriptor::Extension(Extension::T2DeliverySystem(d)) => Some(d),
+            _ => None,
+        }
+    }
+}
+
+/// Typed lookups in a loop of descriptors, like `service.descriptors.find::<Service>()`.
+pub trait DescriptorLoop {
+    /// Return the first descriptor of type `T`.
+    fn find<T: DescriptorBody>(&self) -> Option<&T>;
+    /// Iterate over all descriptors of type `T`, in order.
+    fn filter<T: DescriptorBody>(&self) -> impl Iterator<Item = &T>;
+    /// Return the private data specifier in effect for the descriptor at `index`, which is the last one before it.
+    fn private_data_specifier_at(&self, index: usize) -> Option<u32>;
+}
+
+impl DescriptorLoop for [Descriptor] {
+    fn find<T: DescriptorBody>(&self) -> Option<&T> {
+        self.iter().find_map(T::from_descriptor)
+    }
+
+    fn filter<T: DescriptorBody>(&self) -> impl Iterator<Item = &T> {
+        self.iter().filter_map(T::from_descriptor)
+    }
+
+    fn private_data_specifier_at(&self, index: usize) -> Option<u32> {
+        self[..index.min(self.len())]
+            .filter::<PrivateDataSpecifier>()
+            .last()
+            .map(|d| d.specifier)
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        conf::vdr::parameters::Polarization,
+        mpeg::{
+            LanguageCode, ServiceType,
+            descriptors::{
+                application_signalling::ApplicationSignallingElement,
+                carousel_identifier::Identifier,
+                enhanced_ac3::{
+                    EnhancedAc3ChannelSetup, EnhancedAc3ComponentType, EnhancedAc3ServiceType,
+                },
+                extended_event::ExtendedEventItem,
+                iso639_language::Iso639LanguageElement,
+                linkage::LinkageType,
+                logical_channel::LogicalChannelDescriptorElement,
+                multilingual_service_name::MultilingualServiceNameElement,
+                service_list::ServiceListDescriptorElement,
+                subtitling::SubtitlingElement,
+                teletext::TeletextElement,
+            },
+        },
+    };
+    use rdvb_os_linux::frontend::data::{
+        FeCodeRate, FeGuardInterval, FeHierarchy, FeModulation, FeTransmitMode,
+    };
+
+    /// One of each descriptor, with values that use all of their fields
+    fn samples() -> Vec<Descriptor> {
+        vec![
+            Descriptor::NetworkName(NetworkName {
+                name: b"Network".to_vec(),
+            }),
+            Descriptor::ServiceList(ServiceList {
+                services: vec![ServiceListDescriptorElement {
+                    service_id: 0x0101,
+                    service_type: ServiceType::H264HdDigitalTelevision,
+                }],
+            }),
+            Descriptor::BouquetName(BouquetName {
+                name: "Bouquet".to_string(),
+            }),
+            Descriptor::Service(Service {
+                service_type: ServiceType::DigitalTelevision,
+                provider: "Provider".to_string(),
+                service: "Service".to_string(),
+            }),
+            Descriptor::MultilingualServiceName(MultilingualServiceName {
+                names: vec![
+                    MultilingualServiceNameElement {
+                        language_code: LanguageCode(*b"fra"),
+                        provider: "Fournisseur".to_string(),
+                        service: "Service".to_string(),
+                    },
+                    MultilingualServiceNameElement {
+                        language_code: LanguageCode(*b"deu"),
+                        provider: "Anbieter".to_string(),
+                        service: "Dienst".to_string(),
+                    },
+                ],
+            }),
+            Descriptor::StreamIdentifier(StreamIdentifier { component_tag: 7 }),
+            Descriptor::SatelliteDeliverySystem(SatelliteDeliverySystem {
+                frequency: 1_136_200,
+                orbital_position: 192,
+                west_east_flag: true,
+                polarization: Polarization::Vertical,
+                roll_off: 1,
+                modulation_system: true,
+                modulation_type: 2,
+                symbol_rate: 220_000,
+                fec_inner: 3,
+            }),
+            Descriptor::CableDeliverySystem(CableDeliverySystem {
+                frequency: 3_460_000,
+                fec_outer: 2,
+                modulation: 3,
+                symbol_rate: 69_000,
+                fec_inner: 0xF,
+            }),
+            Descriptor::TerrestrialDeliverySystem(TerrestrialDeliverySystem {
+                center_frequency: 47_416_600,
+                bandwidth: 1,
+                priority: true,
+                time_slicing_indicator: false,
+                mpe_fec_indicator: true,
+                constellation: 2,
+                hierarchy_information: 5,
+                code_rate_hp_stream: 3,
+                code_rate_lp_stream: 4,
+                guard_interval: 2,
+                transmission_mode: 1,
+                other_frequency_flag: true,
+            }),
+            Descriptor::LogicalChannel(LogicalChannel {
+                elements: vec![LogicalChannelDescriptorElement {
+                    service_id: 0x0101,
+                    visible_service: true,
+                    logical_channel_number: 1000,
+                }],
+            }),
+            Descriptor::HdSimulcastLogicalChannel(HdSimulcastLogicalChannel {
+                elements: vec![LogicalChannelDescriptorElement {
+                    service_id: 0x0102,
+                    visible_service: false,
+                    logical_channel_number: 2,
+                }],
+            }),
+            Descriptor::EnhancedAc3(EnhancedAc3 {
+                mixinfoexists: true,
+                component_type: Some(EnhancedAc3ComponentType {
+                    enhanced: true,
+                    full_service: true,
+                    service_type: EnhancedAc3ServiceType::CompleteMain,
+                    channel_setup: EnhancedAc3ChannelSetup::MultichannelOver5Dot1,
+                }),
+                bsid: Some(16),
+                mainid: None,
+                asvc: Some(1),
+                substream1: None,
+                substream2: Some(2),
+                substream3: None,
+                additional_info: vec![0xAA],
+            }),
+            Descriptor::PrivateDataSpecifier(PrivateDataSpecifier {
+                specifier: 0x0000_233A,
+            }),
+            Descriptor::DataBroadcastId(DataBroadcastId {
+                data_broadcast_id: 0x0106,
+                selector_bytes: vec![1, 2],
+            }),
+            Descriptor::DataBroadcast(DataBroadcast {
+                data_broadcast_id: 0x0123,
+                component_tag: 0x11,
+                selector_bytes: vec![5, 6, 7],
+                language_code: LanguageCode(*b"fra"),
+                text: "Interactive".to_string(),
+            }),
+            Descriptor::Extension(Extension::Other {
+                tag_extension: 0x15,
+                selector_bytes: vec![3, 4],
+            }),
+            Descriptor::Subtitling(Subtitling {
+                elements: vec![SubtitlingElement {
+                    language_code: LanguageCode(*b"fra"),
+                    subtitling_type: 0x10,
+                    composition_page_id: 1,
+                    ancillary_page_id: 2,
+                }],
+            }),
+            Descriptor::Teletext(Teletext {
+                elements: vec![TeletextElement {
+                    language_code: LanguageCode(*b"fra"),
+                    teletext_type: 0x02,
+                    magazine: 0x01,
+                    page: 0x88,
+                }],
+            }),
+            Descriptor::ExtendedEvent(ExtendedEvent {
+                descriptor_number: 1,
+                last_descriptor_number: 2,
+                language_code: LanguageCode(*b"eng"),
+                items: vec![ExtendedEventItem {
+                    description: b"Director".to_vec(),
+                    item: b"Someone".to_vec(),
+                }],
+                text: b"A long story".to_vec(),
+            }),
+            Descriptor::Linkage(Linkage {
+                transport_stream_id: 0x0001,
+                original_network_id: 0x20FA,
+                service_id: 0x0101,
+                linkage_type: LinkageType::EventLinkage,
+                private_data: vec![0x12, 0x34, 0x80],
+            }),
+            Descriptor::Component(Component {
+                stream_content_ext: 0xF0,
+                stream_content: 0x02,
+                component_type: 0x03,
+                component_tag: 0x10,
+                language_code: LanguageCode(*b"eng"),
+                chars: b"Stereo".to_vec(),
+            }),
+            Descriptor::Iso639Language(Iso639Language {
+                languages: vec![
+                    Iso639LanguageElement {
+                        language: LanguageCode(*b"fra"),
+                        audio_type: 0x01,
+                    },
+                    Iso639LanguageElement {
+                        language: LanguageCode(*b"eng"),
+                        audio_type: 0x00,
+                    },
+                ],
+            }),
+            Descriptor::ApplicationSignalling(ApplicationSignalling {
+                elements: vec![ApplicationSignallingElement {
+                    application_type: 0x0010,
+                    ait_version_number: 3,
+                }],
+            }),
+            Descriptor::Ac3(Ac3 {
+                component_type: Some(0x42),
+                bsid: None,
+                mainid: Some(1),
+                asvc: None,
+                additional_info_byte: vec![9],
+            }),
+            Descriptor::CarouselIdentifier(CarouselIdentifier {
+                carousel_id: 0x0102_0304,
+                identifier: Identifier::Enhanced {
+                    module_version: 1,
+                    module_id: 2,
+                    block_size: 3,
+                    module_size: 4,
+                    compression_method: 5,
+                    original_size: 6,
+                    time_out: 7,
+                    object_key_length: 2,
+                    object_key_data: vec![8, 9],
+                    private_data_byte: vec![10],
+                },
+            }),
+            Descriptor::Registration(Registration {
+                format_identifier: *b"AC-3",
+                additional_identification_info: vec![0x01],
+            }),
+            Descriptor::_Unknown(UnknownDescriptor {
+                descriptor_id: 0xF0,
+                raw_data: vec![1, 2, 3],
+            }),
+        ]
+    }
+
+    #[test]
+    fn private_descriptors() {
+        let lcn = [0x83, 0x04, 0x01, 0x01, 0xFC, 0x01];
+        let mut buf = vec![0x5F, 0x04, 0x00, 0x00, 0x00, 0x28]; // EACEM
+        buf.extend(lcn);
+        buf.extend([0x5F, 0x04, 0x00, 0x00, 0x12, 0x34]); // Someone else
+        buf.extend(lcn);
+
+        let descriptors = Descriptor::read_many(&buf);
+        assert!(matches!(descriptors[1], Descriptor::LogicalChannel(_)));
+        assert!(matches!(descriptors[3], Descriptor::_Unknown(_)));
+        assert_eq!(descriptors.private_data_specifier_at(0), None);
+        assert_eq!(descriptors.private_data_specifier_at(1), Some(0x28));
+        assert_eq!(descriptors.private_data_specifier_at(3), Some(0x1234));
+
+        // Without any specifier, the usual layout is assumed
+        assert!(matches!(
+            Descriptor::read_many(&lcn)[0],
+            Descriptor::LogicalChannel(_)
+        ));
+    }
+
+    #[test]
+    fn malformed_descriptors() {
+        let buf = [
+            // Truncated private data specifier body
+            0x5F, 0x02, 0x00, 0x00, //
+            // Valid stream identifier
+            0x52, 0x01, 0x07, //
+            // Length going past the end of the loop
+            0x48, 0x10, 0x01,
+        ];
+
+        let descriptors = Descriptor::read_many(&buf);
+
+        assert_eq!(descriptors.len(), 2);
+        assert!(matches!(
+            &descriptors[0],
+            Descriptor::_Unknown(UnknownDescriptor {
+                descriptor_id: 0x5F,
+                ..
+            })
+        ));
+        assert!(matches!(
+            &descriptors[1],
+            Descriptor::StreamIdentifier(StreamIdentifier {
+                component_tag: 0x07
+            })
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        for descriptor in samples() {
+            let buf = descriptor.to_buf();
+            assert_eq!(buf[0], descriptor.descriptor_id());
+            assert_eq!(buf[1] as usize, buf.len() - 2);
+            assert_eq!(
+                Descriptor::read(buf[0], &buf[2..]).unwrap(),
+                descriptor,
+                "{:02X?}",
+                buf
+            );
+        }
+
+        let descriptors = samples();
+        assert_eq!(
+            Descriptor::read_many(&Descriptor::write_many(&descriptors)),
+            descriptors
+        );
+    }
+
+    #[test]
+    fn descriptor_ids() {
+        for descriptor in samples() {
+            let expected = match &descriptor {
+                Descriptor::NetworkName(_) => network_name::DESCRIPTOR_ID,
+                Descriptor::ServiceList(_) => service_list::DESCRIPTOR_ID,
+                Descriptor::BouquetName(_) => bouquet_name::DESCRIPTOR_ID,
+                Descriptor::Service(_) => service::DESCRIPTOR_ID,
+                Descriptor::MultilingualServiceName(_) => multilingual_service_name::DESCRIPTOR_ID,
+                Descriptor::StreamIdentifier(_) => stream_identifier::DESCRIPTOR_ID,
+                Descriptor::SatelliteDeliverySystem(_) => satellite_delivery_system::DESCRIPTOR_ID,
+                Descriptor::CableDeliverySystem(_) => cable_delivery_system::DESCRIPTOR_ID,
+                Descriptor::TerrestrialDeliverySystem(_) => {
+                    terrestrial_delivery_system::DESCRIPTOR_ID
+                }
+                Descriptor::LogicalChannel(_) => logical_channel::DESCRIPTOR_ID,
+                Descriptor::HdSimulcastLogicalChannel(_) => {
+                    hd_simulcast_logical_channel::DESCRIPTOR_ID
+                }
+                Descriptor::EnhancedAc3(_) => enhanced_ac3::DESCRIPTOR_ID,
+                Descriptor::PrivateDataSpecifier(_) => private_data_specifier::DESCRIPTOR_ID,
+                Descriptor::DataBroadcastId(_) => data_broadcast_id::DESCRIPTOR_ID,
+                Descriptor::DataBroadcast(_) => data_broadcast::DESCRIPTOR_ID,
+                Descriptor::Extension(_) => extension::DESCRIPTOR_ID,
+                Descriptor::Teletext(_) => teletext::DESCRIPTOR_ID,
+                Descriptor::Subtitling(_) => subtitling::DESCRIPTOR_ID,
+                Descriptor::ExtendedEvent(_) => extended_event::DESCRIPTOR_ID,
+                Descriptor::Linkage(_) => linkage::DESCRIPTOR_ID,
+                Descriptor::Component(_) => component::DESCRIPTOR_ID,
+                Descriptor::Iso639Language(_) => iso639_language::DESCRIPTOR_ID,
+                Descriptor::ApplicationSignalling(_) => application_signalling::DESCRIPTOR_ID,
+                Descriptor::Ac3(_) => ac3::DESCRIPTOR_ID,
+                Descriptor::CarouselIdentifier(_) => carousel_identifier::DESCRIPTOR_ID,
+                Descriptor::Registration(_) => registration::DESCRIPTOR_ID,
+                Descriptor::_Unknown(u) => u.descriptor_id,
+            };
+            assert_eq!(descriptor.descriptor_id(), expected, "{:?}", descriptor);
+        }
+
+        // Every variant has a sample, and no two share the same tag
+        let mut ids: Vec<u8> = samples().iter().map(|d| d.descriptor_id()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), samples().len());
+    }
+
+    #[test]
+    fn extended_event_fragments() {
+        let fragment = |number: u8, text: &[u8]| ExtendedEvent {
+            descriptor_number: number,
+            last_descriptor_number: 1,
+            language_code: LanguageCode(*b"fra"),
+            items: Vec::new(),
+            text: text.to_vec(),
+        };
+
+        // Out of order, with a character split across both fragments
+        let fragments = [fragment(1, b"\xA9t\xC3\xA9."), fragment(0, b"Un \xC3")];
+        assert_eq!(ExtendedEvent::merge_text(&fragments), "Un été.");
+
+        // Item loop going past its announced length
+        let buf = [0x00, b'f', b'r', b'a', 0x02, 0x05, b'a', 0x00];
+        assert!(ExtendedEvent::from_buf(&buf).is_err());
+    }
+
+    #[test]
+    fn lookup_by_type() {
+        let descriptors = samples();
+        assert_eq!(
+            descriptors.find::<StreamIdentifier>(),
+            Some(&StreamIdentifier { component_tag: 7 })
+        );
+        assert_eq!(descriptors.filter::<Service>().count(), 1);
+        assert!(descriptors[..2].find::<Ac3>().is_none());
+    }
+
+    #[test]
+    fn linkage_types() {
+        for byte in 0..=0xFF {
+            assert_eq!(LinkageType::from_byte(byte).to_byte(), byte);
+        }
+        assert_eq!(
+            LinkageType::from_byte(0x05),
+            LinkageType::ServiceReplacement
+        );
+        assert_eq!(
+            LinkageType::from_byte(0x10),
+            LinkageType::ExtendedEventLinkage(0x10)
+        );
+    }
+
+    #[test]
+    fn terrestrial_delivery_system() {
+        let buf = [
+            0x02, 0xD3, 0x85, 0x18, // Center frequency, 474.166MHz
+            0x1F, 0x82, 0x42, // 8MHz, 64-QAM, 3/4, 1/32, 8k
+            0xFF, 0xFF, 0xFF, 0xFF, // Reserved
+        ];
+
+        let descriptor = TerrestrialDeliverySystem::from_buf(&buf).unwrap();
+
+        assert_eq!(descriptor.center_frequency, 47_416_600);
+        assert_eq!(descriptor.center_frequency_hz(), 474_166_000);
+        assert_eq!(descriptor.frequency(), 474_166_000);
+        assert!(matches!(
+            descriptor.constellation(),
+            Some(FeModulation::QAM_64)
+        ));
+        assert!(matches!(
+            descriptor.code_rate_hp(),
+            Some(FeCodeRate::FEC_3_4)
+        ));
+        assert!(matches!(
+            descriptor.code_rate_lp(),
+            Some(FeCodeRate::FEC_3_4)
+        ));
+        assert!(matches!(
+            descriptor.guard_interval(),
+            FeGuardInterval::GUARD_INTERVAL_1_32
+        ));
+        assert!(matches!(
+            descriptor.transmission_mode(),
+            Some(FeTransmitMode::TRANSMISSION_MODE_8K)
+        ));
+        assert!(matches!(
+            descriptor.hierarchy(),
+            FeHierarchy::HIERARCHY_NONE
+        ));
+        assert_eq!(
+            descriptor.bandwidth_hz(),
+            Some(crate::frontend::properties::set::BandwidthHz::_8MHz)
+        );
+        for code in 0..4 {
+            let bandwidth = TerrestrialDeliverySystem {
+                bandwidth: code,
+                ..descriptor.clone()
+            }
+            .bandwidth_hz()
+            .unwrap();
+            assert_eq!(
+                TerrestrialDeliverySystem::bandwidth_code(bandwidth),
+                Some(code)
+            );
+        }
+        let reserved = TerrestrialDeliverySystem {
+            bandwidth: 4,
+            ..descriptor
+        };
+        assert!(crate::frontend::properties::set::BandwidthHz::try_from(&reserved).is_err());
+    }
+
+    #[test]
+    fn t2_delivery_system() {
+        let buf = [
+            0x04, // T2 delivery system
+            0x02, 0x80, 0x01, // PLP 2, T2 system 0x8001
+            0x03, 0x94, // SISO, 8MHz, 1/128, 32k, no TFS
+            0x00, 0x01, 0x02, 0xD3, 0x85, 0x18, 0x00, // Cell 1 at 474.166MHz, no subcell
+        ];
+
+        let descriptors = [Descriptor::read(0x7F, &buf).unwrap()];
+        let t2 = descriptors.find::<T2DeliverySystem>().unwrap();
+
+        assert_eq!(t2.plp_id, 2);
+        assert_eq!(t2.frequencies().collect::<Vec<_>>(), [474_166_000]);
+        assert_eq!(
+            t2.bandwidth_hz(),
+            Some(crate::frontend::properties::set::BandwidthHz::_8MHz)
+        );
+        assert!(matches!(
+            t2.guard_interval(),
+            Some(FeGuardInterval::GUARD_INTERVAL_1_128)
+        ));
+        assert!(matches!(
+            t2.transmission_mode(),
+            Some(FeTransmitMode::TRANSMISSION_MODE_32K)
+        ));
+
+        // Cell cut in the middle of its frequency
+        assert!(Descriptor::read(0x7F, &buf[..9]).is_err());
+    }
+
+    #[test]
+    fn satellite_delivery_system() {
+        let buf = [
+            0x01, 0x13, 0x62, 0x00, // 11.362GHz
+            0x01, 0x92, // 19.2°
+            0x86, // East, horizontal, DVB-S2 8PSK
+            0x02, 0x20, 0x00, 0x03, // 22Msym/s, FEC 2/3
+        ];
+
+        let descriptor = SatelliteDeliverySystem::from_buf(&buf).unwrap();
+
+        assert_eq!(descriptor.frequency_khz(), 11_362_000);
+        assert_eq!(descriptor.orbital_position, 192);
+        assert!(descriptor.west_east_flag);
+        assert_eq!(
+            descriptor.polarization,
+            crate::conf::vdr::parameters::Polarization::Horizontal
+        );
+        assert_eq!(
+            descriptor.delivery_system(),
+            crate::frontend::DeliverySystem::DvbS2
+        );
+        assert_eq!(descriptor.modulation_type, 0b10);
+        assert_eq!(descriptor.symbols_per_second(), 22_000_000);
+        assert_eq!(descriptor.fec_inner, 0x3);
+    }
+
+    #[test]
+    fn cable_delivery_system() {
+        let buf = [
+            0x03, 0x46, 0x00, 0x00, // 346MHz
+            0xFF, 0xF2, // FEC outer RS(204/188)
+            0x03, // 64-QAM
+            0x00, 0x69, 0x00, 0x0F, // 6.9Msym/s, no inner FEC
+        ];
+
+        let descriptor = CableDeliverySystem::from_buf(&buf).unwrap();
+
+        assert_eq!(descriptor.frequency_hz(), 346_000_000);
+        assert_eq!(descriptor.fec_outer, 0x2);
+        assert_eq!(descriptor.modulation, 0x03);
+        assert_eq!(descriptor.symbols_per_second(), 6_900_000);
+        assert_eq!(descriptor.fec_inner, 0xF);
+    }
+}
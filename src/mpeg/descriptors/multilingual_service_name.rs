@@ -0,0 +1,78 @@
+use crate::{
+    error::DescriptorParseError,
+    mpeg::{LanguageCode, decode_stupid_string, descriptors::check_length},
+};
+
+pub const DESCRIPTOR_ID: u8 = 0x5D;
+
+// ETSI EN 300 468 page 80
+/// Names of the provider and service in several languages, alongside the [Service](super::service::Service) descriptor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultilingualServiceName {
+    pub names: Vec<MultilingualServiceNameElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultilingualServiceNameElement {
+    // ISO 639
+    pub language_code: LanguageCode,
+    pub provider: String,
+    pub service: String,
+}
+
+impl MultilingualServiceName {
+    pub fn from_buf(buf: &[u8]) -> Result<MultilingualServiceName, DescriptorParseError> {
+        let mut names = Vec::new();
+
+        let mut offset = 0;
+        while offset < buf.len() {
+            // Language code and provider name length
+            check_length(buf, offset + 4)?;
+            let language_code = LanguageCode([buf[offset], buf[offset + 1], buf[offset + 2]]);
+            let provider_length = buf[offset + 3] as usize;
+            offset += 4;
+
+            // Provider name and service name length
+            check_length(buf, offset + provider_length + 1)?;
+            let raw_provider = &buf[offset..offset + provider_length];
+            offset += provider_length;
+            let service_length = buf[offset] as usize;
+            offset += 1;
+
+            check_length(buf, offset + service_length)?;
+            let raw_service = &buf[offset..offset + service_length];
+            offset += service_length;
+
+            names.push(MultilingualServiceNameElement {
+                language_code,
+                provider: decode_stupid_string(raw_provider)
+                    .ok_or(DescriptorParseError::UnexpectedValue)?,
+                service: decode_stupid_string(raw_service)
+                    .ok_or(DescriptorParseError::UnexpectedValue)?,
+            });
+        }
+
+        Ok(MultilingualServiceName { names })
+    }
+
+    /// Strings are written as-is, without a character table prefix.
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for name in &self.names {
+            buf.extend_from_slice(&name.language_code.0);
+            for text in [&name.provider, &name.service] {
+                buf.push(text.len() as u8);
+                buf.extend_from_slice(text.as_bytes());
+            }
+        }
+        buf
+    }
+
+    /// Return the name of the service in given ISO 639 language, if there is one.
+    pub fn service_name(&self, language_code: &LanguageCode) -> Option<&str> {
+        self.names
+            .iter()
+            .find(|n| n.language_code.matches(language_code))
+            .map(|n| n.service.as_str())
+    }
+}
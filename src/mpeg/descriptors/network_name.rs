@@ -1,13 +1,19 @@
+use crate::error::DescriptorParseError;
+
 pub const DESCRIPTOR_ID: u8 = 0x40;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NetworkName {
     // TODO: Should have a dedicated String type for this weird strings
     pub name: Vec<u8>,
 }
 
 impl NetworkName {
-    pub fn from_buf(buf: &[u8]) -> NetworkName {
-        NetworkName { name: buf.to_vec() }
+    pub fn from_buf(buf: &[u8]) -> Result<NetworkName, DescriptorParseError> {
+        Ok(NetworkName { name: buf.to_vec() })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        self.name.clone()
     }
 }
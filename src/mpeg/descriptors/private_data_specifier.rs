@@ -1,14 +1,30 @@
+use crate::{error::DescriptorParseError, mpeg::descriptors::check_length};
+
 pub const DESCRIPTOR_ID: u8 = 0x5F;
 
-#[derive(Debug, Clone)]
+// Registered in ETSI TS 101 162
+
+/// EACEM/EICTA, the origin of the logical channel descriptor
+pub const EACEM: u32 = 0x0000_0028;
+pub const NORDIG: u32 = 0x0000_0029;
+/// UK Digital Terrestrial (ITC/Ofcom)
+pub const UK_DTT: u32 = 0x0000_233A;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PrivateDataSpecifier {
     pub specifier: u32,
 }
 
 impl PrivateDataSpecifier {
-    pub fn from_buf(buf: &[u8]) -> PrivateDataSpecifier {
-        PrivateDataSpecifier {
+    pub fn from_buf(buf: &[u8]) -> Result<PrivateDataSpecifier, DescriptorParseError> {
+        check_length(buf, 4)?;
+
+        Ok(PrivateDataSpecifier {
             specifier: u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]),
-        }
+        })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        self.specifier.to_be_bytes().to_vec()
     }
 }
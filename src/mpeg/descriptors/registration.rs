@@ -0,0 +1,35 @@
+use crate::{error::DescriptorParseError, mpeg::descriptors::check_length};
+
+pub const DESCRIPTOR_ID: u8 = 0x05;
+
+/// Format identifier of AC-3 audio streams, as registered with SMPTE
+pub const AC3_FORMAT_IDENTIFIER: [u8; 4] = *b"AC-3";
+/// Format identifier of E-AC-3 audio streams, as registered with SMPTE
+pub const ENHANCED_AC3_FORMAT_IDENTIFIER: [u8; 4] = *b"EAC3";
+/// Format identifier of AC-4 audio streams, as registered with SMPTE
+pub const AC4_FORMAT_IDENTIFIER: [u8; 4] = *b"AC-4";
+
+// ISO/IEC 13818-1 page 75
+/// Identifies the format of private data with a 4 character code, like `AC-3`. Mostly used by ATSC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registration {
+    pub format_identifier: [u8; 4],
+    pub additional_identification_info: Vec<u8>,
+}
+
+impl Registration {
+    pub fn from_buf(buf: &[u8]) -> Result<Registration, DescriptorParseError> {
+        check_length(buf, 4)?;
+
+        Ok(Registration {
+            format_identifier: [buf[0], buf[1], buf[2], buf[3]],
+            additional_identification_info: buf[4..].to_vec(),
+        })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = self.format_identifier.to_vec();
+        buf.extend_from_slice(&self.additional_identification_info);
+        buf
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    conf::vdr::parameters::Polarization,
+    error::DescriptorParseError,
+    frontend::DeliverySystem,
+    mpeg::descriptors::{check_length, read_bcd, write_bcd},
+};
+
+pub const DESCRIPTOR_ID: u8 = 0x43;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SatelliteDeliverySystem {
+    /// In units of 10kHz, see [frequency_khz](Self::frequency_khz)
+    pub frequency: u32,
+    /// In tenths of a degree
+    pub orbital_position: u16,
+    /// East of Greenwich if `true`, West otherwise
+    pub west_east_flag: bool,
+    pub polarization: Polarization,
+    /// Only meaningful for DVB-S2
+    pub roll_off: u8,
+    /// DVB-S2 if `true`, DVB-S otherwise
+    pub modulation_system: bool,
+    pub modulation_type: u8,
+    /// In units of 100 symbols per second, see [symbols_per_second](Self::symbols_per_second)
+    pub symbol_rate: u32,
+    pub fec_inner: u8,
+}
+
+impl SatelliteDeliverySystem {
+    pub fn from_buf(buf: &[u8]) -> Result<SatelliteDeliverySystem, DescriptorParseError> {
+        check_length(buf, 11)?;
+
+        let frequency = read_bcd(&buf[0..4], 8);
+        let orbital_position = read_bcd(&buf[4..6], 4) as u16;
+        let west_east_flag = (buf[6] & 0b1000_0000) != 0;
+        let polarization = match (buf[6] & 0b0110_0000) >> 5 {
+            0b00 => Polarization::Horizontal,
+            0b01 => Polarization::Vertical,
+            0b10 => Polarization::CircularLeft,
+            _ => Polarization::CircularRight,
+        };
+        let roll_off = (buf[6] & 0b0001_1000) >> 3;
+        let modulation_system = (buf[6] & 0b0000_0100) != 0;
+        let modulation_type = buf[6] & 0b0000_0011;
+        let symbol_rate = read_bcd(&buf[7..11], 7);
+        let fec_inner = buf[10] & 0b0000_1111;
+
+        Ok(SatelliteDeliverySystem {
+            frequency,
+            orbital_position,
+            west_east_flag,
+            polarization,
+            roll_off,
+            modulation_system,
+            modulation_type,
+            symbol_rate,
+            fec_inner,
+        })
+    }
+
+    /// Downlink frequency in kHz, as used by [SatelliteTransponder](crate::bands::SatelliteTransponder)
+    pub fn frequency_khz(&self) -> u32 {
+        self.frequency.saturating_mul(10)
+    }
+
+    pub fn symbols_per_second(&self) -> u32 {
+        self.symbol_rate.saturating_mul(100)
+    }
+
+    pub fn delivery_system(&self) -> DeliverySystem {
+        if self.modulation_system {
+            DeliverySystem::DvbS2
+        } else {
+            DeliverySystem::DvbS
+        }
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = write_bcd(self.frequency, 8);
+        buf.extend(write_bcd(self.orbital_position as u32, 4));
+
+        let polarization = match self.polarization {
+            Polarization::Horizontal => 0b00,
+            Polarization::Vertical => 0b01,
+            Polarization::CircularLeft => 0b10,
+            Polarization::CircularRight => 0b11,
+        };
+        buf.push(
+            ((self.west_east_flag as u8) << 7)
+                | (polarization << 5)
+                | (self.roll_off << 3)
+                | ((self.modulation_system as u8) << 2)
+                | self.modulation_type,
+        );
+
+        buf.extend(write_bcd(self.symbol_rate, 7));
+        buf[10] |= self.fec_inner;
+        buf
+    }
+}
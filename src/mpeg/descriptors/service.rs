@@ -1,8 +1,11 @@
-use crate::mpeg::{ServiceType, decode_stupid_string};
+use crate::{
+    error::DescriptorParseError,
+    mpeg::{ServiceType, decode_stupid_string, descriptors::check_length},
+};
 
 pub const DESCRIPTOR_ID: u8 = 0x48;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Service {
     pub service_type: ServiceType,
     pub provider: String,
@@ -10,7 +13,9 @@ pub struct Service {
 }
 
 impl Service {
-    pub fn from_buf(buf: &[u8]) -> Service {
+    pub fn from_buf(buf: &[u8]) -> Result<Service, DescriptorParseError> {
+        check_length(buf, 2)?;
+
         // TODO: Enum for these types
         let service_type = ServiceType::from_byte(buf[0]);
 
@@ -19,23 +24,37 @@ impl Service {
         // Read provider string
         let provider_length = buf[pos];
         pos += 1;
+        check_length(buf, pos + provider_length as usize + 1)?;
         let raw_provider = &buf[pos..pos + provider_length as usize];
         pos += provider_length as usize;
 
         // Read service string
         let service_length = buf[pos];
         pos += 1;
+        check_length(buf, pos + service_length as usize)?;
         let raw_service = &buf[pos..pos + service_length as usize];
         // pos += service_length as usize;
 
         // TODO: Proper decoding (ETSI EN 300 468 page 135)
-        let provider = decode_stupid_string(raw_provider).unwrap();
-        let service = decode_stupid_string(raw_service).unwrap();
+        let provider =
+            decode_stupid_string(raw_provider).ok_or(DescriptorParseError::UnexpectedValue)?;
+        let service =
+            decode_stupid_string(raw_service).ok_or(DescriptorParseError::UnexpectedValue)?;
 
-        Service {
+        Ok(Service {
             service_type,
             provider,
             service,
+        })
+    }
+
+    /// Strings are written as-is, without a character table prefix.
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = vec![self.service_type.to_byte()];
+        for text in [&self.provider, &self.service] {
+            buf.push(text.len() as u8);
+            buf.extend_from_slice(text.as_bytes());
         }
+        buf
     }
 }
@@ -1,13 +1,16 @@
-use crate::mpeg::ServiceType;
+use crate::{
+    error::DescriptorParseError,
+    mpeg::{ServiceType, descriptors::check_length},
+};
 
 pub const DESCRIPTOR_ID: u8 = 0x41;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ServiceList {
     pub services: Vec<ServiceListDescriptorElement>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ServiceListDescriptorElement {
     /// Same as program number in program map except for 0x04, 0x18, 0x1B (NVOD services) (from ETSI EN 300 468)
     pub service_id: u16,
@@ -15,11 +18,12 @@ pub struct ServiceListDescriptorElement {
 }
 
 impl ServiceList {
-    pub fn from_buf(buf: &[u8]) -> ServiceList {
+    pub fn from_buf(buf: &[u8]) -> Result<ServiceList, DescriptorParseError> {
         let mut services = Vec::new();
 
         let mut offset = 0;
         while offset < buf.len() {
+            check_length(buf, offset + 3)?;
             let service_id = u16::from_be_bytes([buf[offset], buf[offset + 1]]);
             let service_type = ServiceType::from_byte(buf[offset + 2]);
             offset += 3;
@@ -29,6 +33,15 @@ impl ServiceList {
             });
         }
 
-        ServiceList { services }
+        Ok(ServiceList { services })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.services.len() * 3);
+        for service in &self.services {
+            buf.extend_from_slice(&service.service_id.to_be_bytes());
+            buf.push(service.service_type.to_byte());
+        }
+        buf
     }
 }
@@ -1,15 +1,23 @@
+use crate::{error::DescriptorParseError, mpeg::descriptors::check_length};
+
 pub const DESCRIPTOR_ID: u8 = 0x52;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StreamIdentifier {
     /// Identifies the component stream for associating it with a description given in a component descriptor.
     pub component_tag: u8,
 }
 
 impl StreamIdentifier {
-    pub fn from_buf(buf: &[u8]) -> StreamIdentifier {
+    pub fn from_buf(buf: &[u8]) -> Result<StreamIdentifier, DescriptorParseError> {
+        check_length(buf, 1)?;
+
         let component_tag = buf[0];
 
-        StreamIdentifier { component_tag }
+        Ok(StreamIdentifier { component_tag })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        vec![self.component_tag]
     }
 }
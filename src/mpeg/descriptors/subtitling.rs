@@ -1,27 +1,33 @@
+use crate::{
+    error::DescriptorParseError,
+    mpeg::{LanguageCode, descriptors::check_length},
+};
+
 pub const DESCRIPTOR_ID: u8 = 0x59;
 
 // ETSI EN 300 468 page 91
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Subtitling {
     pub elements: Vec<SubtitlingElement>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SubtitlingElement {
     // ISO 639
-    pub language_code: [u8; 3],
+    pub language_code: LanguageCode,
     pub subtitling_type: u8,
     pub composition_page_id: u16,
     pub ancillary_page_id: u16,
 }
 
 impl Subtitling {
-    pub fn from_buf(buf: &[u8]) -> Subtitling {
+    pub fn from_buf(buf: &[u8]) -> Result<Subtitling, DescriptorParseError> {
         let mut elements = Vec::new();
 
         let mut offset = 0;
         while offset < buf.len() {
-            let language_code = [buf[offset], buf[offset + 1], buf[offset + 2]];
+            check_length(buf, offset + 8)?;
+            let language_code = LanguageCode([buf[offset], buf[offset + 1], buf[offset + 2]]);
             offset += 3;
             let subtitling_type = buf[offset];
             offset += 1;
@@ -37,6 +43,17 @@ impl Subtitling {
             })
         }
 
-        Subtitling { elements }
+        Ok(Subtitling { elements })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.elements.len() * 8);
+        for element in &self.elements {
+            buf.extend_from_slice(&element.language_code.0);
+            buf.push(element.subtitling_type);
+            buf.extend_from_slice(&element.composition_page_id.to_be_bytes());
+            buf.extend_from_slice(&element.ancillary_page_id.to_be_bytes());
+        }
+        buf
     }
 }
@@ -0,0 +1,265 @@
+use rdvb_os_linux::frontend::data::{FeGuardInterval, FeTransmitMode};
+
+use crate::{
+    error::DescriptorParseError, frontend::properties::set::BandwidthHz,
+    mpeg::descriptors::check_length,
+};
+
+/// Tag extension of the T2 delivery system descriptor, inside an [Extension](super::extension::Extension) descriptor.
+pub const TAG_EXTENSION: u8 = 0x04;
+
+// ETSI EN 300 468 section 6.4.6.3
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct T2DeliverySystem {
+    /// PLP carrying the transport stream, to tune to with [plp](crate::frontend::TuneRequest::plp)
+    pub plp_id: u8,
+    pub t2_system_id: u16,
+    /// Only present in the first descriptor for a T2 system, the others just identify their PLP
+    pub parameters: Option<T2Parameters>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct T2Parameters {
+    pub siso_miso: u8,
+    pub bandwidth: u8,
+    pub guard_interval: u8,
+    pub transmission_mode: u8,
+    pub other_frequency_flag: bool,
+    /// Time-frequency slicing, where each cell is sent over several frequencies
+    pub tfs_flag: bool,
+    pub cells: Vec<T2Cell>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct T2Cell {
+    pub cell_id: u16,
+    /// Plain big-endian values in units of 10Hz. Exactly one unless [tfs_flag](T2Parameters::tfs_flag) is set.
+    pub center_frequencies: Vec<u32>,
+    pub subcells: Vec<T2Subcell>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct T2Subcell {
+    pub cell_id_extension: u8,
+    /// Plain big-endian value in units of 10Hz
+    pub transposer_frequency: u32,
+}
+
+impl T2DeliverySystem {
+    /// Parse the descriptor body following the tag extension.
+    pub fn from_buf(buf: &[u8]) -> Result<T2DeliverySystem, DescriptorParseError> {
+        check_length(buf, 3)?;
+
+        let plp_id = buf[0];
+        let t2_system_id = u16::from_be_bytes([buf[1], buf[2]]);
+        let parameters = if buf.len() > 3 {
+            Some(T2Parameters::from_buf(&buf[3..])?)
+        } else {
+            None
+        };
+
+        Ok(T2DeliverySystem {
+            plp_id,
+            t2_system_id,
+            parameters,
+        })
+    }
+
+    /// Return the bandwidth, if parameters are present and it isn't one of the reserved values
+    pub fn bandwidth_hz(&self) -> Option<BandwidthHz> {
+        Some(match self.parameters.as_ref()?.bandwidth {
+            0 => BandwidthHz::_8MHz,
+            1 => BandwidthHz::_7MHz,
+            2 => BandwidthHz::_6MHz,
+            3 => BandwidthHz::_5MHz,
+            4 => BandwidthHz::_10MHz,
+            5 => BandwidthHz::_1_172MHz,
+            _ => return None,
+        })
+    }
+
+    /// Return the guard interval, if parameters are present and it isn't one of the reserved values
+    pub fn guard_interval(&self) -> Option<FeGuardInterval> {
+        Some(match self.parameters.as_ref()?.guard_interval {
+            0 => FeGuardInterval::GUARD_INTERVAL_1_32,
+            1 => FeGuardInterval::GUARD_INTERVAL_1_16,
+            2 => FeGuardInterval::GUARD_INTERVAL_1_8,
+            3 => FeGuardInterval::GUARD_INTERVAL_1_4,
+            4 => FeGuardInterval::GUARD_INTERVAL_1_128,
+            5 => FeGuardInterval::GUARD_INTERVAL_19_128,
+            6 => FeGuardInterval::GUARD_INTERVAL_19_256,
+            _ => return None,
+        })
+    }
+
+    /// Return the transmission mode, if parameters are present and it isn't one of the reserved values
+    pub fn transmission_mode(&self) -> Option<FeTransmitMode> {
+        Some(match self.parameters.as_ref()?.transmission_mode {
+            0 => FeTransmitMode::TRANSMISSION_MODE_2K,
+            1 => FeTransmitMode::TRANSMISSION_MODE_8K,
+            2 => FeTransmitMode::TRANSMISSION_MODE_4K,
+            3 => FeTransmitMode::TRANSMISSION_MODE_1K,
+            4 => FeTransmitMode::TRANSMISSION_MODE_16K,
+            5 => FeTransmitMode::TRANSMISSION_MODE_32K,
+            _ => return None,
+        })
+    }
+
+    /// Center frequencies in Hz of all cells, ready to tune to. Transposers are left out.
+    pub fn frequencies(&self) -> impl Iterator<Item = u32> {
+        self.parameters
+            .iter()
+            .flat_map(|p| p.cells.iter())
+            .flat_map(|c| c.center_frequencies.iter())
+            .map(|f| f.saturating_mul(10))
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = vec![self.plp_id];
+        buf.extend_from_slice(&self.t2_system_id.to_be_bytes());
+        if let Some(parameters) = &self.parameters {
+            parameters.write(&mut buf);
+        }
+        buf
+    }
+}
+
+impl T2Parameters {
+    fn from_buf(buf: &[u8]) -> Result<T2Parameters, DescriptorParseError> {
+        check_length(buf, 2)?;
+
+        let siso_miso = (buf[0] & 0b1100_0000) >> 6;
+        let bandwidth = (buf[0] & 0b0011_1100) >> 2;
+        let _reserved = buf[0] & 0b0000_0011;
+        let guard_interval = (buf[1] & 0b1110_0000) >> 5;
+        let transmission_mode = (buf[1] & 0b0001_1100) >> 2;
+        let other_frequency_flag = (buf[1] & 0b0000_0010) != 0;
+        let tfs_flag = (buf[1] & 0b0000_0001) != 0;
+
+        let mut cells = Vec::new();
+        let mut offset = 2;
+        while offset < buf.len() {
+            check_length(buf, offset + 2)?;
+            let cell_id = u16::from_be_bytes([buf[offset], buf[offset + 1]]);
+            offset += 2;
+
+            let frequency_loop_length = if tfs_flag {
+                check_length(buf, offset + 1)?;
+                offset += 1;
+                buf[offset - 1] as usize
+            } else {
+                4
+            };
+            check_length(buf, offset + frequency_loop_length)?;
+            let center_frequencies = buf[offset..offset + frequency_loop_length]
+                .chunks_exact(4)
+                .map(|f| u32::from_be_bytes([f[0], f[1], f[2], f[3]]))
+                .collect();
+            offset += frequency_loop_length;
+
+            check_length(buf, offset + 1)?;
+            let subcell_info_loop_length = buf[offset] as usize;
+            offset += 1;
+            check_length(buf, offset + subcell_info_loop_length)?;
+            let subcells = buf[offset..offset + subcell_info_loop_length]
+                .chunks_exact(5)
+                .map(|s| T2Subcell {
+                    cell_id_extension: s[0],
+                    transposer_frequency: u32::from_be_bytes([s[1], s[2], s[3], s[4]]),
+                })
+                .collect();
+            offset += subcell_info_loop_length;
+
+            cells.push(T2Cell {
+                cell_id,
+                center_frequencies,
+                subcells,
+            });
+        }
+
+        Ok(T2Parameters {
+            siso_miso,
+            bandwidth,
+            guard_interval,
+            transmission_mode,
+            other_frequency_flag,
+            tfs_flag,
+            cells,
+        })
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) {
+        buf.push((self.siso_miso << 6) | (self.bandwidth << 2) | 0b0000_0011);
+        buf.push(
+            (self.guard_interval << 5)
+                | (self.transmission_mode << 2)
+                | ((self.other_frequency_flag as u8) << 1)
+                | self.tfs_flag as u8,
+        );
+        for cell in &self.cells {
+            buf.extend_from_slice(&cell.cell_id.to_be_bytes());
+            if self.tfs_flag {
+                buf.push((cell.center_frequencies.len() * 4) as u8);
+            }
+            for frequency in &cell.center_frequencies {
+                buf.extend_from_slice(&frequency.to_be_bytes());
+            }
+            buf.push((cell.subcells.len() * 5) as u8);
+            for subcell in &cell.subcells {
+                buf.push(subcell.cell_id_extension);
+                buf.extend_from_slice(&subcell.transposer_frequency.to_be_bytes());
+            }
+        }
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::descriptors::{Descriptor, extension::Extension};
+
+    #[test]
+    fn round_trip() {
+        let samples = [
+            T2DeliverySystem {
+                plp_id: 1,
+                t2_system_id: 0x8001,
+                parameters: None,
+            },
+            T2DeliverySystem {
+                plp_id: 0,
+                t2_system_id: 0x8001,
+                parameters: Some(T2Parameters {
+                    siso_miso: 1,
+                    bandwidth: 0,
+                    guard_interval: 6,
+                    transmission_mode: 5,
+                    other_frequency_flag: true,
+                    tfs_flag: true,
+                    cells: vec![T2Cell {
+                        cell_id: 0x0101,
+                        center_frequencies: vec![49_000_000, 49_800_000],
+                        subcells: vec![T2Subcell {
+                            cell_id_extension: 2,
+                            transposer_frequency: 50_600_000,
+                        }],
+                    }],
+                }),
+            },
+        ];
+
+        for t2 in samples {
+            let descriptor = Descriptor::Extension(Extension::T2DeliverySystem(t2));
+            let buf = descriptor.to_buf();
+            assert_eq!(
+                Descriptor::read(buf[0], &buf[2..]).unwrap(),
+                descriptor,
+                "{:02X?}",
+                buf
+            );
+        }
+    }
+}
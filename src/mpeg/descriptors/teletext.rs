@@ -0,0 +1,57 @@
+use crate::{
+    error::DescriptorParseError,
+    mpeg::{LanguageCode, descriptors::check_length},
+};
+
+pub const DESCRIPTOR_ID: u8 = 0x56;
+
+// ETSI EN 300 468 page 96
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Teletext {
+    pub elements: Vec<TeletextElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeletextElement {
+    // ISO 639
+    pub language_code: LanguageCode,
+    pub teletext_type: u8,
+    pub magazine: u8,
+    pub page: u8,
+}
+
+impl Teletext {
+    pub fn from_buf(buf: &[u8]) -> Result<Teletext, DescriptorParseError> {
+        let mut elements = Vec::new();
+
+        let mut offset = 0;
+        while offset < buf.len() {
+            check_length(buf, offset + 5)?;
+            let language_code = LanguageCode([buf[offset], buf[offset + 1], buf[offset + 2]]);
+            offset += 3;
+            let teletext_type = (buf[offset] & 0b1111_1000) >> 3;
+            let magazine = buf[offset] & 0b0000_0111;
+            offset += 1;
+            let page = buf[offset];
+            offset += 1;
+            elements.push(TeletextElement {
+                language_code,
+                teletext_type,
+                magazine,
+                page,
+            })
+        }
+
+        Ok(Teletext { elements })
+    }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.elements.len() * 5);
+        for element in &self.elements {
+            buf.extend_from_slice(&element.language_code.0);
+            buf.push((element.teletext_type << 3) | element.magazine);
+            buf.push(element.page);
+        }
+        buf
+    }
+}
@@ -1,8 +1,18 @@
+use rdvb_os_linux::frontend::data::{
+    FeCodeRate, FeGuardInterval, FeHierarchy, FeModulation, FeTransmitMode,
+};
+
+use crate::{
+    error::DescriptorParseError, frontend::properties::set::BandwidthHz,
+    mpeg::descriptors::check_length,
+};
+
 pub const DESCRIPTOR_ID: u8 = 0x5A;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TerrestrialDeliverySystem {
-    pub center_frequency: i32,
+    /// Plain big-endian value in units of 10Hz, see [frequency](Self::frequency)
+    pub center_frequency: u32,
     pub bandwidth: u8,
     pub priority: bool,
     pub time_slicing_indicator: bool,
@@ -17,8 +27,10 @@ pub struct TerrestrialDeliverySystem {
 }
 
 impl TerrestrialDeliverySystem {
-    pub fn from_buf(buf: &[u8]) -> TerrestrialDeliverySystem {
-        let center_frequency = i32::from_be_bytes([buf[0], buf[2], buf[1], buf[3]]);
+    pub fn from_buf(buf: &[u8]) -> Result<TerrestrialDeliverySystem, DescriptorParseError> {
+        check_length(buf, 11)?;
+
+        let center_frequency = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]);
         let bandwidth = (buf[4] & 0b1110_0000) >> 5;
         let priority = (buf[4] & 0b0001_0000) != 0;
         let time_slicing_indicator = (buf[4] & 0b0000_1000) != 0;
@@ -33,7 +45,7 @@ impl TerrestrialDeliverySystem {
         let other_frequency_flag = (buf[6] & 0b0000_0001) != 0;
         let _reserved = u32::from_be_bytes([buf[7], buf[8], buf[9], buf[10]]);
 
-        TerrestrialDeliverySystem {
+        Ok(TerrestrialDeliverySystem {
             center_frequency,
             bandwidth,
             priority,
@@ -46,6 +58,130 @@ impl TerrestrialDeliverySystem {
             guard_interval,
             transmission_mode,
             other_frequency_flag,
+        })
+    }
+
+    /// Center frequency in Hz, ready to tune to
+    pub fn frequency(&self) -> u32 {
+        self.center_frequency.saturating_mul(10)
+    }
+
+    /// Center frequency in Hz, without the risk of overflowing for values outside of the terrestrial bands
+    pub fn center_frequency_hz(&self) -> u64 {
+        self.center_frequency as u64 * 10
+    }
+
+    /// Return the bandwidth, if not one of the reserved values
+    pub fn bandwidth_hz(&self) -> Option<BandwidthHz> {
+        BandwidthHz::try_from(self).ok()
+    }
+
+    /// Return the constellation, if not the reserved value
+    pub fn constellation(&self) -> Option<FeModulation> {
+        match self.constellation {
+            0 => Some(FeModulation::QPSK),
+            1 => Some(FeModulation::QAM_16),
+            2 => Some(FeModulation::QAM_64),
+            _ => None,
+        }
+    }
+
+    /// Return the hierarchy, the in-depth interleaver bit is ignored
+    pub fn hierarchy(&self) -> FeHierarchy {
+        match self.hierarchy_information & 0b011 {
+            0 => FeHierarchy::HIERARCHY_NONE,
+            1 => FeHierarchy::HIERARCHY_1,
+            2 => FeHierarchy::HIERARCHY_2,
+            _ => FeHierarchy::HIERARCHY_4,
+        }
+    }
+
+    /// Return the code rate of the high priority stream, if not one of the reserved values
+    pub fn code_rate_hp(&self) -> Option<FeCodeRate> {
+        code_rate(self.code_rate_hp_stream)
+    }
+
+    /// Return the code rate of the low priority stream, if not one of the reserved values
+    pub fn code_rate_lp(&self) -> Option<FeCodeRate> {
+        code_rate(self.code_rate_lp_stream)
+    }
+
+    pub fn guard_interval(&self) -> FeGuardInterval {
+        match self.guard_interval {
+            0 => FeGuardInterval::GUARD_INTERVAL_1_32,
+            1 => FeGuardInterval::GUARD_INTERVAL_1_16,
+            2 => FeGuardInterval::GUARD_INTERVAL_1_8,
+            _ => FeGuardInterval::GUARD_INTERVAL_1_4,
+        }
+    }
+
+    /// Return the transmission mode, if not the reserved value
+    pub fn transmission_mode(&self) -> Option<FeTransmitMode> {
+        match self.transmission_mode {
+            0 => Some(FeTransmitMode::TRANSMISSION_MODE_2K),
+            1 => Some(FeTransmitMode::TRANSMISSION_MODE_8K),
+            2 => Some(FeTransmitMode::TRANSMISSION_MODE_4K),
+            _ => None,
+        }
+    }
+
+    /// Value of the `bandwidth` field for a given bandwidth, if the descriptor can hold it
+    pub fn bandwidth_code(bandwidth: BandwidthHz) -> Option<u8> {
+        match bandwidth {
+            BandwidthHz::_8MHz => Some(0),
+            BandwidthHz::_7MHz => Some(1),
+            BandwidthHz::_6MHz => Some(2),
+            BandwidthHz::_5MHz => Some(3),
+            BandwidthHz::_1_172MHz | BandwidthHz::_10MHz => None,
         }
     }
+
+    pub fn to_buf(&self) -> Vec<u8> {
+        let mut buf = self.center_frequency.to_be_bytes().to_vec();
+        buf.push(
+            (self.bandwidth << 5)
+                | ((self.priority as u8) << 4)
+                | ((self.time_slicing_indicator as u8) << 3)
+                | ((self.mpe_fec_indicator as u8) << 2)
+                | 0b0000_0011,
+        );
+        buf.push(
+            (self.constellation << 6)
+                | (self.hierarchy_information << 3)
+                | self.code_rate_hp_stream,
+        );
+        buf.push(
+            (self.code_rate_lp_stream << 5)
+                | (self.guard_interval << 3)
+                | (self.transmission_mode << 1)
+                | self.other_frequency_flag as u8,
+        );
+        buf.extend_from_slice(&[0xFF; 4]);
+        buf
+    }
+}
+
+fn code_rate(value: u8) -> Option<FeCodeRate> {
+    match value {
+        0 => Some(FeCodeRate::FEC_1_2),
+        1 => Some(FeCodeRate::FEC_2_3),
+        2 => Some(FeCodeRate::FEC_3_4),
+        3 => Some(FeCodeRate::FEC_5_6),
+        4 => Some(FeCodeRate::FEC_7_8),
+        _ => None,
+    }
+}
+
+impl TryFrom<&TerrestrialDeliverySystem> for BandwidthHz {
+    type Error = DescriptorParseError;
+
+    fn try_from(value: &TerrestrialDeliverySystem) -> Result<Self, Self::Error> {
+        Ok(match value.bandwidth {
+            0 => BandwidthHz::_8MHz,
+            1 => BandwidthHz::_7MHz,
+            2 => BandwidthHz::_6MHz,
+            3 => BandwidthHz::_5MHz,
+            _ => return Err(DescriptorParseError::UnexpectedValue),
+        })
+    }
 }
@@ -1,37 +1,124 @@
+pub mod assembler;
 pub mod descriptors;
+pub mod pes;
+pub mod ts;
+
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    str::FromStr,
+};
+
+use crate::{
+    error::{LanguageCodeParseError, PacketError, PidOutOfRange},
+    si::TableId,
+};
 
 pub const DMX_CHECK_CRC: u32 = 1;
 pub const DMX_ONESHOT: u32 = 2;
 pub const DMX_IMMEDIATE_START: u32 = 4;
 
-// TODO: 0x2000 does not work anymore for receiving all packets. Is there still a way to get the entire stream ? I think mpv might have something.
+/// Size of a single raw Transport Stream packet.
+pub const TS_PACKET_LENGTH: usize = 188;
+
+/// Largest size of a whole section, header included, which only private sections and EIT can reach.
+pub const MAX_SECTION_LENGTH: usize = 4096;
+
+// 0x2000 does not work anymore for receiving all packets, use a TS tap over every wanted PID instead (see Demux::tap_pids).
+
+/// CRC-32/MPEG-2 of `data`, as ending every PSI/SI section.
+///
+/// Over a whole section, CRC included, this gives 0 if nothing was corrupted.
+pub fn crc32_mpeg2(data: &[u8]) -> u32 {
+    crc32_mpeg2_update(0xFFFF_FFFF, data)
+}
+
+fn crc32_mpeg2_update(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= (byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
 
 // -----
 
+#[derive(Debug)]
 pub struct Packet {
     pub header: PacketHeader,
     pub data: Vec<u8>,
     pub crc: u32,
+    /// Header as received, reserved bits included, so that the section can be written back untouched.
+    raw_header: [u8; PacketHeader::LENGTH],
 }
 
 impl Packet {
-    pub fn from_buf(buf: &[u8]) -> Packet {
-        let header = PacketHeader::from_buf(buf);
+    pub fn from_buf(buf: &[u8]) -> Result<Packet, PacketError> {
+        let header = PacketHeader::from_buf(buf)?;
+        // Anything following the section in the buffer is ignored
+        let total_length = PacketHeader::SHORT_LENGTH + header.section_length as usize;
+        if buf.len() < total_length {
+            return Err(PacketError::TooShort);
+        }
 
-        let payload_start = PacketHeader::LENGTH;
-        let payload_end = buf.len() - (PacketHeader::LENGTH - 4); // Remove header and CRC32 from total size
-        let data = buf[payload_start..payload_end].to_vec();
+        let header_length = header.length();
+        let (data, crc) = if header.section_syntax_indicator {
+            // Not checked here, the kernel usually did already, see crc_matches
+            let crc_start = total_length - 4;
+            let crc = u32::from_be_bytes([
+                buf[crc_start],
+                buf[crc_start + 1],
+                buf[crc_start + 2],
+                buf[crc_start + 3],
+            ]);
+            (buf[header_length..crc_start].to_vec(), crc)
+        } else {
+            // Short sections have no CRC field, whatever they end with is left in the data
+            (buf[header_length..total_length].to_vec(), 0)
+        };
 
-        // TODO: At least, I assume ? I couldn't match the CRC... Not sure if there is an init value, or if the CRC field should not be used ?
-        let crc_start = buf.len() - 4;
-        let crc = u32::from_be_bytes([
-            buf[crc_start],
-            buf[crc_start + 1],
-            buf[crc_start + 2],
-            buf[crc_start + 3],
-        ]);
+        let mut raw_header = [0; PacketHeader::LENGTH];
+        raw_header[..header_length].copy_from_slice(&buf[..header_length]);
 
-        Self { header, data, crc }
+        Ok(Self {
+            header,
+            data,
+            crc,
+            raw_header,
+        })
+    }
+
+    /// Write the whole section back, as it was given to [from_buf](Self::from_buf).
+    ///
+    /// This is meant to pass a section on to something else, the header is not updated if `data` was changed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(PacketHeader::LENGTH + self.data.len() + 4);
+        buf.extend_from_slice(&self.raw_header[..self.header.length()]);
+        buf.extend_from_slice(&self.data);
+        if self.header.section_syntax_indicator {
+            buf.extend_from_slice(&self.crc.to_be_bytes());
+        }
+        buf
+    }
+
+    /// Return `true` if the CRC of the section matches its header and data.
+    ///
+    /// Short sections carry no CRC and always match.
+    pub fn crc_matches(&self) -> bool {
+        !self.header.section_syntax_indicator || self.computed_crc() == self.crc
+    }
+
+    /// CRC of the header and data, as it should have been received.
+    pub fn computed_crc(&self) -> u32 {
+        crc32_mpeg2_update(
+            crc32_mpeg2(&self.raw_header[..self.header.length()]),
+            &self.data,
+        )
     }
 }
 
@@ -50,27 +137,50 @@ pub struct PacketHeader {
 
 impl PacketHeader {
     pub const LENGTH: usize = 8;
+    /// Length of the header of sections without the syntax indicator, such as the TDT.
+    pub const SHORT_LENGTH: usize = 3;
 
-    pub fn from_buf(buf: &[u8]) -> PacketHeader {
-        if buf.len() < Self::LENGTH {
-            panic!()
+    pub fn from_buf(buf: &[u8]) -> Result<PacketHeader, PacketError> {
+        if buf.len() < Self::SHORT_LENGTH {
+            return Err(PacketError::TooShort);
         }
 
         let table_id = buf[0];
         let section_syntax_indicator = (buf[1] & 0b1000_0000) != 0;
         // assert_eq!(buf[1] & 0b0100_0000, 0); // TODO: This bit seems to be set for NIT table
         let _reserved_1 = buf[1] & 0b0011_0000;
-        assert_eq!(buf[1] & 0b0000_1100, 0);
-        let section_length = u16::from_be_bytes([buf[1] & 0b0000_0011, buf[2]]);
-        assert!(section_length <= 0x3FD);
+        let section_length = u16::from_be_bytes([buf[1] & 0b0000_1111, buf[2]]);
+        if section_length > Self::max_section_length(table_id) {
+            return Err(PacketError::BadSectionLength);
+        }
+        if !section_syntax_indicator {
+            return Ok(PacketHeader {
+                table_id,
+                section_syntax_indicator,
+                section_length,
+                identifier: 0,
+                version_number: 0,
+                current_next_indicator: true,
+                section_number: 0,
+                last_section_number: 0,
+            });
+        }
+
+        if buf.len() < Self::LENGTH {
+            return Err(PacketError::TooShort);
+        }
+        // Must at least hold the rest of the header and the CRC
+        if section_length < 5 + 4 {
+            return Err(PacketError::BadSectionLength);
+        }
         let transport_stream_id = u16::from_be_bytes([buf[3], buf[4]]);
         let _reserved_2 = buf[5] & 0b1100_0000;
-        let version_number = buf[5] & 0b0011_1110;
+        let version_number = (buf[5] & 0b0011_1110) >> 1;
         let current_next_indicator = (buf[5] & 0b0000_0001) != 0;
         let section_number = buf[6];
         let last_section_number = buf[7];
 
-        PacketHeader {
+        Ok(PacketHeader {
             table_id,
             section_syntax_indicator,
             section_length,
@@ -79,11 +189,41 @@ impl PacketHeader {
             current_next_indicator,
             section_number,
             last_section_number,
+        })
+    }
+
+    /// Length of the header, depending on the section syntax indicator.
+    pub fn length(&self) -> usize {
+        if self.section_syntax_indicator {
+            Self::LENGTH
+        } else {
+            Self::SHORT_LENGTH
         }
     }
 
     pub fn payload_len(&self) -> u16 {
-        self.section_length - (5 + 4)
+        if self.section_syntax_indicator {
+            self.section_length - (5 + 4)
+        } else {
+            self.section_length
+        }
+    }
+
+    /// Largest `section_length` allowed for a table.
+    ///
+    /// PSI tables and most SI tables stop at 1021 bytes, while EIT and private sections may go up to 4093.
+    pub fn max_section_length(table_id: u8) -> u16 {
+        match TableId::from_u8(table_id) {
+            TableId::Pat
+            | TableId::Cat
+            | TableId::Pmt
+            | TableId::NitActual
+            | TableId::NitOther
+            | TableId::SdtActual
+            | TableId::SdtOther
+            | TableId::Bat => 0x3FD,
+            _ => 0xFFD,
+        }
     }
 }
 
@@ -93,7 +233,7 @@ impl PacketHeader {
 /// Table of all possible service types.
 ///
 /// Taken from ETSI EN 300 468 page 85 (table 89)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ServiceType {
     DigitalTelevision,
     DigitalRadioSound,
@@ -160,6 +300,164 @@ impl ServiceType {
             _ => Self::Reserved(byte),
         }
     }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            Self::DigitalTelevision => 0x01,
+            Self::DigitalRadioSound => 0x02,
+            Self::Teletext => 0x03,
+            Self::NvodReference => 0x04,
+            Self::NvodTimeShifted => 0x05,
+            Self::Mosaic => 0x06,
+            Self::FmRadio => 0x07,
+            Self::DvbSrmService => 0x08,
+            Self::AdvancedCodecDigitalRadioSound => 0x0A,
+            Self::H264Mosaic => 0x0B,
+            Self::DataBroadcast => 0x0C,
+            Self::CiReserved => 0x0D,
+            Self::RcsMap => 0x0E,
+            Self::RcsForwardLinkSignalling => 0x0F,
+            Self::DvbMultimediaHomePlatform => 0x10,
+            Self::Mpeg2HdDigitalTelevision => 0x11,
+            Self::H264SdDigitalTelevision => 0x16,
+            Self::H264SdnvodTimeShifted => 0x17,
+            Self::H264SdnvodReference => 0x18,
+            Self::H264HdDigitalTelevision => 0x19,
+            Self::H264HdnvodTimeShifted => 0x1A,
+            Self::H264HdnvodReference => 0x1B,
+            Self::H264FrameCompatiblePlanoStereoscopicHdDigitalTelevision => 0x1C,
+            Self::H264FrameCompatiblePlanoStereoscopicHdnvodTimeShifted => 0x1D,
+            Self::H264FrameCompatiblePlanoStereoscopicHdnvodReference => 0x1E,
+            Self::HevcDigitalTelevision => 0x1F,
+            Self::HevcUhdDigitalTelevision => 0x20,
+            Self::UserDefined(byte) | Self::Reserved(byte) => *byte,
+        }
+    }
+}
+
+//
+// -----
+
+/// ISO 639-2 language code, like `fra`, as found in many descriptors.
+///
+/// Bytes are kept as received, even if they aren't letters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LanguageCode(pub [u8; 3]);
+
+impl LanguageCode {
+    /// Return the code as text, or `"und"` (undetermined) if it isn't made of ASCII letters.
+    pub fn as_str(&self) -> &str {
+        if self.0.iter().all(u8::is_ascii_alphabetic) {
+            // Can't fail, ASCII is valid UTF-8
+            std::str::from_utf8(&self.0).unwrap_or("und")
+        } else {
+            "und"
+        }
+    }
+
+    /// Compare codes regardless of case, as some broadcasters send them in uppercase.
+    pub fn matches(&self, other: &LanguageCode) -> bool {
+        self.0.eq_ignore_ascii_case(&other.0)
+    }
+}
+
+impl From<[u8; 3]> for LanguageCode {
+    fn from(value: [u8; 3]) -> Self {
+        LanguageCode(value)
+    }
+}
+
+impl From<LanguageCode> for [u8; 3] {
+    fn from(value: LanguageCode) -> Self {
+        value.0
+    }
+}
+
+impl Display for LanguageCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for LanguageCode {
+    type Err = LanguageCodeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes: [u8; 3] = s
+            .as_bytes()
+            .try_into()
+            .map_err(|_| LanguageCodeParseError)?;
+        if !bytes.iter().all(u8::is_ascii_alphabetic) {
+            return Err(LanguageCodeParseError);
+        }
+        Ok(LanguageCode(bytes))
+    }
+}
+
+//
+// -----
+
+/// Packet ID of a TS packet, always within the 13-bit range.
+///
+/// Well-known ones are defined in `ISO/IEC 13818-1` and `EN 300 468 V1.17.1`, p23.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pid(u16);
+
+impl Pid {
+    /// Program Association Table
+    pub const PAT: Pid = Pid(0x0000);
+    /// Conditional Access Table
+    pub const CAT: Pid = Pid(0x0001);
+    /// Network Information Table. This is the usual one, the actual one is given by the PAT for program 0.
+    pub const NIT: Pid = Pid(0x0010);
+    /// Service Description Table, shared with the Bouquet Association Table
+    pub const SDT: Pid = Pid(0x0011);
+    /// Event Information Table
+    pub const EIT: Pid = Pid(0x0012);
+    /// Time and Date Table, shared with the Time Offset Table
+    pub const TDT: Pid = Pid(0x0014);
+    /// Stuffing packets, which carry nothing
+    pub const NULL: Pid = Pid(0x1FFF);
+
+    pub fn new(value: u16) -> Result<Pid, PidOutOfRange> {
+        if value > Self::NULL.0 {
+            return Err(PidOutOfRange(value));
+        }
+        Ok(Pid(value))
+    }
+
+    /// Read from a 16-bit field, ignoring the 3 reserved bits above the PID.
+    pub fn from_bits(value: u16) -> Pid {
+        Pid(value & 0x1FFF)
+    }
+
+    pub fn value(self) -> u16 {
+        self.0
+    }
+
+    pub fn is_null(self) -> bool {
+        self == Self::NULL
+    }
+}
+
+impl TryFrom<u16> for Pid {
+    type Error = PidOutOfRange;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        Pid::new(value)
+    }
+}
+
+impl From<Pid> for u16 {
+    fn from(value: Pid) -> Self {
+        value.0
+    }
+}
+
+impl Display for Pid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:#06X}", self.0)
+    }
 }
 
 //
@@ -205,3 +503,140 @@ pub fn decode_stupid_string(raw_text: &[u8]) -> Option<String> {
 
     // todo!()
 }
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_number() {
+        // Version 21, current
+        let header =
+            PacketHeader::from_buf(&[0x42, 0xB0, 0x0D, 0x00, 0x01, 0b1110_1011, 0x00, 0x00])
+                .unwrap();
+
+        assert_eq!(header.version_number, 21);
+        assert!(header.current_next_indicator);
+    }
+
+    #[test]
+    fn long_sections() {
+        // EIT schedule section of 2000 bytes in total, 1997 announced by its header
+        let mut buf = vec![0x50, 0xF7, 0xCD, 0x01, 0x01, 0xC1, 0x00, 0x00];
+        buf.resize(2000, 0xFF);
+        let packet = Packet::from_buf(&buf).unwrap();
+        assert_eq!(packet.header.section_length, 1997);
+        assert_eq!(packet.header.payload_len() as usize, packet.data.len());
+
+        // Same length for an SDT
+        buf[0] = 0x42;
+        assert!(matches!(
+            Packet::from_buf(&buf),
+            Err(PacketError::BadSectionLength)
+        ));
+    }
+
+    #[test]
+    fn raw_bytes() {
+        // Reserved bits are set, they aren't parsed but must still be written back
+        let buf = [
+            0x42, 0xF0, 0x0C, 0x00, 0x01, 0xC1, 0x00, 0x00, // Header
+            0x20, 0xFA, 0xFF, // Payload
+            0x12, 0x34, 0x56, 0x78, // CRC
+        ];
+        let packet = Packet::from_buf(&buf).unwrap();
+        assert_eq!(packet.crc, 0x1234_5678);
+        assert_eq!(packet.to_bytes(), buf);
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let buf = [
+            0x42, 0xF0, 0x0C, 0x00, 0x01, 0xC1, 0x00, 0x00, // Header
+            0x20, 0xFA, 0xFF, // Payload
+            0x12, 0x34, 0x56, 0x78, // CRC
+            0xFF, 0xFF, // Stuffing
+        ];
+        let packet = Packet::from_buf(&buf).unwrap();
+        assert_eq!(packet.data, [0x20, 0xFA, 0xFF]);
+        assert_eq!(packet.crc, 0x1234_5678);
+        assert_eq!(packet.to_bytes(), buf[..15]);
+
+        assert!(matches!(
+            Packet::from_buf(&buf[..14]),
+            Err(PacketError::TooShort)
+        ));
+    }
+
+    #[test]
+    fn short_sections() {
+        // TDT, only a UTC time after the 3 bytes of header
+        let buf = [0x70, 0x70, 0x05, 0xE7, 0x82, 0x12, 0x45, 0x00];
+        let packet = Packet::from_buf(&buf).unwrap();
+        assert!(!packet.header.section_syntax_indicator);
+        assert_eq!(packet.header.length(), PacketHeader::SHORT_LENGTH);
+        assert_eq!(packet.header.payload_len(), 5);
+        assert_eq!(packet.data, buf[3..]);
+        assert!(packet.crc_matches());
+        assert_eq!(packet.to_bytes(), buf);
+    }
+
+    #[test]
+    fn pid() {
+        assert_eq!(Pid::new(0x0011).unwrap(), Pid::SDT);
+        assert!(Pid::new(0x2000).is_err());
+        assert_eq!(Pid::from_bits(0xFFFF), Pid::NULL);
+        assert!(Pid::NULL.is_null());
+        assert!(!Pid::PAT.is_null());
+        assert_eq!(Pid::NIT.to_string(), "0x0010");
+    }
+
+    #[test]
+    fn crc() {
+        assert_eq!(crc32_mpeg2(b"123456789"), 0x0376_E6E7);
+
+        let mut buf = [
+            0x00, 0xB0, 0x0D, 0x00, 0x01, 0xC1, 0x00, 0x00, // Header
+            0x00, 0x01, 0xE1, 0x00, // Program 1, PID 0x0100
+            0x00, 0x00, 0x00, 0x00, // CRC
+        ];
+        let crc = crc32_mpeg2(&buf[..12]);
+        buf[12..].copy_from_slice(&crc.to_be_bytes());
+        assert_eq!(crc32_mpeg2(&buf), 0);
+        assert!(Packet::from_buf(&buf).unwrap().crc_matches());
+
+        buf[10] = 0xE2;
+        assert!(!Packet::from_buf(&buf).unwrap().crc_matches());
+    }
+
+    #[test]
+    fn malformed_headers() {
+        assert!(matches!(
+            Packet::from_buf(&[0x42, 0xB0, 0x0D, 0x00]),
+            Err(PacketError::TooShort)
+        ));
+        // Longer than allowed for an SDT
+        assert!(matches!(
+            PacketHeader::from_buf(&[0x42, 0xB4, 0x0D, 0x00, 0x01, 0xC1, 0x00, 0x00]),
+            Err(PacketError::BadSectionLength)
+        ));
+        assert!(matches!(
+            PacketHeader::from_buf(&[0x42, 0xB0, 0x02, 0x00, 0x01, 0xC1, 0x00, 0x00]),
+            Err(PacketError::BadSectionLength)
+        ));
+    }
+
+    #[test]
+    fn language_code() {
+        let code: LanguageCode = "fra".parse().unwrap();
+        assert_eq!(code, LanguageCode(*b"fra"));
+        assert_eq!(code.to_string(), "fra");
+        assert!(code.matches(&LanguageCode(*b"FRA")));
+        assert_eq!(LanguageCode(*b"fr\x01").as_str(), "und");
+        assert!("fr".parse::<LanguageCode>().is_err());
+        assert!("fr1".parse::<LanguageCode>().is_err());
+    }
+}
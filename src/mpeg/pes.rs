@@ -0,0 +1,192 @@
+//! Packetized Elementary Stream, carrying the actual audio and video data of a program.
+//!
+//! As defined in ISO/IEC 13818-1 p31 (2.4.3.6)
+
+use crate::error::PesError;
+
+/// Frequency of the PTS and DTS clock
+pub const TIMESTAMP_HZ: u64 = 90_000;
+
+// Stream IDs that are never followed by the optional PES header, ISO/IEC 13818-1 p34
+const PROGRAM_STREAM_MAP: u8 = 0xBC;
+const PADDING_STREAM: u8 = 0xBE;
+const PRIVATE_STREAM_2: u8 = 0xBF;
+const ECM_STREAM: u8 = 0xF0;
+const EMM_STREAM: u8 = 0xF1;
+const DSMCC_STREAM: u8 = 0xF2;
+const H222_1_TYPE_E_STREAM: u8 = 0xF8;
+const PROGRAM_STREAM_DIRECTORY: u8 = 0xFF;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PesPacket<'a> {
+    pub stream_id: u8,
+    /// Number of bytes following this field. `0` means unbounded, which is only allowed for video in Transport Streams.
+    pub packet_length: u16,
+    /// Unset for streams without the optional header, like padding or private stream 2.
+    pub header: Option<PesHeader>,
+    pub payload: &'a [u8],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PesHeader {
+    pub scrambling_control: u8,
+    pub priority: bool,
+    pub data_alignment_indicator: bool,
+    pub copyright: bool,
+    pub original_or_copy: bool,
+    /// Presentation time stamp, in 90kHz units.
+    pub pts: Option<u64>,
+    /// Decoding time stamp, in 90kHz units. Only set if it differs from the PTS.
+    pub dts: Option<u64>,
+}
+
+impl<'a> PesPacket<'a> {
+    pub fn from_buf(buf: &'a [u8]) -> Result<PesPacket<'a>, PesError> {
+        if buf.len() < 6 {
+            return Err(PesError::TooShort);
+        }
+        if buf[0..3] != [0x00, 0x00, 0x01] {
+            return Err(PesError::BadStartCode);
+        }
+        let stream_id = buf[3];
+        let packet_length = u16::from_be_bytes([buf[4], buf[5]]);
+
+        // When the length is known, don't read past the end of this packet
+        let end = match packet_length {
+            0 => buf.len(),
+            length => 6 + length as usize,
+        };
+        if buf.len() < end {
+            return Err(PesError::TooShort);
+        }
+
+        if !has_header(stream_id) {
+            return Ok(PesPacket {
+                stream_id,
+                packet_length,
+                header: None,
+                payload: &buf[6..end],
+            });
+        }
+
+        if end < 9 {
+            return Err(PesError::TooShort);
+        }
+        if buf[6] & 0b1100_0000 != 0b1000_0000 {
+            return Err(PesError::BadMarker);
+        }
+        let scrambling_control = (buf[6] & 0b0011_0000) >> 4;
+        let priority = (buf[6] & 0b0000_1000) != 0;
+        let data_alignment_indicator = (buf[6] & 0b0000_0100) != 0;
+        let copyright = (buf[6] & 0b0000_0010) != 0;
+        let original_or_copy = (buf[6] & 0b0000_0001) != 0;
+        let pts_dts_flags = (buf[7] & 0b1100_0000) >> 6;
+        // Other optional fields (ESCR, ES rate...) are skipped over with the header length
+        let header_data_length = buf[8] as usize;
+
+        let payload_start = 9 + header_data_length;
+        if end < payload_start {
+            return Err(PesError::TooShort);
+        }
+        let header_data = &buf[9..payload_start];
+
+        let (pts, dts) = match pts_dts_flags {
+            0b10 => (Some(read_timestamp(header_data, 0)?), None),
+            0b11 => (
+                Some(read_timestamp(header_data, 0)?),
+                Some(read_timestamp(header_data, 5)?),
+            ),
+            0b00 => (None, None),
+            _ => return Err(PesError::BadMarker),
+        };
+
+        Ok(PesPacket {
+            stream_id,
+            packet_length,
+            header: Some(PesHeader {
+                scrambling_control,
+                priority,
+                data_alignment_indicator,
+                copyright,
+                original_or_copy,
+                pts,
+                dts,
+            }),
+            payload: &buf[payload_start..end],
+        })
+    }
+
+    pub fn pts(&self) -> Option<u64> {
+        self.header.as_ref().and_then(|h| h.pts)
+    }
+
+    /// DTS if present, PTS otherwise, as they are the same when the DTS is omitted.
+    pub fn dts(&self) -> Option<u64> {
+        self.header.as_ref().and_then(|h| h.dts.or(h.pts))
+    }
+}
+
+fn has_header(stream_id: u8) -> bool {
+    !matches!(
+        stream_id,
+        PROGRAM_STREAM_MAP
+            | PADDING_STREAM
+            | PRIVATE_STREAM_2
+            | ECM_STREAM
+            | EMM_STREAM
+            | DSMCC_STREAM
+            | H222_1_TYPE_E_STREAM
+            | PROGRAM_STREAM_DIRECTORY
+    )
+}
+
+/// Read a 33 bits timestamp, split over 5 bytes with marker bits in between.
+fn read_timestamp(buf: &[u8], offset: usize) -> Result<u64, PesError> {
+    let bytes = buf.get(offset..offset + 5).ok_or(PesError::TooShort)?;
+    if bytes[0] & 1 == 0 || bytes[2] & 1 == 0 || bytes[4] & 1 == 0 {
+        return Err(PesError::BadMarker);
+    }
+
+    let high = ((bytes[0] & 0b0000_1110) as u64) << 29;
+    let middle = (u16::from_be_bytes([bytes[1], bytes[2]]) as u64 >> 1) << 15;
+    let low = u16::from_be_bytes([bytes[3], bytes[4]]) as u64 >> 1;
+    Ok(high | middle | low)
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pts_and_dts() {
+        let buf = [
+            0x00, 0x00, 0x01, 0xE0, 0x00, 0x0F, // Start code, video stream 0, length
+            0x84, 0xC0, 0x0A, // Data alignment, PTS and DTS, header length
+            0x39, 0x8D, 0x15, 0xCF, 0x13, // PTS
+            0x19, 0x8D, 0x15, 0x00, 0x01, // DTS
+            0xAA, 0xBB, // Payload
+        ];
+        let packet = PesPacket::from_buf(&buf).unwrap();
+
+        assert_eq!(packet.stream_id, 0xE0);
+        let header = packet.header.as_ref().unwrap();
+        assert!(header.data_alignment_indicator);
+        assert_eq!(packet.pts(), Some(0x1_2345_6789));
+        assert_eq!(packet.dts(), Some(0x1_2345_0000));
+        assert_eq!(packet.payload, [0xAA, 0xBB]);
+    }
+
+    #[test]
+    fn without_header() {
+        let buf = [0x00, 0x00, 0x01, 0xBE, 0x00, 0x03, 0xFF, 0xFF, 0xFF];
+        let packet = PesPacket::from_buf(&buf).unwrap();
+
+        assert_eq!(packet.header, None);
+        assert_eq!(packet.payload, [0xFF, 0xFF, 0xFF]);
+
+        assert!(PesPacket::from_buf(&[0x00, 0x00, 0x02, 0xBE, 0x00, 0x00]).is_err());
+    }
+}
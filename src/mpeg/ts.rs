@@ -0,0 +1,345 @@
+//! Raw Transport Stream packets, as received from a TS tap.
+//!
+//! As defined in ISO/IEC 13818-1 p19 (2.4.3.2)
+
+use crate::{error::TsError, mpeg::TS_PACKET_LENGTH};
+
+/// First byte of every TS packet
+pub const SYNC_BYTE: u8 = 0x47;
+
+/// Value of the byte following a section when the rest of the payload is stuffing
+const STUFFING_BYTE: u8 = 0xFF;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TsPacket<'a> {
+    pub transport_error_indicator: bool,
+    pub payload_unit_start_indicator: bool,
+    pub transport_priority: bool,
+    pub pid: u16,
+    pub scrambling_control: u8,
+    /// Incremented for every packet with a payload on a PID, wrapping after 15.
+    pub continuity_counter: u8,
+    pub adaptation_field: Option<AdaptationField>,
+    /// Unset for packets only holding an adaptation field.
+    pub payload: Option<&'a [u8]>,
+}
+
+impl<'a> TsPacket<'a> {
+    pub fn from_buf(buf: &'a [u8]) -> Result<TsPacket<'a>, TsError> {
+        if buf.len() < TS_PACKET_LENGTH {
+            return Err(TsError::TooShort);
+        }
+        if buf[0] != SYNC_BYTE {
+            return Err(TsError::BadSyncByte);
+        }
+
+        let transport_error_indicator = (buf[1] & 0b1000_0000) != 0;
+        let payload_unit_start_indicator = (buf[1] & 0b0100_0000) != 0;
+        let transport_priority = (buf[1] & 0b0010_0000) != 0;
+        let pid = u16::from_be_bytes([buf[1] & 0b0001_1111, buf[2]]);
+        let scrambling_control = (buf[3] & 0b1100_0000) >> 6;
+        let adaptation_field_control = (buf[3] & 0b0011_0000) >> 4;
+        let continuity_counter = buf[3] & 0b0000_1111;
+
+        let has_adaptation_field = (adaptation_field_control & 0b10) != 0;
+        let has_payload = (adaptation_field_control & 0b01) != 0;
+
+        let mut payload_start = 4;
+        let mut adaptation_field = None;
+        if has_adaptation_field {
+            let adaptation_field_length = buf[4] as usize;
+            payload_start += 1 + adaptation_field_length;
+            if payload_start > TS_PACKET_LENGTH {
+                return Err(TsError::BadAdaptationFieldLength);
+            }
+            adaptation_field = Some(AdaptationField::from_buf(&buf[5..payload_start])?);
+        }
+
+        Ok(TsPacket {
+            transport_error_indicator,
+            payload_unit_start_indicator,
+            transport_priority,
+            pid,
+            scrambling_control,
+            continuity_counter,
+            adaptation_field,
+            payload: has_payload.then(|| &buf[payload_start..TS_PACKET_LENGTH]),
+        })
+    }
+}
+
+/// Optional data before the payload of a TS packet, as defined in ISO/IEC 13818-1 p22 (2.4.3.4)
+///
+/// Only the most useful fields are read, others like the OPCR or splicing point are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AdaptationField {
+    /// The continuity counter or the PCR time base restart with this packet.
+    pub discontinuity_indicator: bool,
+    /// Decoding can start from this packet, like on a video key frame. Useful for seeking.
+    pub random_access_indicator: bool,
+    pub elementary_stream_priority_indicator: bool,
+    pub pcr: Option<Pcr>,
+}
+
+impl AdaptationField {
+    /// Read from the bytes following the adaptation field length.
+    pub fn from_buf(buf: &[u8]) -> Result<AdaptationField, TsError> {
+        // Stuffing only
+        let Some(&flags) = buf.first() else {
+            return Ok(AdaptationField::default());
+        };
+
+        let pcr_flag = (flags & 0b0001_0000) != 0;
+        let pcr = if pcr_flag {
+            let bytes = buf.get(1..7).ok_or(TsError::BadAdaptationFieldLength)?;
+            Some(Pcr::from_buf(bytes))
+        } else {
+            None
+        };
+
+        Ok(AdaptationField {
+            discontinuity_indicator: (flags & 0b1000_0000) != 0,
+            random_access_indicator: (flags & 0b0100_0000) != 0,
+            elementary_stream_priority_indicator: (flags & 0b0010_0000) != 0,
+            pcr,
+        })
+    }
+}
+
+/// Program Clock Reference, sampled from the 27MHz clock of the encoder.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Pcr {
+    /// In 90kHz units, like PTS and DTS.
+    pub base: u64,
+    /// In 27MHz units, from 0 to 299.
+    pub extension: u16,
+}
+
+impl Pcr {
+    fn from_buf(buf: &[u8]) -> Pcr {
+        let base = ((buf[0] as u64) << 25)
+            | ((buf[1] as u64) << 17)
+            | ((buf[2] as u64) << 9)
+            | ((buf[3] as u64) << 1)
+            | ((buf[4] as u64) >> 7);
+        let extension = u16::from_be_bytes([buf[4] & 0b0000_0001, buf[5]]);
+        Pcr { base, extension }
+    }
+
+    /// Full value, in 27MHz units.
+    pub fn ticks(&self) -> u64 {
+        self.base * 300 + self.extension as u64
+    }
+
+    pub fn to_nanos(&self) -> u64 {
+        self.ticks() * 1000 / 27
+    }
+}
+
+//
+// -----
+
+/// Reassembles the sections carried over the TS packets of a single PID.
+///
+/// Feed every packet of the PID to [push](Self::push), the returned sections can be parsed with [`Packet::from_buf`](crate::mpeg::Packet::from_buf).
+/// If a packet is missing, the section it was part of is dropped.
+#[derive(Default)]
+pub struct SectionDepacketizer {
+    buffer: Vec<u8>,
+    /// Whether the start of the section in `buffer` was received
+    in_section: bool,
+    continuity_counter: Option<u8>,
+}
+
+impl SectionDepacketizer {
+    pub fn new() -> SectionDepacketizer {
+        SectionDepacketizer::default()
+    }
+
+    /// Add a received packet. Returns every section completed by this packet.
+    pub fn push(&mut self, packet: &TsPacket) -> Vec<Vec<u8>> {
+        let mut sections = Vec::new();
+
+        // Packets without payload don't increment the counter
+        let Some(payload) = packet.payload else {
+            return sections;
+        };
+        if packet.transport_error_indicator {
+            self.reset();
+            return sections;
+        }
+
+        if let Some(previous) = self.continuity_counter {
+            if packet.continuity_counter == previous {
+                // Duplicate packet, already handled
+                return sections;
+            }
+            if packet.continuity_counter != (previous + 1) & 0x0F {
+                self.reset();
+            }
+        }
+        self.continuity_counter = Some(packet.continuity_counter);
+
+        if packet.payload_unit_start_indicator {
+            let Some((&pointer_field, payload)) = payload.split_first() else {
+                self.reset();
+                return sections;
+            };
+            let pointer_field = pointer_field as usize;
+            if pointer_field > payload.len() {
+                self.reset();
+                return sections;
+            }
+
+            // Bytes before the pointed position end the previous section
+            let (end, start) = payload.split_at(pointer_field);
+            if self.in_section {
+                self.buffer.extend_from_slice(end);
+                self.take_sections(&mut sections);
+            }
+
+            self.buffer.clear();
+            self.buffer.extend_from_slice(start);
+            self.in_section = true;
+        } else if self.in_section {
+            self.buffer.extend_from_slice(payload);
+        }
+
+        if self.in_section {
+            self.take_sections(&mut sections);
+        }
+        sections
+    }
+
+    /// Move all complete sections at the start of the buffer over to `sections`.
+    fn take_sections(&mut self, sections: &mut Vec<Vec<u8>>) {
+        loop {
+            match self.buffer.first() {
+                // Nothing more in this packet, wait for the next section to start
+                None | Some(&STUFFING_BYTE) => {
+                    self.buffer.clear();
+                    self.in_section = false;
+                    return;
+                }
+                _ => {}
+            }
+            if self.buffer.len() < 3 {
+                return;
+            }
+            let section_length =
+                3 + u16::from_be_bytes([self.buffer[1] & 0b0000_1111, self.buffer[2]]) as usize;
+            if self.buffer.len() < section_length {
+                return;
+            }
+            sections.push(self.buffer.drain(..section_length).collect());
+        }
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.in_section = false;
+        self.continuity_counter = None;
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts_packet(
+        payload_unit_start_indicator: bool,
+        continuity_counter: u8,
+        payload: &[u8],
+    ) -> Vec<u8> {
+        let mut buf = vec![
+            SYNC_BYTE,
+            if payload_unit_start_indicator {
+                0x40
+            } else {
+                0x00
+            },
+            0x11,
+            0x10 | continuity_counter,
+        ];
+        buf.extend_from_slice(payload);
+        buf.resize(TS_PACKET_LENGTH, STUFFING_BYTE);
+        buf
+    }
+
+    fn long_section() -> Vec<u8> {
+        let mut section = vec![0x42, 0xF0, 0xF7];
+        section.extend((0..247).map(|i| i as u8));
+        section
+    }
+
+    #[test]
+    fn packet_header() {
+        let buf = ts_packet(true, 5, &[0x00]);
+        let packet = TsPacket::from_buf(&buf).unwrap();
+
+        assert_eq!(packet.pid, 0x0011);
+        assert!(packet.payload_unit_start_indicator);
+        assert_eq!(packet.continuity_counter, 5);
+        assert_eq!(packet.payload.unwrap().len(), 184);
+
+        assert!(TsPacket::from_buf(&buf[..100]).is_err());
+    }
+
+    #[test]
+    fn pcr() {
+        let mut buf = vec![
+            SYNC_BYTE, 0x01, 0x00, 0x20, // Adaptation field only
+            0xB7, 0x50, // Length, random access and PCR flags
+            0x91, 0xA2, 0xB3, 0xC4, 0xFF, 0x23, // PCR
+        ];
+        buf.resize(TS_PACKET_LENGTH, STUFFING_BYTE);
+        let packet = TsPacket::from_buf(&buf).unwrap();
+
+        assert_eq!(packet.payload, None);
+        let adaptation_field = packet.adaptation_field.unwrap();
+        assert!(adaptation_field.random_access_indicator);
+        assert!(!adaptation_field.discontinuity_indicator);
+        let pcr = adaptation_field.pcr.unwrap();
+        assert_eq!(pcr.base, 0x1_2345_6789);
+        assert_eq!(pcr.extension, 0x123);
+        assert_eq!(pcr.to_nanos(), 54_296_870_510_777);
+    }
+
+    #[test]
+    fn section_over_two_packets() {
+        let section = long_section();
+        let mut first = vec![0x00];
+        first.extend_from_slice(&section[..183]);
+        let first = ts_packet(true, 0, &first);
+        let second = ts_packet(false, 1, &section[183..]);
+
+        let mut depacketizer = SectionDepacketizer::new();
+        assert!(
+            depacketizer
+                .push(&TsPacket::from_buf(&first).unwrap())
+                .is_empty()
+        );
+        let sections = depacketizer.push(&TsPacket::from_buf(&second).unwrap());
+        assert_eq!(sections, [section]);
+    }
+
+    #[test]
+    fn discontinuity_drops_section() {
+        let section = long_section();
+        let mut first = vec![0x00];
+        first.extend_from_slice(&section[..183]);
+        let first = ts_packet(true, 0, &first);
+        let second = ts_packet(false, 2, &section[183..]);
+
+        let mut depacketizer = SectionDepacketizer::new();
+        depacketizer.push(&TsPacket::from_buf(&first).unwrap());
+        assert!(
+            depacketizer
+                .push(&TsPacket::from_buf(&second).unwrap())
+                .is_empty()
+        );
+    }
+}
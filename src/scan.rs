@@ -1,40 +1,118 @@
 //! Helpers for scanning a DVB system for channels or other information.
 
-use std::{collections::HashMap, path::Path, time::Duration};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{Display, Formatter, Result as FmtResult},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use nix::errno::Errno;
+use rdvb_os_linux::frontend::data::FeStatus;
 
 use crate::{
-    bands::ChannelParameters,
-    demux::{PidTableIdPair, receive_multiple_single_packets, receive_single_packet},
+    bands::{ChannelParameters, Lnb, Region, SatelliteTransponder},
+    demux::{
+        Demux, PidTableIdPair, is_bad_section, receive_multiple_tables, receive_single_packet,
+    },
+    error::{DemuxError, FetchPmtError, FrontendError, ScanError, SiParseError},
     frontend::{
-        DeliverySystem, Frontend,
+        DeliverySystem, FrequencyUnit, Frontend, TuneRequest, poll_until,
         properties::{get::SignalStrength, set::BandwidthHz},
+        wait_for_lock_outcome,
+    },
+    mpeg::{
+        Packet, Pid,
+        assembler::SectionAssembler,
+        descriptors::{
+            DescriptorLoop, t2_delivery_system::T2DeliverySystem,
+            terrestrial_delivery_system::TerrestrialDeliverySystem,
+        },
     },
     si::{
+        TableId,
+        bat::{BouquetAssociation, PID as BAT_PID, TABLE_ID as BAT_TABLE_ID},
         nit::{ACTUAL_NETWORK_TABLE_ID as NIT_ACTUAL_NETWORK_TABLE_ID, NetworkInformation},
-        pat::{PID as PAT_PID, PatValue, TABLE_ID as PAT_TABLE_ID, parse_pat},
+        pat::{PID as PAT_PID, PatValue, ProgramAssociation, TABLE_ID as PAT_TABLE_ID, parse_pat},
         pmt::{ProgramMap, TABLE_ID as PMT_TABLE_ID},
         sdt::{
-            ACTUAL_TRANSPORT_TABLE_ID as SDT_ACTUAL_TRANSPORT_TABLE_ID, PID as SDT_PID,
+            ACTUAL_TRANSPORT_TABLE_ID as SDT_ACTUAL_TRANSPORT_TABLE_ID,
+            OTHER_TRANSPORT_TABLE_ID as SDT_OTHER_TRANSPORT_TABLE_ID, PID as SDT_PID,
             ServiceDescription,
         },
     },
 };
 
+const SIGNAL_GRACE: Duration = Duration::from_millis(300);
 const LOCK_TIMEOUT: Duration = Duration::from_secs(1);
 const PAT_TIMEOUT: Duration = Duration::from_secs(3); // A bit longer as DVB-T2 seems to send these less often
+const SI_TIMEOUT: Duration = Duration::from_secs(11); // NIT may only be sent every 10 seconds
 
 /// A single physical transponder emitting DVB data out over a frequency for a system.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Transponder {
+    /// Unique per transponder, as read from the PAT.
+    pub transport_stream_id: u16,
     pub frequency: u32,
     pub system: DeliverySystem,
-    pub bandwidth: BandwidthHz,
+    /// Unset for satellite transponders
+    pub bandwidth: Option<BandwidthHz>,
+    /// Only set for satellite transponders
+    pub symbol_rate: Option<u32>,
     pub strength: SignalStrength,
     pub program_map: Vec<ProgramMap>,
+    /// Assembled from every section of the SDT.
     pub service_description: ServiceDescription,
+    /// Assembled from every section of the NIT.
     pub network_information: NetworkInformation,
 }
 
+impl Transponder {
+    /// Center frequency in Hz advertised for this transponder by its own NIT, for terrestrial transponders.
+    ///
+    /// This may differ a bit from [frequency](Self::frequency), which is what the frontend was tuned to.
+    pub fn own_center_frequency(&self) -> Option<u32> {
+        self.network_information
+            .elements
+            .iter()
+            .filter(|e| e.transport_stream_id == self.transport_stream_id)
+            .find_map(|e| e.transport_descriptors.find::<TerrestrialDeliverySystem>())
+            .map(TerrestrialDeliverySystem::frequency)
+    }
+
+    /// T2 delivery system advertised for this transponder by its own NIT, giving the PLP it was received on.
+    pub fn own_t2_delivery_system(&self) -> Option<&T2DeliverySystem> {
+        self.network_information
+            .elements
+            .iter()
+            .filter(|e| e.transport_stream_id == self.transport_stream_id)
+            .find_map(|e| e.transport_descriptors.find::<T2DeliverySystem>())
+    }
+}
+
+impl Display for Transponder {
+    /// Single line summary, like `474.166 MHz, DVB-T, 8 MHz, strength 78%, 5 services`
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let frequency_mhz = match self.system.frequency_unit() {
+            FrequencyUnit::KHz => self.frequency as f64 / 1_000.0,
+            FrequencyUnit::Hz => self.frequency as f64 / 1_000_000.0,
+        };
+        write!(f, "{} MHz, {}", frequency_mhz, self.system)?;
+        if let Some(bandwidth) = self.bandwidth {
+            write!(f, ", {}", bandwidth)?;
+        }
+        if let Some(symbol_rate) = self.symbol_rate {
+            write!(f, ", {} kBd", symbol_rate / 1_000)?;
+        }
+        write!(
+            f,
+            ", strength {}, {} services",
+            self.strength,
+            self.service_description.services.len()
+        )
+    }
+}
+
 /// Scans a whole system, like DVB-T or DVB-S. This returns a list of valid transponders.
 pub fn scan_system<F, T>(
     frontend: &mut Frontend,
@@ -42,7 +120,7 @@ pub fn scan_system<F, T>(
     system: DeliverySystem,
     demux_path: &Path,
     cb: F,
-) -> Vec<Transponder>
+) -> Result<Vec<Transponder>, ScanError>
 where
     F: Fn(usize),
     T: Iterator<Item = ChannelParameters>,
@@ -50,24 +128,501 @@ where
     // Indexed by transport stream ID (unique per transponder)
     let mut found_transponders: HashMap<u16, Transponder> = HashMap::new();
 
-    for channel in frequencies {
-        scan_channel(
-            frontend,
-            demux_path,
-            system,
+    for progress in scan_system_iter(frontend, frequencies, system, demux_path) {
+        if let Some(transponder) = progress?.transponder {
+            found_transponders.insert(transponder.transport_stream_id, transponder);
+        }
+        cb(found_transponders.len())
+    }
+
+    Ok(found_transponders.into_values().collect())
+}
+
+/// Scans all terrestrial channels of a region, with the defaults of its [ScanProfile](crate::bands::ScanProfile).
+///
+/// Each delivery system of the profile that the frontend supports is scanned over the whole band plan, one after the other.
+/// `cb` is called with the amount of transponders found so far by the current system.
+/// To scan other bands or systems, use [`scan_system`] directly.
+pub fn scan_region<F>(
+    frontend: &mut Frontend,
+    demux_path: &Path,
+    region: Region,
+    cb: F,
+) -> Result<Vec<Transponder>, ScanError>
+where
+    F: Fn(usize),
+{
+    let profile = region.scan_profile();
+    let supported = frontend.list_systems().map_err(ScanError::Frontend)?;
+
+    // Indexed by transport stream ID, in case the same one is found with several systems
+    let mut found_transponders: HashMap<u16, Transponder> = HashMap::new();
+
+    for &system in profile.systems.iter().filter(|s| supported.contains(*s)) {
+        for transponder in scan_system(frontend, profile.band_plan.iter(), system, demux_path, &cb)?
+        {
+            match found_transponders.get(&transponder.transport_stream_id) {
+                Some(prev) if prev.strength >= transponder.strength => {}
+                _ => {
+                    found_transponders.insert(transponder.transport_stream_id, transponder);
+                }
+            }
+        }
+    }
+
+    Ok(found_transponders.into_values().collect())
+}
+
+/// Scans a whole system like [`scan_system`], but yields after each channel so results can be shown as they come.
+///
+/// The same transponder may be yielded more than once, if it is received better on a later channel.
+pub fn scan_system_iter<T>(
+    frontend: &mut Frontend,
+    frequencies: T,
+    system: DeliverySystem,
+    demux_path: &Path,
+) -> impl Iterator<Item = Result<ScanProgress, ScanError>>
+where
+    T: Iterator<Item = ChannelParameters>,
+{
+    ScanSystemIter {
+        frontend,
+        frequencies,
+        system,
+        demux_path,
+        found_transponders: HashMap::new(),
+        known_centers: HashMap::new(),
+    }
+}
+
+/// State of a scan after a channel was scanned, see [`scan_system_iter`].
+#[derive(Debug)]
+pub struct ScanProgress {
+    pub current_frequency: u32,
+    /// Set if a new transponder was found on this channel, or if it receives an already found one better.
+    pub transponder: Option<Transponder>,
+}
+
+struct ScanSystemIter<'a, T> {
+    frontend: &'a mut Frontend,
+    frequencies: T,
+    system: DeliverySystem,
+    demux_path: &'a Path,
+    found_transponders: HashMap<u16, Transponder>,
+    /// Center frequencies advertised by the NIT of found transponders, with their transport stream ID
+    known_centers: HashMap<u32, u16>,
+}
+
+impl<T> ScanSystemIter<'_, T> {
+    /// Tune to a channel on which an already found transponder is advertised, only reading its PAT.
+    ///
+    /// Returns `None` if nothing was received, or if it is another transport stream that needs a full scan.
+    /// If it is the expected one, its tables aren't received again: only its frequency is updated, if it is received better here.
+    fn check_known_transponder(
+        &mut self,
+        channel: &ChannelParameters,
+        transport_stream_id: u16,
+    ) -> Result<Option<ScanProgress>, ScanError> {
+        self.frontend
+            .tune(channel.frequency, self.system, channel.bandwidth)
+            .map_err(ScanError::Frontend)?;
+        let received = receive_pat(self.frontend, self.demux_path)?.map(|p| p.transport_stream_id);
+        if received.is_some_and(|id| id != transport_stream_id) {
+            return Ok(None);
+        }
+
+        let mut progress = ScanProgress {
+            current_frequency: channel.frequency,
+            transponder: None,
+        };
+        if received.is_none() {
+            return Ok(Some(progress));
+        }
+        let strength = self
+            .frontend
+            .signal_strength()
+            .map_err(ScanError::Frontend)?;
+        let transponder = self
+            .found_transponders
+            .get_mut(&transport_stream_id)
+            .expect("known centers are only added for found transponders");
+        if strength > transponder.strength {
+            transponder.frequency = channel.frequency;
+            transponder.strength = strength;
+            progress.transponder = Some(transponder.clone());
+        }
+        Ok(Some(progress))
+    }
+}
+
+impl<T: Iterator<Item = ChannelParameters>> Iterator for ScanSystemIter<'_, T> {
+    type Item = Result<ScanProgress, ScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let channel = self.frequencies.next()?;
+        // A quick look at the PAT is enough to tell if this is the same transport stream again
+        if let Some(id) = advertised_transport_stream(&self.known_centers, &channel) {
+            match self.check_known_transponder(&channel, id) {
+                Ok(Some(progress)) => return Some(Ok(progress)),
+                Ok(None) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        let found = match scan_channel(
+            self.frontend,
+            self.demux_path,
+            self.system,
             channel.frequency,
             channel.bandwidth,
+            &mut self.found_transponders,
+        ) {
+            Ok(found) => found,
+            Err(e) => return Some(Err(e)),
+        };
+        let center = found.and_then(|id| {
+            let center = self.found_transponders[&id].own_center_frequency()?;
+            Some((center, id))
+        });
+        if let Some((center, id)) = center {
+            self.known_centers.insert(center, id);
+        }
+
+        Some(Ok(ScanProgress {
+            current_frequency: channel.frequency,
+            transponder: found.map(|id| self.found_transponders[&id].clone()),
+        }))
+    }
+}
+
+/// Return the transport stream ID of a found transponder whose NIT advertises a center frequency on this channel.
+fn advertised_transport_stream(
+    known_centers: &HashMap<u32, u16>,
+    channel: &ChannelParameters,
+) -> Option<u16> {
+    known_centers
+        .iter()
+        .find(|&(&center, _)| same_channel(center, channel.frequency, channel.bandwidth))
+        .map(|(_, &id)| id)
+}
+
+/// Frequency and PLP pairs advertised by a T2 delivery system descriptor that were received by scanning `frequency`.
+fn received_t2_pairs(
+    t2: &T2DeliverySystem,
+    frequency: u32,
+    bandwidth: BandwidthHz,
+) -> Vec<(u32, Option<u8>)> {
+    let mut pairs = vec![(frequency, Some(t2.plp_id))];
+    pairs.extend(
+        t2.frequencies()
+            .filter(|&f| same_channel(f, frequency, bandwidth))
+            .map(|f| (f, Some(t2.plp_id))),
+    );
+    pairs
+}
+
+/// Return `true` if both frequencies are less than half a channel apart.
+fn same_channel(a: u32, b: u32, bandwidth: BandwidthHz) -> bool {
+    a.abs_diff(b) < bandwidth.value() / 2
+}
+
+/// Scans a terrestrial network starting from a single known frequency.
+///
+/// Instead of sweeping a whole band, every frequency advertised by the NIT of found transponders is tuned to.
+/// This is much faster, and finds transponders outside of the usual raster.
+/// For DVB-T2, each transport stream is tuned to with the PLP given by its [T2DeliverySystem] descriptor.
+pub fn scan_network<F>(
+    frontend: &mut Frontend,
+    demux_path: &Path,
+    system: DeliverySystem,
+    start_frequency: u32,
+    bandwidth: BandwidthHz,
+    cb: F,
+) -> Result<Vec<Transponder>, ScanError>
+where
+    F: Fn(usize),
+{
+    let mut found_transponders: HashMap<u16, Transponder> = HashMap::new();
+    // Frequency and PLP, a T2 frequency may carry several transport streams
+    let mut scanned: HashSet<(u32, Option<u8>)> = HashSet::new();
+    let mut pending = VecDeque::from([(start_frequency, bandwidth, None)]);
+
+    while let Some((frequency, bandwidth, plp)) = pending.pop_front() {
+        if !scanned.insert((frequency, plp)) {
+            continue;
+        }
+
+        match plp {
+            Some(plp) => {
+                TuneRequest::new(frontend.align_frequency(frequency), system)
+                    .bandwidth(bandwidth)
+                    .plp(plp as u32)
+                    .execute(frontend)
+                    .map_err(ScanError::Frontend)?;
+                scan_tuned(
+                    frontend,
+                    demux_path,
+                    system,
+                    frequency,
+                    Some(bandwidth),
+                    None,
+                    &mut found_transponders,
+                )?;
+            }
+            None => {
+                let found = scan_channel(
+                    frontend,
+                    demux_path,
+                    system,
+                    frequency,
+                    bandwidth,
+                    &mut found_transponders,
+                )?;
+                // Its T2 descriptor would queue this same channel again, this time with the PLP that was just received
+                if let Some(t2) =
+                    found.and_then(|id| found_transponders[&id].own_t2_delivery_system())
+                {
+                    scanned.extend(received_t2_pairs(t2, frequency, bandwidth));
+                }
+            }
+        }
+        cb(found_transponders.len());
+
+        // Queue everything the network knows about that wasn't tuned to yet
+        let elements = found_transponders
+            .values()
+            .flat_map(|t| t.network_information.elements.iter());
+        for element in elements {
+            let descriptors = &element.transport_descriptors;
+            let t2 = descriptors
+                .find::<T2DeliverySystem>()
+                .filter(|_| system == DeliverySystem::DvbT2);
+            let plp = t2.map(|t2| t2.plp_id);
+
+            let mut queue = |frequency: u32, advertised: Option<BandwidthHz>| {
+                if !scanned.contains(&(frequency, plp)) {
+                    pending.push_back((frequency, advertised.unwrap_or(bandwidth), plp));
+                }
+            };
+            for delivery in descriptors.filter::<TerrestrialDeliverySystem>() {
+                queue(delivery.frequency(), delivery.bandwidth_hz());
+            }
+            if let Some(t2) = t2 {
+                for frequency in t2.frequencies() {
+                    queue(frequency, t2.bandwidth_hz());
+                }
+            }
+        }
+    }
+
+    Ok(found_transponders.into_values().collect())
+}
+
+/// Scans a list of known transponders from a satellite, received through an LNB. This returns a list of valid transponders.
+pub fn scan_satellite<F, T>(
+    frontend: &mut Frontend,
+    transponders: T,
+    lnb: &Lnb,
+    demux_path: &Path,
+    cb: F,
+) -> Result<Vec<Transponder>, ScanError>
+where
+    F: Fn(usize),
+    T: Iterator<Item = SatelliteTransponder>,
+{
+    let mut found_transponders: HashMap<u16, Transponder> = HashMap::new();
+
+    for transponder in transponders {
+        let tuning = transponder.tuning(lnb);
+        frontend
+            .tune_satellite(
+                tuning.frequency,
+                transponder.system,
+                transponder.symbol_rate,
+                tuning.voltage,
+                tuning.tone,
+            )
+            .map_err(ScanError::Frontend)?;
+        scan_tuned(
+            frontend,
+            demux_path,
+            transponder.system,
+            transponder.frequency_khz,
+            None,
+            Some(transponder.symbol_rate),
             &mut found_transponders,
-        );
+        )?;
         cb(found_transponders.len())
     }
 
-    found_transponders.into_values().collect()
+    Ok(found_transponders.into_values().collect())
+}
+
+/// Collect the SDT of the other transport streams of the network, as broadcast by the one currently tuned to.
+///
+/// This gives the services of a whole network without tuning to each transponder, when the network broadcasts them.
+/// How many tables to expect isn't known, so this keeps collecting for `duration` and returns all tables that were complete by then.
+pub fn receive_other_services(
+    demux_path: &Path,
+    duration: Duration,
+) -> Result<Vec<ServiceDescription>, DemuxError> {
+    receive_tables(
+        demux_path,
+        SDT_PID,
+        SDT_OTHER_TRANSPORT_TABLE_ID,
+        duration,
+        ServiceDescription::from_packets,
+    )
+}
+
+/// Collect the BAT of all bouquets broadcast by the transport stream currently tuned to.
+///
+/// Like [receive_other_services], this keeps collecting for `duration`, as many networks don't broadcast any bouquet at all.
+pub fn receive_bouquets(
+    demux_path: &Path,
+    duration: Duration,
+) -> Result<Vec<BouquetAssociation>, DemuxError> {
+    receive_tables(
+        demux_path,
+        BAT_PID,
+        BAT_TABLE_ID,
+        duration,
+        BouquetAssociation::from_packets,
+    )
+}
+
+/// Collect all tables with given table ID for `duration`, each table identified by the ID extension of its sections.
+fn receive_tables<T>(
+    demux_path: &Path,
+    pid: Pid,
+    table_id: TableId,
+    duration: Duration,
+    parse: fn(&[Packet]) -> Result<T, SiParseError>,
+) -> Result<Vec<T>, DemuxError> {
+    let mut demux = Demux::new_nonblocking(demux_path).map_err(DemuxError::Open)?;
+    demux.filter_continuous(pid, Some(table_id), None)?;
+    let mut collector = TableCollector::new(parse);
+
+    let deadline = Instant::now() + duration;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() || !demux.poll(remaining).map_err(DemuxError::Read)? {
+            break;
+        }
+        let packet = match demux.read_one_packet() {
+            Ok(packet) => packet,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+            // Already counted in the demux errors, the next sections may be fine
+            Err(e) if is_bad_section(&e) => continue,
+            Err(e) => return Err(DemuxError::Read(e)),
+        };
+        collector.push(packet);
+    }
+    demux.stop()?;
+
+    Ok(collector.finish())
+}
+
+/// Tables being received by [receive_tables], each one identified by the ID extension of its sections.
+struct TableCollector<T> {
+    parse: fn(&[Packet]) -> Result<T, SiParseError>,
+    // Indexed by table ID extension (like the transport stream ID of an SDT), each one is a separate table
+    assemblers: HashMap<u16, SectionAssembler>,
+    complete: HashMap<u16, T>,
+}
+
+impl<T> TableCollector<T> {
+    fn new(parse: fn(&[Packet]) -> Result<T, SiParseError>) -> TableCollector<T> {
+        TableCollector {
+            parse,
+            assemblers: HashMap::new(),
+            complete: HashMap::new(),
+        }
+    }
+
+    fn push(&mut self, packet: Packet) {
+        let identifier = packet.header.identifier;
+        if self.complete.contains_key(&identifier) {
+            return;
+        }
+        let assembler = self.assemblers.entry(identifier).or_default();
+        if assembler.push(packet) {
+            let sections = self
+                .assemblers
+                .remove(&identifier)
+                .and_then(SectionAssembler::finish)
+                .expect("assembler reported a complete table");
+            // A corrupt table is dropped, it may be received properly when repeated
+            if let Ok(table) = (self.parse)(&sections) {
+                self.complete.insert(identifier, table);
+            }
+        }
+    }
+
+    /// Return all tables that were complete and valid
+    fn finish(self) -> Vec<T> {
+        self.complete.into_values().collect()
+    }
+}
+
+/// Receive the PMT of a single service on the transport stream currently tuned to, to get its audio and video PIDs.
+///
+/// The PAT is read first to find the PID of the PMT. `timeout` covers both tables.
+pub fn fetch_pmt(
+    demux_path: &Path,
+    service_id: u16,
+    timeout: Duration,
+) -> Result<ProgramMap, FetchPmtError> {
+    let deadline = Instant::now() + timeout;
+
+    let packet = receive_single_packet(demux_path, PAT_PID, Some(PAT_TABLE_ID), Some(timeout))
+        .map_err(FetchPmtError::Demux)?;
+    let pat = parse_pat(&packet).map_err(FetchPmtError::Si)?;
+    let pid = pat
+        .elements
+        .iter()
+        .find_map(|e| match e.value {
+            PatValue::ProgramMap(pid) if e.program_number == service_id => Some(pid),
+            _ => None,
+        })
+        .ok_or(FetchPmtError::ServiceNotFound(service_id))?;
+
+    let mut demux = Demux::new_nonblocking(demux_path)
+        .map_err(|e| FetchPmtError::Demux(DemuxError::Open(e)))?;
+    demux
+        .filter_continuous(pid, Some(PMT_TABLE_ID), None)
+        .map_err(FetchPmtError::Demux)?;
+
+    // Several services may share the same PMT PID, wait for the right one
+    let packet = loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero()
+            || !demux
+                .poll(remaining)
+                .map_err(|e| FetchPmtError::Demux(DemuxError::Read(e)))?
+        {
+            demux.stop().map_err(FetchPmtError::Demux)?;
+            return Err(FetchPmtError::Demux(DemuxError::Timeout));
+        }
+        let packet = match demux.read_one_packet() {
+            Ok(packet) => packet,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+            Err(e) => return Err(FetchPmtError::Demux(DemuxError::Read(e))),
+        };
+        if packet.header.identifier == service_id {
+            break packet;
+        }
+    };
+    demux.stop().map_err(FetchPmtError::Demux)?;
+
+    ProgramMap::from_packet(&packet).map_err(FetchPmtError::Si)
 }
 
 /// Scan a single channel (as in frequency, not TV channel) for a given system to look for a valid transponder.
 ///
 /// This also checks for duplicate transponders.
+/// Returns the transport stream ID of the transponder if one was added to or replaced in `found_transponders`.
+/// A frequency that can't be received properly is skipped, only problems with the adapter itself are returned as errors.
 pub fn scan_channel(
     frontend: &mut Frontend,
     demux_path: &Path,
@@ -75,43 +630,94 @@ pub fn scan_channel(
     frequency: u32,
     bandwidth: BandwidthHz,
     found_transponders: &mut HashMap<u16, Transponder>,
-) {
+) -> Result<Option<u16>, ScanError> {
     // --- Tune to given frequency, bandwidth and system
-    // TODO: No need to set bandwidth and system every time, right ?
-    frontend.tune(frequency, system, bandwidth).unwrap();
+    // The frontend only sends the frequency if system and bandwidth didn't change since the last channel
+    frontend
+        .tune(frequency, system, bandwidth)
+        .map_err(ScanError::Frontend)?;
+
+    scan_tuned(
+        frontend,
+        demux_path,
+        system,
+        frequency,
+        Some(bandwidth),
+        None,
+        found_transponders,
+    )
+}
 
-    // --- Check every 100ms if the frontend got a lock on something
-    if !frontend.wait_for_lock(Some(LOCK_TIMEOUT), None).unwrap() {
-        return;
+/// Wait for a lock on what the frontend was just tuned to, and receive its PAT (Program Association Table) on its own.
+///
+/// Returns `None` if nothing usable is received on this frequency.
+fn receive_pat(
+    frontend: &mut Frontend,
+    demux_path: &Path,
+) -> Result<Option<ProgramAssociation>, ScanError> {
+    if !wait_for_transponder(|| frontend.status()).map_err(ScanError::Frontend)? {
+        return Ok(None);
     }
 
-    // --- Get the PAT (Program Association Table) on its own
     let packet =
         match receive_single_packet(demux_path, PAT_PID, Some(PAT_TABLE_ID), Some(PAT_TIMEOUT)) {
             Ok(v) => v,
-            Err(e) => match e.kind() {
-                // If receiving a valid packet times out, this probably means we're not receiving this transponder well enough, skip it
-                std::io::ErrorKind::TimedOut => return,
-                _ => panic!(),
-            },
+            // If receiving a valid packet times out, this probably means we're not receiving this transponder well enough, skip it
+            Err(e) => return skip_unless_device_gone(e).map(|_| None),
         };
-    let pat_entries = parse_pat(&packet);
-    let transport_stream_id = packet.header.identifier;
+    // A truncated table means reception is too poor to use this transponder
+    Ok(parse_pat(&packet).ok())
+}
+
+/// Poll `status` until the frontend locks, returning `false` if it doesn't.
+///
+/// Frequencies without any signal are given up on after [SIGNAL_GRACE], only the others get the whole [LOCK_TIMEOUT].
+fn wait_for_transponder<F>(mut status: F) -> Result<bool, FrontendError>
+where
+    F: FnMut() -> Result<FeStatus, FrontendError>,
+{
+    // --- Skip right away if there is no signal at all on this frequency
+    let has_signal = poll_until(
+        || {
+            let current = status()?;
+            Ok(current.has_signal() || current.has_lock())
+        },
+        Some(SIGNAL_GRACE),
+        None,
+    )?;
+    if !has_signal {
+        return Ok(false);
+    }
+
+    // --- Some signal is present, give the frontend time to get a lock on something
+    Ok(wait_for_lock_outcome(status, Some(LOCK_TIMEOUT), None)?.locked)
+}
+
+/// Look for a valid transponder on what the frontend was just tuned to.
+///
+/// Returns the transport stream ID of the transponder if one was added to or replaced in `found_transponders`.
+fn scan_tuned(
+    frontend: &mut Frontend,
+    demux_path: &Path,
+    system: DeliverySystem,
+    frequency: u32,
+    bandwidth: Option<BandwidthHz>,
+    symbol_rate: Option<u32>,
+    found_transponders: &mut HashMap<u16, Transponder>,
+) -> Result<Option<u16>, ScanError> {
+    let Some(pat) = receive_pat(frontend, demux_path)? else {
+        return Ok(None);
+    };
+    let transport_stream_id = pat.transport_stream_id;
 
     // --- Query signal strength and compare with previously received transponder if some
-    let strength = frontend.signal_strength().unwrap();
+    let strength = frontend.signal_strength().map_err(ScanError::Frontend)?;
     if let Some(prev_transponder) = found_transponders.get(&transport_stream_id) {
         // We picked up the same transponder twice, choose the one with the strongest signal
-        match strength.partial_cmp(&prev_transponder.strength) {
-            Some(o) => match o {
-                // This frequency has stronger reception, continue.
-                std::cmp::Ordering::Greater => {}
-                // The other was better or equal, don't continue with this one.
-                _ => return,
-            },
-            // Trying to compare either incompatible units or an outright failure.
-            // This should not happen unless I messed up or the adapter is hysteric
-            None => panic!(),
+        // The other was better or equal, don't continue with this one.
+        // Readings on different scales are still ordered, see ValueStat.
+        if strength <= prev_transponder.strength {
+            return Ok(None);
         }
     }
 
@@ -129,7 +735,7 @@ pub fn scan_channel(
     // Add all PIDs from PAT
     let mut nit_indices = Vec::new();
     let mut pmt_indices = Vec::new();
-    for entry in pat_entries {
+    for entry in pat.elements {
         match entry.value {
             PatValue::Network(pid) => {
                 nit_indices.push(all_pairs.len());
@@ -155,37 +761,234 @@ pub fn scan_channel(
         table_id: Some(SDT_ACTUAL_TRANSPORT_TABLE_ID),
     });
 
-    // Receive all packets
-    let packets = receive_multiple_single_packets(demux_path, all_pairs, None).unwrap();
+    // Receive all tables, as NIT and SDT may span multiple sections
+    let tables = match receive_multiple_tables(demux_path, all_pairs, Some(SI_TIMEOUT)) {
+        Ok(tables) => tables,
+        Err(e) => return skip_unless_device_gone(e),
+    };
 
     // Parse all NITs (there should only be one)
     // TODO: Could optimize this for a single packet...
     let mut nit = None;
     for index in nit_indices {
-        nit = Some(NetworkInformation::from_packet(&packets[index]));
+        let Ok(parsed) = NetworkInformation::from_packets(&tables[index]) else {
+            return Ok(None);
+        };
+        nit = Some(parsed);
     }
-    let nit = nit.unwrap();
+    // Without a NIT, this isn't a transponder that can be used, skip it
+    let Some(nit) = nit else {
+        return Ok(None);
+    };
 
-    // Parse all PMTs
+    // Parse all PMTs (always a single section)
     let mut program_map = Vec::new();
     for index in pmt_indices {
-        let pmt = ProgramMap::from_packet(&packets[index]);
+        let Ok(pmt) = ProgramMap::from_packet(&tables[index][0]) else {
+            return Ok(None);
+        };
         program_map.push(pmt);
     }
 
     // Parse SDT
-    let sdt = ServiceDescription::from_packet(&packets[sdt_index]);
+    let Ok(sdt) = ServiceDescription::from_packets(&tables[sdt_index]) else {
+        return Ok(None);
+    };
 
     found_transponders.insert(
         transport_stream_id,
         Transponder {
+            transport_stream_id,
             frequency,
             system,
             bandwidth,
+            symbol_rate,
             strength,
             program_map,
             service_description: sdt,
             network_information: nit,
         },
     );
+    Ok(Some(transport_stream_id))
+}
+
+/// Skip the transponder for errors caused by poor reception, like timeouts or corrupt sections, but stop the scan if the adapter is gone.
+fn skip_unless_device_gone(e: DemuxError) -> Result<Option<u16>, ScanError> {
+    if is_device_gone(&e) {
+        Err(ScanError::Demux(e))
+    } else {
+        Ok(None)
+    }
+}
+
+fn is_device_gone(e: &DemuxError) -> bool {
+    match e {
+        DemuxError::Open(_) => true,
+        DemuxError::Read(e) => e.raw_os_error() == Some(Errno::ENODEV as i32),
+        DemuxError::Start(errno)
+        | DemuxError::Stop(errno)
+        | DemuxError::SetFilter(errno)
+        | DemuxError::Pid(errno)
+        | DemuxError::BufferSize(errno) => *errno == Errno::ENODEV,
+        _ => false,
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::mpeg::descriptors::t2_delivery_system::{T2Cell, T2Parameters};
+
+    use super::*;
+
+    #[test]
+    fn empty_frequency_skipped_quickly() {
+        const FE_HAS_SIGNAL: u32 = 0x01;
+        const FE_HAS_LOCK: u32 = 0x1F;
+
+        let start = Instant::now();
+        assert!(!wait_for_transponder(|| Ok(FeStatus::from(0))).unwrap());
+        let elapsed = start.elapsed();
+        assert!(elapsed >= SIGNAL_GRACE);
+        assert!(elapsed < SIGNAL_GRACE + LOCK_TIMEOUT / 2);
+
+        // A signal that locks after a few reads isn't given up on
+        let mut reads = 0;
+        let locked = wait_for_transponder(|| {
+            reads += 1;
+            Ok(FeStatus::from(if reads < 3 {
+                FE_HAS_SIGNAL
+            } else {
+                FE_HAS_LOCK
+            }))
+        })
+        .unwrap();
+        assert!(locked);
+    }
+
+    #[test]
+    fn skippable_demux_errors() {
+        let io = |errno: Errno| DemuxError::Read(std::io::Error::from_raw_os_error(errno as i32));
+        let corrupt = std::io::Error::new(std::io::ErrorKind::InvalidData, "CRC");
+
+        assert!(matches!(
+            skip_unless_device_gone(DemuxError::Timeout),
+            Ok(None)
+        ));
+        assert!(matches!(
+            skip_unless_device_gone(DemuxError::Read(corrupt)),
+            Ok(None)
+        ));
+        assert!(matches!(
+            skip_unless_device_gone(io(Errno::EOVERFLOW)),
+            Ok(None)
+        ));
+        assert!(skip_unless_device_gone(io(Errno::ENODEV)).is_err());
+        assert!(skip_unless_device_gone(DemuxError::SetFilter(Errno::ENODEV)).is_err());
+    }
+
+    #[test]
+    fn corrupt_bouquet_skipped() {
+        let bouquet = |id: u8| {
+            let buf = [
+                0x4A, 0xF0, 0x1E, 0x00, id, 0xC1, 0x00, 0x00, // Header
+                0xF0, 0x06, // Bouquet descriptors
+                0x47, 0x04, b'T', b'N', b'T', b'!', // Bouquet name
+                0xF0, 0x0B, // Transport stream loop
+                0x00, 0x01, 0x20, 0xFA, 0xF0, 0x05, // TS 1 of network 0x20FA
+                0x41, 0x03, 0x01, 0x01, 0x01, // Service list with service 0x0101
+                0x00, 0x00, 0x00, 0x00, // CRC
+            ];
+            Packet::from_buf(&buf).unwrap()
+        };
+        // Bouquet descriptors running past the end of the section
+        let corrupt = [
+            0x4A, 0xF0, 0x0B, 0x00, 0x42, 0xC1, 0x00, 0x00, // Header
+            0xF0, 0x30, // Bouquet descriptors
+            0x00, 0x00, 0x00, 0x00, // CRC
+        ];
+
+        let mut collector = TableCollector::new(BouquetAssociation::from_packets);
+        collector.push(Packet::from_buf(&corrupt).unwrap());
+        collector.push(bouquet(0x43));
+        collector.push(bouquet(0x42));
+        let mut bouquets = collector.finish();
+        bouquets.sort_by_key(|b| b.bouquet_id);
+
+        assert_eq!(bouquets.len(), 2);
+        assert_eq!(bouquets[0].bouquet_id, 0x0042);
+        assert_eq!(bouquets[0].name(), Some("TNT!"));
+        assert_eq!(bouquets[1].bouquet_id, 0x0043);
+    }
+
+    #[test]
+    fn known_transport_stream_skipped() {
+        let channel = |frequency| ChannelParameters {
+            frequency,
+            bandwidth: BandwidthHz::_8MHz,
+            number: None,
+            display_prefix: "",
+        };
+        // Found on a neighbouring frequency, its NIT advertises a center offset by 166kHz
+        let known_centers = HashMap::from([(474_166_000, 0x0004)]);
+
+        assert_eq!(
+            advertised_transport_stream(&known_centers, &channel(474_000_000)),
+            Some(0x0004)
+        );
+        assert_eq!(
+            advertised_transport_stream(&known_centers, &channel(482_000_000)),
+            None
+        );
+        assert_eq!(
+            advertised_transport_stream(&HashMap::new(), &channel(474_000_000)),
+            None
+        );
+    }
+
+    #[test]
+    fn start_frequency_scanned_once() {
+        let t2 = T2DeliverySystem {
+            plp_id: 2,
+            t2_system_id: 0x8001,
+            parameters: Some(T2Parameters {
+                siso_miso: 0,
+                bandwidth: 0,
+                guard_interval: 0,
+                transmission_mode: 0,
+                other_frequency_flag: true,
+                tfs_flag: false,
+                cells: vec![T2Cell {
+                    cell_id: 1,
+                    // In units of 10Hz
+                    center_frequencies: vec![47_416_600],
+                    subcells: vec![],
+                }],
+            }),
+        };
+        // Also in the NIT, another cell on a different channel
+        let mut other_cell = t2.clone();
+        other_cell.parameters.as_mut().unwrap().cells[0].center_frequencies = vec![48_200_000];
+
+        let pairs = received_t2_pairs(&t2, 474_000_000, BandwidthHz::_8MHz);
+        assert!(pairs.contains(&(474_000_000, Some(2))));
+        assert!(pairs.contains(&(474_166_000, Some(2))));
+        assert_eq!(
+            received_t2_pairs(&other_cell, 474_000_000, BandwidthHz::_8MHz),
+            vec![(474_000_000, Some(2))]
+        );
+    }
+
+    #[test]
+    fn same_channel_offsets() {
+        // UK transmitters may be offset from the raster by 166kHz
+        assert!(same_channel(474_000_000, 474_166_000, BandwidthHz::_8MHz));
+        assert!(same_channel(474_166_000, 473_834_000, BandwidthHz::_8MHz));
+        assert!(!same_channel(474_000_000, 482_000_000, BandwidthHz::_8MHz));
+        assert!(!same_channel(474_000_000, 478_000_000, BandwidthHz::_8MHz));
+    }
 }
@@ -0,0 +1,138 @@
+use crate::{
+    error::SiParseError,
+    mpeg::{
+        Packet, Pid,
+        descriptors::{
+            Descriptor, DescriptorLoop, bouquet_name::BouquetName, service_list::ServiceList,
+        },
+    },
+    si::{SectionReader, TableId},
+};
+
+/// "Bouquet Association Table" fixed packet ID, as defined in `EN 300 468 V1.17.1`, p23
+///
+/// Shared with the SDT.
+pub const PID: Pid = Pid::SDT;
+
+/// "Bouquet Association Section" table ID, as defined in `EN 300 468 V1.17.1`, p24
+pub const TABLE_ID: TableId = TableId::Bat;
+
+/// BAT groups services of possibly several networks into a bouquet, like the channels of a commercial offer
+#[derive(Debug, Clone)]
+pub struct BouquetAssociation {
+    pub bouquet_id: u16,
+    pub bouquet_descriptors: Vec<Descriptor>,
+    pub elements: Vec<BatElement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BatElement {
+    pub transport_stream_id: u16,
+    pub original_network_id: u16,
+    pub transport_descriptors: Vec<Descriptor>,
+}
+
+impl BouquetAssociation {
+    // ETSI EN 300 468 page 28
+    pub fn from_packet(packet: &Packet) -> Result<BouquetAssociation, SiParseError> {
+        let mut reader = SectionReader::new("BAT", packet);
+
+        // Top 4 bits of the lengths are reserved
+        let bouquet_descriptors_length = reader.u16()? & 0x0FFF;
+        let bouquet_descriptors =
+            Descriptor::read_many(reader.bytes(bouquet_descriptors_length as usize)?);
+
+        // The loop runs until the end of the section anyway, so its length isn't used
+        let _transport_stream_loop_length = reader.u16()? & 0x0FFF;
+
+        let mut elements = Vec::new();
+
+        while !reader.is_done() {
+            let transport_stream_id = reader.u16()?;
+            let original_network_id = reader.u16()?;
+            let transport_descriptors_length = reader.u16()? & 0x0FFF;
+            let transport_descriptors =
+                Descriptor::read_many(reader.bytes(transport_descriptors_length as usize)?);
+
+            elements.push(BatElement {
+                transport_stream_id,
+                original_network_id,
+                transport_descriptors,
+            });
+        }
+
+        Ok(BouquetAssociation {
+            bouquet_id: packet.header.identifier,
+            bouquet_descriptors,
+            elements,
+        })
+    }
+
+    /// Parse a table spread over multiple sections, like the ones returned by a [SectionAssembler](crate::mpeg::assembler::SectionAssembler).
+    pub fn from_packets(packets: &[Packet]) -> Result<BouquetAssociation, SiParseError> {
+        let mut sections = packets.iter().map(Self::from_packet);
+        let mut bat = sections.next().ok_or(SiParseError {
+            table: "BAT",
+            offset: 0,
+        })??;
+        for section in sections {
+            let section = section?;
+            bat.bouquet_descriptors.extend(section.bouquet_descriptors);
+            bat.elements.extend(section.elements);
+        }
+        Ok(bat)
+    }
+
+    /// Name of this bouquet, if broadcast.
+    pub fn name(&self) -> Option<&str> {
+        self.bouquet_descriptors
+            .find::<BouquetName>()
+            .map(|d| d.name.as_str())
+    }
+
+    /// Return `true` if the service is part of this bouquet, according to the service lists of its transport streams.
+    pub fn contains_service(
+        &self,
+        original_network_id: u16,
+        transport_stream_id: u16,
+        service_id: u16,
+    ) -> bool {
+        self.elements
+            .iter()
+            .filter(|e| {
+                e.original_network_id == original_network_id
+                    && e.transport_stream_id == transport_stream_id
+            })
+            .flat_map(|e| e.transport_descriptors.filter::<ServiceList>())
+            .any(|l| l.services.iter().any(|s| s.service_id == service_id))
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bouquet() {
+        let buf = [
+            0x4A, 0xF0, 0x1E, 0x00, 0x42, 0xC1, 0x00, 0x00, // Header, bouquet 0x42
+            0xF0, 0x06, // Bouquet descriptors
+            0x47, 0x04, b'T', b'N', b'T', b'!', // Bouquet name
+            0xF0, 0x0B, // Transport stream loop
+            0x00, 0x01, 0x20, 0xFA, 0xF0, 0x05, // TS 1 of network 0x20FA
+            0x41, 0x03, 0x01, 0x01, 0x01, // Service list with service 0x0101
+            0x00, 0x00, 0x00, 0x00, // CRC
+        ];
+        let bat = BouquetAssociation::from_packet(&Packet::from_buf(&buf).unwrap()).unwrap();
+
+        assert_eq!(bat.bouquet_id, 0x0042);
+        assert_eq!(bat.name(), Some("TNT!"));
+        assert_eq!(bat.elements.len(), 1);
+        assert!(bat.contains_service(0x20FA, 0x0001, 0x0101));
+        assert!(!bat.contains_service(0x20FA, 0x0002, 0x0101));
+        assert!(!bat.contains_service(0x20FA, 0x0001, 0x0102));
+    }
+}
@@ -1,6 +1,320 @@
 //! "System Information" (I think), everything related to information contained in TS packets.
 
+pub mod bat;
 pub mod nit;
 pub mod pat;
 pub mod pmt;
 pub mod sdt;
+
+use crate::{
+    error::{SectionParseError, SiParseError},
+    mpeg::Packet,
+    si::{
+        bat::BouquetAssociation,
+        nit::NetworkInformation,
+        pat::{ProgramAssociation, parse_pat},
+        pmt::ProgramMap,
+        sdt::ServiceDescription,
+    },
+};
+
+//
+// -----
+
+/// A single section parsed by [parse_section], according to its table ID.
+#[derive(Debug)]
+pub enum ParsedSection {
+    Pat(ProgramAssociation),
+    Pmt(ProgramMap),
+    /// Either for the actual network or another one
+    Nit(NetworkInformation),
+    /// Either for the actual transport stream or another one
+    Sdt(ServiceDescription),
+    Bat(BouquetAssociation),
+    /// Table without a parser, left as-is
+    Other(Packet),
+}
+
+/// Parse a single section, including its header and CRC, without needing any hardware.
+///
+/// This is meant for sections that were captured or extracted from a recorded stream with a [SectionDepacketizer](crate::mpeg::ts::SectionDepacketizer).
+/// Tables spread over several sections should be assembled with the `from_packets` function of their type instead.
+pub fn parse_section(bytes: &[u8]) -> Result<ParsedSection, SectionParseError> {
+    let packet = Packet::from_buf(bytes).map_err(SectionParseError::Packet)?;
+    Ok(match TableId::from_u8(packet.header.table_id) {
+        TableId::Pat => ParsedSection::Pat(parse_pat(&packet).map_err(SectionParseError::Si)?),
+        TableId::Pmt => {
+            ParsedSection::Pmt(ProgramMap::from_packet(&packet).map_err(SectionParseError::Si)?)
+        }
+        TableId::NitActual | TableId::NitOther => ParsedSection::Nit(
+            NetworkInformation::from_packet(&packet).map_err(SectionParseError::Si)?,
+        ),
+        TableId::SdtActual | TableId::SdtOther => ParsedSection::Sdt(
+            ServiceDescription::from_packet(&packet).map_err(SectionParseError::Si)?,
+        ),
+        TableId::Bat => ParsedSection::Bat(
+            BouquetAssociation::from_packet(&packet).map_err(SectionParseError::Si)?,
+        ),
+        _ => ParsedSection::Other(packet),
+    })
+}
+
+//
+// -----
+
+/// Bounds-checked reading of the fields of a section payload.
+pub(crate) struct SectionReader<'a> {
+    table: &'static str,
+    data: &'a [u8],
+    /// Payload length announced by the section header
+    length: usize,
+    offset: usize,
+}
+
+impl<'a> SectionReader<'a> {
+    /// Only the payload announced by the section header is read, even if more data follows.
+    pub(crate) fn new(table: &'static str, packet: &'a Packet) -> SectionReader<'a> {
+        let length = packet.header.payload_len() as usize;
+        SectionReader {
+            table,
+            data: &packet.data[..length.min(packet.data.len())],
+            length,
+            offset: 0,
+        }
+    }
+
+    /// Return `true` once the whole payload was read.
+    ///
+    /// A payload announced longer than the data actually received isn't done, so the next read reports the truncation.
+    pub(crate) fn is_done(&self) -> bool {
+        self.offset >= self.length
+    }
+
+    pub(crate) fn error(&self) -> SiParseError {
+        SiParseError {
+            table: self.table,
+            offset: self.offset,
+        }
+    }
+
+    pub(crate) fn bytes(&mut self, length: usize) -> Result<&'a [u8], SiParseError> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + length)
+            .ok_or_else(|| self.error())?;
+        self.offset += length;
+        Ok(bytes)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, SiParseError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, SiParseError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+}
+
+//
+// -----
+
+/// Table carried by a section, as defined in `EN 300 468 V1.17.1`, p24 (table 2)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TableId {
+    Pat,
+    Cat,
+    Pmt,
+    NitActual,
+    NitOther,
+    SdtActual,
+    SdtOther,
+    Bat,
+    EitPresentFollowingActual,
+    EitPresentFollowingOther,
+    /// Index of the table among the 16 schedule tables of the actual transport stream
+    EitScheduleActual(u8),
+    /// Index of the table among the 16 schedule tables of other transport streams
+    EitScheduleOther(u8),
+    Tdt,
+    Tot,
+    /// Any table not listed here, including user defined ones
+    Other(u8),
+}
+
+impl TableId {
+    pub fn from_u8(value: u8) -> TableId {
+        match value {
+            0x00 => Self::Pat,
+            0x01 => Self::Cat,
+            0x02 => Self::Pmt,
+            0x40 => Self::NitActual,
+            0x41 => Self::NitOther,
+            0x42 => Self::SdtActual,
+            0x46 => Self::SdtOther,
+            0x4A => Self::Bat,
+            0x4E => Self::EitPresentFollowingActual,
+            0x4F => Self::EitPresentFollowingOther,
+            0x50..=0x5F => Self::EitScheduleActual(value - 0x50),
+            0x60..=0x6F => Self::EitScheduleOther(value - 0x60),
+            0x70 => Self::Tdt,
+            0x73 => Self::Tot,
+            _ => Self::Other(value),
+        }
+    }
+
+    /// Schedule indexes past 15 are wrapped, as there is no table for them.
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::Pat => 0x00,
+            Self::Cat => 0x01,
+            Self::Pmt => 0x02,
+            Self::NitActual => 0x40,
+            Self::NitOther => 0x41,
+            Self::SdtActual => 0x42,
+            Self::SdtOther => 0x46,
+            Self::Bat => 0x4A,
+            Self::EitPresentFollowingActual => 0x4E,
+            Self::EitPresentFollowingOther => 0x4F,
+            Self::EitScheduleActual(index) => 0x50 | (index & 0x0F),
+            Self::EitScheduleOther(index) => 0x60 | (index & 0x0F),
+            Self::Tdt => 0x70,
+            Self::Tot => 0x73,
+            Self::Other(value) => value,
+        }
+    }
+}
+
+//
+// -----
+
+/// Whether a service or an event is on air, as defined in `EN 300 468 V1.17.1`, p31 (table 6)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RunningStatus {
+    Undefined,
+    NotRunning,
+    StartsInFewSeconds,
+    Pausing,
+    Running,
+    OffAir,
+    Reserved(u8),
+}
+
+impl RunningStatus {
+    /// Read from the 3-bit field
+    pub fn from_u8(value: u8) -> RunningStatus {
+        match value {
+            0 => Self::Undefined,
+            1 => Self::NotRunning,
+            2 => Self::StartsInFewSeconds,
+            3 => Self::Pausing,
+            4 => Self::Running,
+            5 => Self::OffAir,
+            _ => Self::Reserved(value),
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::Undefined => 0,
+            Self::NotRunning => 1,
+            Self::StartsInFewSeconds => 2,
+            Self::Pausing => 3,
+            Self::Running => 4,
+            Self::OffAir => 5,
+            Self::Reserved(value) => value,
+        }
+    }
+
+    /// Return `true` if the service or event is currently being broadcast
+    pub fn is_on_air(self) -> bool {
+        matches!(self, Self::Running | Self::Pausing)
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpeg::PacketHeader;
+
+    #[test]
+    fn section_reader() {
+        let buf = [
+            0x00, 0xB0, 0x0C, 0x00, 0x01, 0xC1, 0x00, 0x00, // Header
+            0x00, 0x00, 0xE0, // Payload
+            0x00, 0x00, 0x00, 0x00, // CRC
+        ];
+        let mut packet = Packet::from_buf(&buf).unwrap();
+        // Header announcing 8 bytes of payload, with only 3 received
+        packet.header.section_length = 0x11;
+        let mut reader = SectionReader::new("PAT", &packet);
+
+        assert_eq!(reader.u16().unwrap(), 0x0000);
+        assert!(!reader.is_done());
+        let error = reader.u16().unwrap_err();
+        assert_eq!(error.table, "PAT");
+        assert_eq!(error.offset, 2);
+        assert_eq!(reader.u8().unwrap(), 0xE0);
+    }
+
+    #[test]
+    fn section_dispatch() {
+        let pat = [
+            0x00, 0xB0, 0x0D, 0x00, 0x01, 0xC1, 0x00, 0x00, // Header
+            0x00, 0x01, 0xE1, 0x00, // Program 1, PID 0x0100
+            0x00, 0x00, 0x00, 0x00, // CRC
+        ];
+        assert!(matches!(
+            parse_section(&pat),
+            Ok(ParsedSection::Pat(ProgramAssociation {
+                transport_stream_id: 0x0001,
+                ..
+            }))
+        ));
+
+        // Present/following EIT, no parser for it
+        let mut eit = pat;
+        eit[0] = 0x4E;
+        assert!(matches!(
+            parse_section(&eit),
+            Ok(ParsedSection::Other(Packet {
+                header: PacketHeader { table_id: 0x4E, .. },
+                ..
+            }))
+        ));
+
+        assert!(matches!(
+            parse_section(&pat[..6]),
+            Err(SectionParseError::Packet(_))
+        ));
+
+        // TDT, without the extended header
+        let tdt = [0x70, 0x70, 0x05, 0xE7, 0x82, 0x12, 0x45, 0x00];
+        assert!(matches!(
+            parse_section(&tdt),
+            Ok(ParsedSection::Other(Packet { ref data, .. })) if data.len() == 5
+        ));
+    }
+
+    #[test]
+    fn table_ids() {
+        for value in 0..=u8::MAX {
+            assert_eq!(TableId::from_u8(value).to_u8(), value);
+        }
+        assert_eq!(TableId::from_u8(0x46), TableId::SdtOther);
+        assert_eq!(TableId::from_u8(0x5F), TableId::EitScheduleActual(15));
+        assert_eq!(TableId::from_u8(0x72), TableId::Other(0x72));
+    }
+
+    #[test]
+    fn running_status() {
+        for value in 0..8 {
+            assert_eq!(RunningStatus::from_u8(value).to_u8(), value);
+        }
+        assert_eq!(RunningStatus::from_u8(4), RunningStatus::Running);
+        assert!(!RunningStatus::from_u8(1).is_on_air());
+    }
+}
@@ -1,7 +1,24 @@
-use crate::mpeg::{Packet, descriptors::Descriptor};
+use crate::{
+    error::SiParseError,
+    mpeg::{
+        Packet, Pid,
+        descriptors::{
+            Descriptor, DescriptorLoop, terrestrial_delivery_system::TerrestrialDeliverySystem,
+        },
+    },
+    si::{SectionReader, TableId},
+};
+
+/// "Network Information Table" usual packet ID, as defined in `EN 300 468 V1.17.1`, p23
+///
+/// The actual PID is the one given by the PAT for program 0.
+pub const PID: Pid = Pid::NIT;
 
 /// "Network Information Section - Actual network" table ID, as defined in `EN 300 468 V1.17.1`, p24
-pub const ACTUAL_NETWORK_TABLE_ID: u8 = 0x40;
+pub const ACTUAL_NETWORK_TABLE_ID: TableId = TableId::NitActual;
+
+/// "Network Information Section - Other network" table ID, as defined in `EN 300 468 V1.17.1`, p24
+pub const OTHER_NETWORK_TABLE_ID: TableId = TableId::NitOther;
 
 /// NIT describes all services that are available in neighboring area. It contains a list of transponders and associated services
 #[derive(Debug, Clone)]
@@ -19,52 +36,25 @@ pub struct NitElement {
 
 impl NetworkInformation {
     // ETSI EN 300 468 page 27
-    pub fn from_packet(packet: &Packet) -> NetworkInformation {
-        let mut current_offset = 0;
-
-        let _reserved = packet.data[current_offset] & 0b1111_0000;
-        let network_descriptors_length = u16::from_be_bytes([
-            packet.data[current_offset] & 0b0000_1111,
-            packet.data[current_offset + 1],
-        ]);
-        current_offset += 2;
+    pub fn from_packet(packet: &Packet) -> Result<NetworkInformation, SiParseError> {
+        let mut reader = SectionReader::new("NIT", packet);
 
-        let network_descriptors = Descriptor::read_many(
-            &packet.data[current_offset..current_offset + network_descriptors_length as usize],
-        );
-        current_offset += network_descriptors_length as usize;
+        // Top 4 bits of the lengths are reserved
+        let network_descriptors_length = reader.u16()? & 0x0FFF;
+        let network_descriptors =
+            Descriptor::read_many(reader.bytes(network_descriptors_length as usize)?);
 
-        let _reserved = packet.data[current_offset] & 0b1111_0000;
-        // let transport_stream_loop_length = u16::from_be_bytes([
-        //     packet.data[current_offset] & 0b0000_1111,
-        //     packet.data[current_offset + 1],
-        // ]);
-        current_offset += 2;
+        // The loop runs until the end of the section anyway, so its length isn't used
+        let _transport_stream_loop_length = reader.u16()? & 0x0FFF;
 
         let mut elements = Vec::new();
 
-        // TODO: I'm assuming I have to use payload_len instead of data.len because of CRC32 at the end ? Should check that, maybe have a CRC32 field in Packet
-        while (current_offset as u16) < packet.header.payload_len() {
-            let transport_stream_id =
-                u16::from_be_bytes([packet.data[current_offset], packet.data[current_offset + 1]]);
-            current_offset += 2;
-
-            let original_network_id =
-                u16::from_be_bytes([packet.data[current_offset], packet.data[current_offset + 1]]);
-            current_offset += 2;
-
-            let _reserved = packet.data[current_offset] & 0b1111_0000;
-            let transport_descriptors_length = u16::from_be_bytes([
-                packet.data[current_offset] & 0b0000_1111,
-                packet.data[current_offset + 1],
-            ]);
-            current_offset += 2;
-
-            let transport_descriptors = Descriptor::read_many(
-                &packet.data
-                    [current_offset..current_offset + transport_descriptors_length as usize],
-            );
-            current_offset += transport_descriptors_length as usize;
+        while !reader.is_done() {
+            let transport_stream_id = reader.u16()?;
+            let original_network_id = reader.u16()?;
+            let transport_descriptors_length = reader.u16()? & 0x0FFF;
+            let transport_descriptors =
+                Descriptor::read_many(reader.bytes(transport_descriptors_length as usize)?);
 
             elements.push(NitElement {
                 transport_stream_id,
@@ -73,9 +63,32 @@ impl NetworkInformation {
             });
         }
 
-        NetworkInformation {
+        Ok(NetworkInformation {
             network_descriptors,
             elements,
+        })
+    }
+
+    /// Parse a table spread over multiple sections, like the ones returned by a [SectionAssembler](crate::mpeg::assembler::SectionAssembler).
+    pub fn from_packets(packets: &[Packet]) -> Result<NetworkInformation, SiParseError> {
+        let mut sections = packets.iter().map(Self::from_packet);
+        let mut nit = sections.next().ok_or(SiParseError {
+            table: "NIT",
+            offset: 0,
+        })??;
+        for section in sections {
+            let section = section?;
+            nit.network_descriptors.extend(section.network_descriptors);
+            nit.elements.extend(section.elements);
         }
+        Ok(nit)
+    }
+
+    /// Iterate over the terrestrial delivery system descriptors of all transport streams in this network.
+    pub fn terrestrial_delivery_systems(&self) -> impl Iterator<Item = &TerrestrialDeliverySystem> {
+        self.elements.iter().flat_map(|e| {
+            e.transport_descriptors
+                .filter::<TerrestrialDeliverySystem>()
+        })
     }
 }
@@ -1,12 +1,16 @@
 // ISO/IEC 13818-1 page 61
 
-use crate::mpeg::Packet;
+use crate::{
+    error::SiParseError,
+    mpeg::{Packet, Pid},
+    si::{SectionReader, TableId},
+};
 
 /// "Program Association Table" fixed packet ID, as defined in `EN 300 468 V1.17.1`, p23
-pub const PID: u16 = 0x0000;
+pub const PID: Pid = Pid::PAT;
 
 /// "Program Association Section" table ID, as defined in `EN 300 468 V1.17.1`, p24
-pub const TABLE_ID: u8 = 0x00;
+pub const TABLE_ID: TableId = TableId::Pat;
 
 #[derive(Debug)]
 pub struct PatElement {
@@ -14,36 +18,37 @@ pub struct PatElement {
     pub value: PatValue,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum PatValue {
-    Network(u16),
-    ProgramMap(u16),
+    /// PID of the NIT, which is usually [`nit::PID`](crate::si::nit::PID) in DVB.
+    Network(Pid),
+    ProgramMap(Pid),
 }
 
-/// Program Association Table
-pub fn parse_pat(packet: &Packet) -> Vec<PatElement> {
-    // let transport_stream_id = packet.header.identifier;
+/// Parsed Program Association Table, listing the programs of a transport stream.
+#[derive(Debug)]
+pub struct ProgramAssociation {
+    pub transport_stream_id: u16,
+    /// Changes whenever the table is updated
+    pub version: u8,
+    pub elements: Vec<PatElement>,
+}
 
+/// Program Association Table
+pub fn parse_pat(packet: &Packet) -> Result<ProgramAssociation, SiParseError> {
     let mut elements = Vec::new();
 
-    let mut current_offset = 0;
-    // Removing 5 bytes after the section length field in header and 4 bytes of CRC.
-    while (current_offset as u16) < packet.header.payload_len() {
-        let program_number =
-            u16::from_be_bytes([packet.data[current_offset], packet.data[current_offset + 1]]);
-        current_offset += 2;
-        let _another_reserved = packet.data[current_offset] & 0b1110_0000;
-        let value = u16::from_be_bytes([
-            packet.data[current_offset] & 0b0001_1111,
-            packet.data[current_offset + 1],
-        ]);
-        current_offset += 2;
+    let mut reader = SectionReader::new("PAT", packet);
+    while !reader.is_done() {
+        let program_number = reader.u16()?;
+        // Top 3 bits are reserved
+        let value = Pid::from_bits(reader.u16()?);
 
         elements.push(PatElement {
             program_number,
             value: {
+                // Program 0 is reserved for the network PID, ISO/IEC 13818-1 p62
                 if program_number == 0 {
-                    // TODO: Apparently if this is 16 this really isn't the network ID
                     PatValue::Network(value)
                 } else {
                     PatValue::ProgramMap(value)
@@ -54,5 +59,45 @@ pub fn parse_pat(packet: &Packet) -> Vec<PatElement> {
 
     // CRC here
 
-    elements
+    Ok(ProgramAssociation {
+        transport_stream_id: packet.header.identifier,
+        version: packet.header.version_number,
+        elements,
+    })
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_pid() {
+        // PAT with the usual DVB network PID and two programs
+        let buf = [
+            0x00, 0xB0, 0x15, 0x00, 0x01, 0xC1, 0x00, 0x00, // Header
+            0x00, 0x00, 0xE0, 0x10, // Network, PID 0x0010
+            0x00, 0x01, 0xE1, 0x00, // Program 1, PID 0x0100
+            0x00, 0x02, 0xE2, 0x00, // Program 2, PID 0x0200
+            0xEC, 0x0A, 0xFE, 0xBC, // CRC
+        ];
+        let pat = parse_pat(&Packet::from_buf(&buf).unwrap()).unwrap();
+        assert_eq!(pat.transport_stream_id, 0x0001);
+        assert_eq!(pat.version, 0);
+
+        let elements = pat.elements;
+        assert_eq!(elements.len(), 3);
+        assert_eq!(elements[0].program_number, 0);
+        assert_eq!(elements[0].value, PatValue::Network(Pid::NIT));
+        assert_eq!(
+            elements[1].value,
+            PatValue::ProgramMap(Pid::from_bits(0x0100))
+        );
+        assert_eq!(
+            elements[2].value,
+            PatValue::ProgramMap(Pid::from_bits(0x0200))
+        );
+    }
 }
@@ -1,9 +1,23 @@
-use crate::mpeg::{Packet, descriptors::Descriptor};
+use crate::{
+    error::SiParseError,
+    mpeg::{
+        Packet,
+        descriptors::{
+            Descriptor, DescriptorLoop, ac3, enhanced_ac3,
+            extension::AC4_TAG_EXTENSION,
+            registration::{
+                AC3_FORMAT_IDENTIFIER, AC4_FORMAT_IDENTIFIER, ENHANCED_AC3_FORMAT_IDENTIFIER,
+                Registration,
+            },
+        },
+    },
+    si::{SectionReader, TableId},
+};
 
 /// Program Map Section table ID, as defined in `EN 300 468 V1.17.1`, p24
-pub const TABLE_ID: u8 = 0x02;
+pub const TABLE_ID: TableId = TableId::Pmt;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ProgramMap {
     pub program_number: u16,
     pub pcr_pid: u16,
@@ -11,7 +25,7 @@ pub struct ProgramMap {
     pub elementary_streams: Vec<ElementaryStream>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ElementaryStream {
     pub stream_type: StreamType,
     pub elementary_pid: u16,
@@ -115,61 +129,205 @@ impl StreamType {
         }
     }
 
-    pub fn is_video(self) -> bool {
+    /// What kind of content is carried, as far as the stream type alone tells.
+    ///
+    /// Private streams may carry audio or subtitles, see [`ElementaryStream::category`] to look into their descriptors.
+    pub fn category(self) -> StreamCategory {
+        match self {
+            StreamType::IsoIec11172Video
+            | StreamType::ItuTRecH262IsoIec13818_2VideoOrIsoIec11172_2ConstrainedParameterVideoStream
+            | StreamType::IsoIec14496_2Visual
+            | StreamType::IsoIec14496_10AVCVideo
+            | StreamType::IsoIec23008_2H265 => StreamCategory::Video,
+            StreamType::IsoIec11172Audio
+            | StreamType::IsoIec13818_3Audio
+            | StreamType::IsoIec13818_7AudioWithAdtsTransportSyntax
+            | StreamType::IsoIec14496_3AudioWithTheLatmTransportSyntaxAsDefinedInIsoIec14496_3Amd1 => {
+                StreamCategory::Audio
+            }
+            StreamType::ItuTRecH2220IsoIec13818_1PrivateSections
+            | StreamType::ItuTRecH2220IsoIec13818_1PESPacketsContainingPrivateData
+            | StreamType::UserPrivate(_) => StreamCategory::Private,
+            StreamType::ItuTIsoIecReserved | StreamType::ItuTRecH2220IsoIec13818_1Reserved(_) => {
+                StreamCategory::Reserved
+            }
+            _ => StreamCategory::Data,
+        }
+    }
+
+    /// Short human-readable name of the codec or format, like `"H.264/AVC Video"`.
+    pub fn codec_name(self) -> &'static str {
         match self {
-            StreamType::IsoIec11172Video => {}
-            StreamType::ItuTRecH262IsoIec13818_2VideoOrIsoIec11172_2ConstrainedParameterVideoStream => {}
-            StreamType::IsoIec14496_10AVCVideo => {}
-            StreamType::IsoIec23008_2H265 => {}
-            _ => return false
+            StreamType::ItuTIsoIecReserved => "Reserved",
+            StreamType::IsoIec11172Video => "MPEG-1 Video",
+            StreamType::ItuTRecH262IsoIec13818_2VideoOrIsoIec11172_2ConstrainedParameterVideoStream => "MPEG-2 Video",
+            StreamType::IsoIec11172Audio => "MPEG-1 Audio",
+            StreamType::IsoIec13818_3Audio => "MPEG-2 Audio",
+            StreamType::ItuTRecH2220IsoIec13818_1PrivateSections => "Private Sections",
+            StreamType::ItuTRecH2220IsoIec13818_1PESPacketsContainingPrivateData => "Private Data",
+            StreamType::IsoIec13522Mheg => "MHEG",
+            StreamType::ItuTRecH2220IsoIec13818_1AnnexADsmCC => "DSM-CC",
+            StreamType::ItuTRecH2221 => "H.222.1",
+            StreamType::IsoIec13818_6TypeA => "DSM-CC Multi-Protocol Encapsulation",
+            StreamType::IsoIec13818_6TypeB => "DSM-CC U-N Messages",
+            StreamType::IsoIec13818_6TypeC => "DSM-CC Stream Descriptors",
+            StreamType::IsoIec13818_6TypeD => "DSM-CC Sections",
+            StreamType::ItuTRecH2220IsoIec13818_1Auxiliary => "Auxiliary",
+            StreamType::IsoIec13818_7AudioWithAdtsTransportSyntax => "AAC (ADTS)",
+            StreamType::IsoIec14496_2Visual => "MPEG-4 Visual",
+            StreamType::IsoIec14496_3AudioWithTheLatmTransportSyntaxAsDefinedInIsoIec14496_3Amd1 => "AAC (LATM)",
+            StreamType::IsoIec14496_1SlPacketizedStreamOrFlexMuxStreamCarriedInPesPackets
+            | StreamType::IsoIec14496_1SlPacketizedStreamOrFlexMusStreamCarriedInIsoIec14496Sections => {
+                "MPEG-4 SL/FlexMux"
+            }
+            StreamType::IsoIec13818_6SynchronizedDownloadProtocol => "DSM-CC Synchronized Download",
+            StreamType::IsoIec14496_10AVCVideo => "H.264/AVC Video",
+            StreamType::IsoIec23008_2H265 => "H.265/HEVC Video",
+            StreamType::ItuTRecH2220IsoIec13818_1Reserved(_) => "Reserved",
+            StreamType::UserPrivate(_) => "User Private",
         }
-        true
+    }
+
+    /// Audio streams that are identified by their stream type alone
+    pub fn is_audio(self) -> bool {
+        self.category() == StreamCategory::Audio
+    }
+
+    pub fn is_video(self) -> bool {
+        self.category() == StreamCategory::Video
+    }
+}
+
+/// Broad kind of content of an elementary stream, see [`StreamType::category`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StreamCategory {
+    Video,
+    Audio,
+    /// DVB subtitles
+    Subtitle,
+    /// Teletext, carousels, and everything else that isn't directly played back
+    Data,
+    /// Content can only be told from the descriptors of the stream
+    Private,
+    Reserved,
+}
+
+impl ElementaryStream {
+    /// For private data streams, return the tag of the descriptor telling which Dolby codec (AC-3, E-AC-3, AC-4) is carried.
+    ///
+    /// Without a dedicated descriptor, the format identifier of a registration descriptor is used, like ATSC does.
+    /// The tag of the matching dedicated descriptor is returned in this case.
+    pub fn dolby_audio_type(&self) -> Option<u8> {
+        if self.stream_type.category() != StreamCategory::Private {
+            return None;
+        }
+
+        // Same strategy as w_scan2 scan.c parse_pmt, AC-3 wins over anything else
+        let mut audio_type = None;
+        for descriptor in &self.descriptors {
+            match descriptor {
+                Descriptor::Ac3(_) => return Some(descriptor.descriptor_id()),
+                Descriptor::EnhancedAc3(_) => audio_type = Some(descriptor.descriptor_id()),
+                Descriptor::Extension(e) if e.tag_extension() == AC4_TAG_EXTENSION => {
+                    audio_type = Some(AC4_TAG_EXTENSION)
+                }
+                _ => {}
+            }
+        }
+        audio_type.or_else(|| {
+            self.descriptors
+                .filter::<Registration>()
+                .find_map(|r| match r.format_identifier {
+                    AC3_FORMAT_IDENTIFIER => Some(ac3::DESCRIPTOR_ID),
+                    ENHANCED_AC3_FORMAT_IDENTIFIER => Some(enhanced_ac3::DESCRIPTOR_ID),
+                    AC4_FORMAT_IDENTIFIER => Some(AC4_TAG_EXTENSION),
+                    _ => None,
+                })
+        })
+    }
+
+    /// Like [`StreamType::category`], but private streams are classified from their descriptors when possible.
+    pub fn category(&self) -> StreamCategory {
+        match self.stream_type.category() {
+            StreamCategory::Private if self.dolby_audio_type().is_some() => StreamCategory::Audio,
+            StreamCategory::Private if self.is_subtitle() => StreamCategory::Subtitle,
+            StreamCategory::Private if self.is_teletext() => StreamCategory::Data,
+            category => category,
+        }
+    }
+
+    /// Like [`StreamType::codec_name`], but the Dolby codec of private streams is named when known.
+    pub fn codec_name(&self) -> &'static str {
+        match self.dolby_audio_type() {
+            Some(ac3::DESCRIPTOR_ID) => "AC-3",
+            Some(enhanced_ac3::DESCRIPTOR_ID) => "E-AC-3",
+            Some(AC4_TAG_EXTENSION) => "AC-4",
+            _ => self.stream_type.codec_name(),
+        }
+    }
+
+    pub fn is_audio(&self) -> bool {
+        self.category() == StreamCategory::Audio
+    }
+
+    pub fn is_subtitle(&self) -> bool {
+        self.descriptors
+            .iter()
+            .any(|d| matches!(d, Descriptor::Subtitling(_)))
+    }
+
+    pub fn is_teletext(&self) -> bool {
+        self.descriptors
+            .iter()
+            .any(|d| matches!(d, Descriptor::Teletext(_)))
     }
 }
 
 impl ProgramMap {
+    /// PID carrying the Program Clock Reference. This may or may not be the same as the video stream's.
+    pub fn pcr_pid(&self) -> u16 {
+        self.pcr_pid
+    }
+
+    pub fn video_streams(&self) -> impl Iterator<Item = &ElementaryStream> {
+        self.elementary_streams
+            .iter()
+            .filter(|e| e.stream_type.is_video())
+    }
+
+    pub fn audio_streams(&self) -> impl Iterator<Item = &ElementaryStream> {
+        self.elementary_streams.iter().filter(|e| e.is_audio())
+    }
+
+    pub fn subtitle_streams(&self) -> impl Iterator<Item = &ElementaryStream> {
+        self.elementary_streams.iter().filter(|e| e.is_subtitle())
+    }
+
+    pub fn teletext_streams(&self) -> impl Iterator<Item = &ElementaryStream> {
+        self.elementary_streams.iter().filter(|e| e.is_teletext())
+    }
+
     // ISO/IEC 13818-1 page 64
-    pub fn from_packet(packet: &Packet) -> ProgramMap {
-        let _reserved_1 = packet.data[0] & 0b1110_0000;
-        let pcr_pid = u16::from_be_bytes([packet.data[0] & 0b0001_1111, packet.data[1]]);
-        let _reserved_2 = packet.data[2] & 0b1111_0000;
-        assert_eq!((packet.data[2] as u16) & 0b0000_1100, 0);
-        let program_info_length =
-            u16::from_be_bytes([packet.data[2] & 0b0000_0011, packet.data[3]]);
+    pub fn from_packet(packet: &Packet) -> Result<ProgramMap, SiParseError> {
+        let mut reader = SectionReader::new("PMT", packet);
+
+        // Top 3 bits are reserved
+        let pcr_pid = reader.u16()? & 0x1FFF;
+        // Top 4 bits are reserved, and the 2 following ones are always cleared as lengths can't exceed 0x3FF
+        let program_info_length = reader.u16()? & 0x03FF;
 
         // Parse descriptors
         // TODO: Not sure what these descriptors may contain as I've never seen any here
-        let mut current_offset = 4;
-        let program_info_descriptors = Descriptor::read_many(
-            &packet.data[current_offset..current_offset + program_info_length as usize],
-        );
-        current_offset += program_info_length as usize;
+        let program_info_descriptors =
+            Descriptor::read_many(reader.bytes(program_info_length as usize)?);
 
         let mut elementary_streams = Vec::new();
 
-        while (current_offset as u16) < packet.header.payload_len() {
-            let stream_type = packet.data[current_offset];
-            current_offset += 1;
-
-            let _reserved_a = packet.data[current_offset] & 0b1110_0000;
-            let elementary_pid = u16::from_be_bytes([
-                packet.data[current_offset] & 0b0001_1111,
-                packet.data[current_offset + 1],
-            ]);
-            current_offset += 2;
-
-            let _reserved_b = packet.data[current_offset] & 0b1111_0000;
-            assert_eq!((packet.data[current_offset] as u16) & 0b0000_1100, 0);
-            let es_info_length = u16::from_be_bytes([
-                packet.data[current_offset] & 0b0000_0011,
-                packet.data[current_offset + 1],
-            ]);
-            current_offset += 2;
-
-            let descriptors = Descriptor::read_many(
-                &packet.data[current_offset..current_offset + es_info_length as usize],
-            );
-            current_offset += es_info_length as usize;
+        while !reader.is_done() {
+            let stream_type = reader.u8()?;
+            let elementary_pid = reader.u16()? & 0x1FFF;
+            let es_info_length = reader.u16()? & 0x03FF;
+            let descriptors = Descriptor::read_many(reader.bytes(es_info_length as usize)?);
 
             elementary_streams.push(ElementaryStream {
                 stream_type: StreamType::from_u8(stream_type),
@@ -178,11 +336,11 @@ impl ProgramMap {
             });
         }
 
-        ProgramMap {
+        Ok(ProgramMap {
             program_number: packet.header.identifier,
             pcr_pid,
             program_info_descriptors,
             elementary_streams,
-        }
+        })
     }
 }
@@ -1,53 +1,73 @@
-use crate::mpeg::{Packet, descriptors::Descriptor};
+use crate::{
+    error::SiParseError,
+    mpeg::{
+        Packet, Pid,
+        descriptors::{Descriptor, DescriptorLoop, service::Service as ServiceDescriptor},
+    },
+    si::{RunningStatus, SectionReader, TableId},
+};
 
 /// "Service Definition Table" fixed packet ID, as defined in `EN 300 468 V1.17.1`, p23
-pub const PID: u16 = 0x0011;
+pub const PID: Pid = Pid::SDT;
 
 /// "Service Description Section - Actual transport stream" table ID, as defined in `EN 300 468 V1.17.1`, p24
-pub const ACTUAL_TRANSPORT_TABLE_ID: u8 = 0x42;
+pub const ACTUAL_TRANSPORT_TABLE_ID: TableId = TableId::SdtActual;
 
-#[derive(Debug)]
+/// "Service Description Section - Other transport stream" table ID, as defined in `EN 300 468 V1.17.1`, p24
+pub const OTHER_TRANSPORT_TABLE_ID: TableId = TableId::SdtOther;
+
+#[derive(Debug, Clone)]
 pub struct ServiceDescription {
+    pub transport_stream_id: u16,
+    /// `true` if this describes the transport stream it was received on, `false` for another one of the network.
+    pub actual: bool,
     pub original_network_id: u16,
     pub services: Vec<Service>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Service {
     pub service_id: u16,
     pub eit_schedule: bool,
     pub eit_present_following: bool,
-    pub running_status: u8,
+    pub running_status: RunningStatus,
     pub free_ca_mode: bool,
     pub descriptors: Vec<Descriptor>,
 }
 
+impl Service {
+    /// The service descriptor, telling what this service is about. Services without one can't really be used.
+    pub fn service_descriptor(&self) -> Option<&ServiceDescriptor> {
+        self.descriptors.find::<ServiceDescriptor>()
+    }
+
+    /// Name of this service, as given by its service descriptor.
+    pub fn service_name(&self) -> Option<&str> {
+        self.service_descriptor().map(|d| d.service.as_str())
+    }
+}
+
 impl ServiceDescription {
     // ETSI EN 300 468 page 30
-    pub fn from_packet(packet: &Packet) -> Self {
-        let original_network_id = u16::from_be_bytes([packet.data[0], packet.data[1]]);
-        let _reserved = packet.data[2];
+    pub fn from_packet(packet: &Packet) -> Result<Self, SiParseError> {
+        let mut reader = SectionReader::new("SDT", packet);
+        let original_network_id = reader.u16()?;
+        let _reserved = reader.u8()?;
 
         let mut services = Vec::new();
 
-        let mut offset = 3;
-        while offset < (packet.header.payload_len() as usize) {
-            let service_id = u16::from_be_bytes([packet.data[offset], packet.data[offset + 1]]);
-            let _reserved = packet.data[offset + 2] & 0b1111_1100;
-            let eit_schedule = (packet.data[offset + 2] & 0b0000_0010) != 0;
-            let eit_present_following = (packet.data[offset + 2] & 0b0000_0001) != 0;
-            let running_status = (packet.data[offset + 3] & 0b1110_0000) >> 5;
-            let free_ca_mode = (packet.data[offset + 3] & 0b0001_0000) != 0;
-            let descriptors_length = u16::from_be_bytes([
-                packet.data[offset + 3] & 0b0000_1111,
-                packet.data[offset + 4],
-            ]);
-
-            offset += 5;
-
-            let descriptors =
-                Descriptor::read_many(&packet.data[offset..offset + descriptors_length as usize]);
-            offset += descriptors_length as usize;
+        while !reader.is_done() {
+            let service_id = reader.u16()?;
+            let flags = reader.u8()?;
+            let _reserved = flags & 0b1111_1100;
+            let eit_schedule = (flags & 0b0000_0010) != 0;
+            let eit_present_following = (flags & 0b0000_0001) != 0;
+            let status = reader.u16()?;
+            let running_status = RunningStatus::from_u8((status >> 13) as u8);
+            let free_ca_mode = (status & 0x1000) != 0;
+            let descriptors_length = status & 0x0FFF;
+
+            let descriptors = Descriptor::read_many(reader.bytes(descriptors_length as usize)?);
 
             services.push(Service {
                 service_id,
@@ -59,9 +79,66 @@ impl ServiceDescription {
             });
         }
 
-        ServiceDescription {
+        Ok(ServiceDescription {
+            transport_stream_id: packet.header.identifier,
+            actual: TableId::from_u8(packet.header.table_id) == ACTUAL_TRANSPORT_TABLE_ID,
             original_network_id,
             services,
+        })
+    }
+
+    /// Parse a table spread over multiple sections, like the ones returned by a [SectionAssembler](crate::mpeg::assembler::SectionAssembler).
+    pub fn from_packets(packets: &[Packet]) -> Result<Self, SiParseError> {
+        let mut sections = packets.iter().map(Self::from_packet);
+        let mut sdt = sections.next().ok_or(SiParseError {
+            table: "SDT",
+            offset: 0,
+        })??;
+        for section in sections {
+            sdt.services.extend(section?.services);
         }
+        Ok(sdt)
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_transport() {
+        let buf = [
+            0x46, 0xB0, 0x11, 0x00, 0x02, 0xC1, 0x00, 0x00, // Header, other TS 2
+            0x20, 0xFA, 0xFF, // Original network ID
+            0x01, 0x01, 0xFC, 0x80, 0x00, // Running service without descriptors
+            0x00, 0x00, 0x00, 0x00, // CRC
+        ];
+        let sdt = ServiceDescription::from_packet(&Packet::from_buf(&buf).unwrap()).unwrap();
+
+        assert!(!sdt.actual);
+        assert_eq!(sdt.transport_stream_id, 0x0002);
+        assert_eq!(sdt.original_network_id, 0x20FA);
+        assert_eq!(sdt.services.len(), 1);
+        assert_eq!(sdt.services[0].running_status, RunningStatus::Running);
+        assert_eq!(sdt.services[0].service_name(), None);
+    }
+
+    #[test]
+    fn truncated() {
+        let buf = [
+            0x42, 0xB0, 0x13, 0x00, 0x01, 0xC1, 0x00, 0x00, // Header
+            0x20, 0xFA, 0xFF, // Original network ID
+            0x01, 0x01, 0xFC, 0x80, 0x03, // Service with 3 bytes of descriptors
+            0x48, 0x01, // Only 2 of them fit in the section
+            0x00, 0x00, 0x00, 0x00, // CRC
+        ];
+        let error = ServiceDescription::from_packet(&Packet::from_buf(&buf).unwrap()).unwrap_err();
+        assert_eq!(error.table, "SDT");
+        assert_eq!(error.offset, 8);
+
+        assert!(ServiceDescription::from_packets(&[]).is_err());
     }
 }
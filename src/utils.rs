@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
 #[derive(Debug, Copy, Clone)]
 pub struct ValueBounds {
     pub min: u32,
@@ -8,4 +10,44 @@ impl ValueBounds {
     pub fn new(min: u32, max: u32) -> ValueBounds {
         ValueBounds { min, max }
     }
+
+    /// Return `true` if `value` is within bounds, both included.
+    pub fn contains(&self, value: u32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
+    /// Bring `value` back within bounds.
+    pub fn clamp(&self, value: u32) -> u32 {
+        value.max(self.min).min(self.max)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.max.saturating_sub(self.min)
+    }
+}
+
+impl Display for ValueBounds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}..={}", self.min, self.max)
+    }
+}
+
+//
+// -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds() {
+        let bounds = ValueBounds::new(174_000_000, 862_000_000);
+        assert!(bounds.contains(474_000_000));
+        assert!(bounds.contains(862_000_000));
+        assert!(!bounds.contains(100_000_000));
+        assert_eq!(bounds.clamp(900_000_000), 862_000_000);
+        assert_eq!(bounds.clamp(100_000_000), 174_000_000);
+        assert_eq!(bounds.width(), 688_000_000);
+        assert_eq!(bounds.to_string(), "174000000..=862000000");
+    }
 }